@@ -1,9 +1,11 @@
 use rand::Rng;
+use rust_sponge::congestion_control::controller::CongestionControlAlgorithm;
 use rust_sponge::tcp_connection::TCPConnection;
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
 use rust_sponge::util::buffer::Buffer;
 use rust_sponge::SizeT;
 use std::cmp::min;
+use std::env;
 use std::time::Instant;
 
 const len: SizeT = 100 * 1024 * 1024;
@@ -16,7 +18,12 @@ fn main() {
 }
 
 fn main_loop(reorder: bool) {
+    // optional congestion control algorithm, e.g. `tcp_benchmark cubic`
     let config = TCPConfig {
+        congestion_control: env::args()
+            .nth(1)
+            .map(|a| a.parse::<CongestionControlAlgorithm>().unwrap())
+            .unwrap_or(CongestionControlAlgorithm::Disabled),
         ..Default::default()
     };
 
@@ -23,10 +23,16 @@ fn show_usage(argv0: &str, msg: &str) {
         "   -w <winsz>      Use a window of <winsz> bytes                   {}\n\n",
         TCPConfig::MAX_PAYLOAD_SIZE
     );
+    print!("   -wmax <winsz>   Auto-tune the receive window up to <winsz>      (fixed)\n\n");
     print!(
         "   -t <tmout>      Set rt_timeout to tmout                         {}\n\n",
         TCPConfig::TIMEOUT_DFLT
     );
+    print!("   -cc <algo>      Congestion control: disabled, reno, cubic, bbr  (disabled)\n\n");
+    print!("   -ws             Negotiate window scaling (RFC 7323)             (off)\n\n");
+    print!("   -rack           RACK-TLP loss detection (RFC 8985)              (off)\n\n");
+    print!("   -nodelay        Disable Nagle's algorithm                       (nagle on)\n");
+    print!("   -cork           Hold partial segments up to the cork timeout    (off)\n\n");
     print!("   -Lu <loss>      Set uplink loss to <rate> (float in 0..1)       (no loss)\n");
     print!("   -Ld <loss>      Set downlink loss to <rate> (float in 0..1)     (no loss)\n\n");
     print!("   -h              Show this message and quit.\n\n");
@@ -45,7 +51,10 @@ fn check_argc(argc: i32, argv: &Vec<String>, curr: i32, err: &str) {
 }
 
 fn get_config(argc: i32, argv: &Vec<String>) -> (TCPConfig, FdAdapterConfig, bool) {
-    let mut c_fsm = TCPConfig::default();
+    let mut c_fsm = TCPConfig {
+        nodelay: false,
+        ..Default::default()
+    };
     let mut c_filt = FdAdapterConfig {
         source: SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
         destination: SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
@@ -65,10 +74,30 @@ fn get_config(argc: i32, argv: &Vec<String>) -> (TCPConfig, FdAdapterConfig, boo
             check_argc(argc, argv, curr, "ERROR: -w requires one argument.");
             c_fsm.recv_capacity = argv[(curr + 1) as usize].as_str().parse().unwrap();
             curr += 2;
+        } else if v.eq("-wmax") {
+            check_argc(argc, argv, curr, "ERROR: -wmax requires one argument.");
+            c_fsm.recv_capacity_max = Some(argv[(curr + 1) as usize].as_str().parse().unwrap());
+            curr += 2;
         } else if v.eq("-t") {
             check_argc(argc, argv, curr, "ERROR: -t requires one argument.");
             c_fsm.rt_timeout = argv[(curr + 1) as usize].as_str().parse().unwrap();
             curr += 2;
+        } else if v.eq("-cc") {
+            check_argc(argc, argv, curr, "ERROR: -cc requires one argument.");
+            c_fsm.congestion_control = argv[(curr + 1) as usize].as_str().parse().unwrap();
+            curr += 2;
+        } else if v.eq("-ws") {
+            c_fsm.window_scale = true;
+            curr += 1;
+        } else if v.eq("-rack") {
+            c_fsm.rack = true;
+            curr += 1;
+        } else if v.eq("-nodelay") {
+            c_fsm.nodelay = true;
+            curr += 1;
+        } else if v.eq("-cork") {
+            c_fsm.cork = true;
+            curr += 1;
         } else if v.eq("-Lu") {
             check_argc(argc, argv, curr, "ERROR: -Lu requires one argument.");
             let lossrate: f32 = argv[(curr + 1) as usize].as_str().parse().unwrap();
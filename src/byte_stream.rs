@@ -167,6 +167,28 @@ impl ByteStream {
         self.avail
     }
 
+    // buffered bytes are kept, moved to the front of the larger buffer
+    #[allow(dead_code)]
+    pub fn grow(&mut self, capacity: SizeT) {
+        if capacity <= self.capacity {
+            return;
+        }
+
+        let size = self.buffer_size();
+        let mut buffer = vec![0u8; capacity];
+        buffer[..size].copy_from_slice(self.peek_output(size).as_slice());
+        self.buffer = buffer;
+        self.read_pos = 0;
+        self.write_pos = size;
+        self.avail += capacity - self.capacity;
+        self.capacity = capacity;
+    }
+
+    #[allow(dead_code)]
+    pub fn capacity(&self) -> SizeT {
+        self.capacity
+    }
+
     #[allow(dead_code)]
     pub fn set_error(&mut self) {
         self.error = true;
@@ -0,0 +1,304 @@
+use crate::congestion_control::controller::{initial_window, CongestionControl};
+use crate::congestion_control::rate_sample::RateSample;
+use crate::SizeT;
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BbrMode {
+    Startup = 0,
+    Drain,
+    ProbeBw,
+    ProbeRtt,
+}
+
+// model-based control after BBR v1: the bottleneck bandwidth is the windowed max of the
+// delivery rate, the propagation delay is the windowed min of the rtt, and cwnd/pacing follow
+// their product. rates are in bytes per ms
+#[derive(Debug)]
+pub struct Bbr {
+    mss: SizeT,
+    cwnd: SizeT,
+    prior_cwnd: SizeT,
+    mode: BbrMode,
+    pacing_gain: f64,
+    cwnd_gain: f64,
+    btl_bw_filter: VecDeque<(u64, f64)>,
+    btl_bw: f64,
+    min_rtt: Option<SizeT>,
+    min_rtt_stamp: SizeT,
+    min_rtt_expired: bool,
+    round_count: u64,
+    next_round_delivered: SizeT,
+    round_start: bool,
+    full_bw: f64,
+    full_bw_count: u32,
+    filled_pipe: bool,
+    cycle_index: usize,
+    cycle_stamp: SizeT,
+    probe_rtt_done_stamp: Option<SizeT>,
+    total_delivered: SizeT,
+    pending_sample: Option<RateSample>,
+}
+impl Bbr {
+    pub const HIGH_GAIN: f64 = 2.885;
+    pub const BTL_BW_FILTER_ROUNDS: u64 = 10;
+    pub const MIN_RTT_FILTER_MS: SizeT = 10000;
+    pub const PROBE_RTT_DURATION_MS: SizeT = 200;
+    pub const MIN_CWND_SEGMENTS: SizeT = 4;
+    pub const PACING_GAIN_CYCLE: [f64; 8] = [1.25, 0.75, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+
+    #[allow(dead_code)]
+    pub fn new(mss: SizeT) -> Bbr {
+        Bbr {
+            mss,
+            cwnd: initial_window(mss),
+            prior_cwnd: 0,
+            mode: BbrMode::Startup,
+            pacing_gain: Bbr::HIGH_GAIN,
+            cwnd_gain: Bbr::HIGH_GAIN,
+            btl_bw_filter: VecDeque::new(),
+            btl_bw: 0.0,
+            min_rtt: None,
+            min_rtt_stamp: 0,
+            min_rtt_expired: false,
+            round_count: 0,
+            next_round_delivered: 0,
+            round_start: false,
+            full_bw: 0.0,
+            full_bw_count: 0,
+            filled_pipe: false,
+            cycle_index: 0,
+            cycle_stamp: 0,
+            probe_rtt_done_stamp: None,
+            total_delivered: 0,
+            pending_sample: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn mode(&self) -> BbrMode {
+        self.mode
+    }
+
+    #[allow(dead_code)]
+    pub fn btl_bw(&self) -> f64 {
+        self.btl_bw
+    }
+
+    #[allow(dead_code)]
+    pub fn min_rtt(&self) -> Option<SizeT> {
+        self.min_rtt
+    }
+
+    #[allow(dead_code)]
+    pub fn round_count(&self) -> u64 {
+        self.round_count
+    }
+
+    #[allow(dead_code)]
+    pub fn filled_pipe(&self) -> bool {
+        self.filled_pipe
+    }
+
+    #[allow(dead_code)]
+    pub fn pacing_gain(&self) -> f64 {
+        self.pacing_gain
+    }
+
+    // estimated bandwidth-delay product in bytes
+    #[allow(dead_code)]
+    pub fn bdp(&self) -> Option<SizeT> {
+        match self.min_rtt {
+            Some(rtt) if self.btl_bw > 0.0 => Some((self.btl_bw * rtt as f64) as SizeT),
+            _ => None,
+        }
+    }
+
+    fn min_cwnd(&self) -> SizeT {
+        Bbr::MIN_CWND_SEGMENTS * self.mss
+    }
+
+    fn target_cwnd(&self, gain: f64) -> SizeT {
+        match self.bdp() {
+            Some(bdp) => max((gain * bdp as f64) as SizeT, self.min_cwnd()),
+            None => initial_window(self.mss),
+        }
+    }
+
+    fn update_model(&mut self, rs: &RateSample, now: SizeT) {
+        self.total_delivered = rs.total_delivered;
+
+        self.round_start = false;
+        if rs.prior_delivered >= self.next_round_delivered {
+            self.next_round_delivered = rs.total_delivered;
+            self.round_count += 1;
+            self.round_start = true;
+        }
+
+        if let Some(rate) = rs.delivery_rate() {
+            if rate >= self.btl_bw || !rs.is_app_limited {
+                self.btl_bw_filter.push_back((self.round_count, rate));
+            }
+        }
+        while let Some((round, _)) = self.btl_bw_filter.front() {
+            if round + Bbr::BTL_BW_FILTER_ROUNDS > self.round_count {
+                break;
+            }
+            self.btl_bw_filter.pop_front();
+        }
+        self.btl_bw = self
+            .btl_bw_filter
+            .iter()
+            .fold(0.0, |acc, (_, rate)| if *rate > acc { *rate } else { acc });
+
+        self.min_rtt_expired =
+            self.min_rtt.is_some() && now > self.min_rtt_stamp + Bbr::MIN_RTT_FILTER_MS;
+        if let Some(rtt) = rs.rtt {
+            if self.min_rtt.is_none() || rtt <= self.min_rtt.unwrap() || self.min_rtt_expired {
+                self.min_rtt = Some(rtt);
+                self.min_rtt_stamp = now;
+            }
+        }
+
+        // startup ends once the bandwidth stops growing by 25% for three rounds
+        if !self.filled_pipe && self.round_start && !rs.is_app_limited {
+            if self.btl_bw >= self.full_bw * 1.25 {
+                self.full_bw = self.btl_bw;
+                self.full_bw_count = 0;
+            } else {
+                self.full_bw_count += 1;
+                if self.full_bw_count >= 3 {
+                    self.filled_pipe = true;
+                }
+            }
+        }
+    }
+
+    fn enter_probe_bw(&mut self, now: SizeT) {
+        self.mode = BbrMode::ProbeBw;
+        self.cwnd_gain = 2.0;
+        self.cycle_index = 2;
+        self.cycle_stamp = now;
+        self.pacing_gain = Bbr::PACING_GAIN_CYCLE[self.cycle_index];
+    }
+
+    fn update_mode(&mut self, bytes_in_flight: SizeT, now: SizeT) {
+        if self.mode == BbrMode::Startup && self.filled_pipe {
+            self.mode = BbrMode::Drain;
+            self.pacing_gain = 1.0 / Bbr::HIGH_GAIN;
+            self.cwnd_gain = Bbr::HIGH_GAIN;
+        }
+        if self.mode == BbrMode::Drain && bytes_in_flight <= self.target_cwnd(1.0) {
+            self.enter_probe_bw(now);
+        }
+
+        if self.mode == BbrMode::ProbeBw {
+            if let Some(rtt) = self.min_rtt {
+                if now - self.cycle_stamp > rtt {
+                    self.cycle_index = (self.cycle_index + 1) % Bbr::PACING_GAIN_CYCLE.len();
+                    self.cycle_stamp = now;
+                    self.pacing_gain = Bbr::PACING_GAIN_CYCLE[self.cycle_index];
+                }
+            }
+        }
+
+        if self.mode != BbrMode::ProbeRtt && self.min_rtt_expired {
+            self.mode = BbrMode::ProbeRtt;
+            self.pacing_gain = 1.0;
+            self.cwnd_gain = 1.0;
+            self.prior_cwnd = self.cwnd;
+            self.probe_rtt_done_stamp = None;
+        }
+        if self.mode == BbrMode::ProbeRtt {
+            if self.probe_rtt_done_stamp.is_none() && bytes_in_flight <= self.min_cwnd() {
+                self.probe_rtt_done_stamp = Some(now + Bbr::PROBE_RTT_DURATION_MS);
+            } else if self.probe_rtt_done_stamp.is_some()
+                && now >= self.probe_rtt_done_stamp.unwrap()
+            {
+                self.min_rtt_stamp = now;
+                self.min_rtt_expired = false;
+                self.cwnd = max(self.cwnd, self.prior_cwnd);
+                if self.filled_pipe {
+                    self.enter_probe_bw(now);
+                } else {
+                    self.mode = BbrMode::Startup;
+                    self.pacing_gain = Bbr::HIGH_GAIN;
+                    self.cwnd_gain = Bbr::HIGH_GAIN;
+                }
+            }
+        }
+    }
+}
+impl CongestionControl for Bbr {
+    fn name(&self) -> &'static str {
+        "bbr"
+    }
+
+    // the model keeps learning through loss, only the window growth differs in recovery
+    fn on_ack(
+        &mut self,
+        acked_bytes: SizeT,
+        bytes_in_flight: SizeT,
+        in_recovery: bool,
+        now: SizeT,
+    ) {
+        if let Some(rs) = self.pending_sample.take() {
+            self.update_model(&rs, now);
+        }
+        self.update_mode(bytes_in_flight, now);
+
+        let target = self.target_cwnd(self.cwnd_gain);
+        if self.mode == BbrMode::ProbeRtt {
+            self.cwnd = min(self.cwnd, self.min_cwnd());
+            return;
+        }
+        if in_recovery {
+            // packet conservation: as much goes out as was delivered
+            self.cwnd = max(self.cwnd, bytes_in_flight + acked_bytes);
+        } else if self.filled_pipe {
+            self.cwnd = min(self.cwnd + acked_bytes, target);
+        } else if self.cwnd < target || self.total_delivered < initial_window(self.mss) {
+            self.cwnd += acked_bytes;
+        }
+        self.cwnd = max(self.cwnd, self.min_cwnd());
+    }
+
+    fn on_loss(&mut self, bytes_in_flight: SizeT, _now: SizeT) {
+        // packet conservation: no more than what is still in flight
+        self.prior_cwnd = self.cwnd;
+        self.cwnd = max(min(self.cwnd, bytes_in_flight), self.min_cwnd());
+    }
+
+    fn on_timeout(&mut self, _bytes_in_flight: SizeT, _now: SizeT) {
+        self.prior_cwnd = self.cwnd;
+        self.cwnd = self.mss;
+    }
+
+    fn on_recovery_end(&mut self, _now: SizeT) {
+        self.cwnd = max(self.cwnd, self.prior_cwnd);
+    }
+
+    fn on_rate_sample(&mut self, rs: &RateSample) {
+        self.pending_sample = Some(*rs);
+    }
+
+    fn pacing_rate(&self) -> Option<f64> {
+        if self.btl_bw > 0.0 {
+            Some(self.pacing_gain * self.btl_bw)
+        } else {
+            None
+        }
+    }
+
+    fn cwnd(&self) -> SizeT {
+        self.cwnd
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "bbr(mode={:?}, cwnd={}, btl_bw={:.3}B/ms, min_rtt={:?}, pacing_gain={:.2}, round={})",
+            self.mode, self.cwnd, self.btl_bw, self.min_rtt, self.pacing_gain, self.round_count
+        )
+    }
+}
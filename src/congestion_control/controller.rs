@@ -0,0 +1,93 @@
+use crate::congestion_control::bbr::Bbr;
+use crate::congestion_control::cubic::Cubic;
+use crate::congestion_control::rate_sample::RateSample;
+use crate::congestion_control::reno::Reno;
+use crate::SizeT;
+use std::cmp::{max, min};
+use std::fmt;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+// consulted by TCPSender alongside the peer's advertised window,
+// all window values are in bytes and all times are in ms of sender tick time
+pub trait CongestionControl: Debug + Send {
+    fn name(&self) -> &'static str;
+
+    // every valid ack, with the bytes it newly acknowledged (cumulatively). in_recovery is set
+    // while loss recovery is going on, when controllers may hold cwnd rather than grow it
+    fn on_ack(&mut self, acked_bytes: SizeT, bytes_in_flight: SizeT, in_recovery: bool, now: SizeT);
+
+    // loss detected without the retransmission timer firing
+    fn on_loss(&mut self, bytes_in_flight: SizeT, now: SizeT);
+
+    // retransmission timer expired
+    fn on_timeout(&mut self, bytes_in_flight: SizeT, now: SizeT);
+
+    // everything outstanding at the last on_loss has been acked
+    fn on_recovery_end(&mut self, _now: SizeT) {}
+
+    // round trip time samples in ms
+    fn on_rtt_sample(&mut self, _rtt: SizeT) {}
+
+    // called ahead of on_ack whenever the ack delivered data
+    fn on_rate_sample(&mut self, _rs: &RateSample) {}
+
+    // bytes per ms, for controllers that pace their transmissions
+    fn pacing_rate(&self) -> Option<f64> {
+        None
+    }
+
+    fn cwnd(&self) -> SizeT;
+
+    fn summary(&self) -> String {
+        format!("{}(cwnd={})", self.name(), self.cwnd())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CongestionControlAlgorithm {
+    Disabled = 0,
+    Reno,
+    Cubic,
+    Bbr,
+}
+impl CongestionControlAlgorithm {
+    #[allow(dead_code)]
+    pub fn build(&self, mss: SizeT) -> Option<Box<dyn CongestionControl>> {
+        match self {
+            CongestionControlAlgorithm::Disabled => None,
+            CongestionControlAlgorithm::Reno => Some(Box::new(Reno::new(mss))),
+            CongestionControlAlgorithm::Cubic => Some(Box::new(Cubic::new(mss))),
+            CongestionControlAlgorithm::Bbr => Some(Box::new(Bbr::new(mss))),
+        }
+    }
+}
+impl fmt::Display for CongestionControlAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CongestionControlAlgorithm::Disabled => "disabled",
+            CongestionControlAlgorithm::Reno => "reno",
+            CongestionControlAlgorithm::Cubic => "cubic",
+            CongestionControlAlgorithm::Bbr => "bbr",
+        };
+        write!(f, "{}", s)
+    }
+}
+impl FromStr for CongestionControlAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disabled" | "none" => Ok(CongestionControlAlgorithm::Disabled),
+            "reno" => Ok(CongestionControlAlgorithm::Reno),
+            "cubic" => Ok(CongestionControlAlgorithm::Cubic),
+            "bbr" => Ok(CongestionControlAlgorithm::Bbr),
+            _ => Err(format!("unknown congestion control algorithm {}", s)),
+        }
+    }
+}
+
+// RFC 3390: min(4*MSS, max(2*MSS, 4380 bytes))
+pub fn initial_window(mss: SizeT) -> SizeT {
+    min(4 * mss, max(2 * mss, 4380))
+}
@@ -0,0 +1,195 @@
+use crate::congestion_control::controller::{initial_window, CongestionControl};
+use crate::SizeT;
+use std::cmp::max;
+
+// RFC 9438, windows are tracked in segments internally and reported in bytes
+#[derive(Debug)]
+pub struct Cubic {
+    mss: SizeT,
+    cwnd: f64,
+    ssthresh: f64,
+    w_max: f64,
+    w_est: f64,
+    k: f64,
+    origin_point: f64,
+    epoch_start: Option<SizeT>,
+    rtt: SizeT,
+}
+impl Cubic {
+    pub const C: f64 = 0.4;
+    pub const BETA: f64 = 0.7;
+    // additive increase of the reno-friendly estimate, 3 * (1 - beta) / (1 + beta)
+    pub const ALPHA: f64 = 3.0 * (1.0 - Cubic::BETA) / (1.0 + Cubic::BETA);
+
+    #[allow(dead_code)]
+    pub fn new(mss: SizeT) -> Cubic {
+        let iw = (initial_window(mss) / mss) as f64;
+        Cubic {
+            mss,
+            cwnd: iw,
+            ssthresh: f64::MAX,
+            w_max: 0.0,
+            w_est: 0.0,
+            k: 0.0,
+            origin_point: 0.0,
+            epoch_start: None,
+            rtt: 0,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn ssthresh(&self) -> SizeT {
+        if self.ssthresh == f64::MAX {
+            SizeT::MAX
+        } else {
+            (self.ssthresh * self.mss as f64).round() as SizeT
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn w_max(&self) -> SizeT {
+        (self.w_max * self.mss as f64).round() as SizeT
+    }
+
+    #[allow(dead_code)]
+    pub fn w_est(&self) -> SizeT {
+        (self.w_est * self.mss as f64).round() as SizeT
+    }
+
+    // time (ms) it takes the cubic function to grow back to w_max
+    #[allow(dead_code)]
+    pub fn k(&self) -> SizeT {
+        (self.k * 1000.0) as SizeT
+    }
+
+    #[allow(dead_code)]
+    pub fn epoch_start(&self) -> Option<SizeT> {
+        self.epoch_start
+    }
+
+    #[allow(dead_code)]
+    pub fn in_slow_start(&self) -> bool {
+        self.cwnd < self.ssthresh
+    }
+
+    // reno-friendly region: the cubic curve is still below what reno would reach
+    #[allow(dead_code)]
+    pub fn in_reno_friendly_region(&self, now: SizeT) -> bool {
+        match self.epoch_start {
+            Some(start) => self.w_cubic(Cubic::seconds(now - start)) < self.w_est,
+            None => false,
+        }
+    }
+
+    fn seconds(ms: SizeT) -> f64 {
+        ms as f64 / 1000.0
+    }
+
+    fn w_cubic(&self, t: f64) -> f64 {
+        Cubic::C * (t - self.k).powi(3) + self.origin_point
+    }
+
+    fn start_epoch(&mut self, now: SizeT) {
+        self.epoch_start = Some(now);
+        if self.cwnd < self.w_max {
+            self.k = ((self.w_max - self.cwnd) / Cubic::C).cbrt();
+            self.origin_point = self.w_max;
+        } else {
+            self.k = 0.0;
+            self.origin_point = self.cwnd;
+        }
+        self.w_est = self.cwnd;
+    }
+
+    fn congestion_event(&mut self) {
+        // fast convergence: release bandwidth if the saturation point is decreasing
+        if self.cwnd < self.w_max {
+            self.w_max = self.cwnd * (1.0 + Cubic::BETA) / 2.0;
+        } else {
+            self.w_max = self.cwnd;
+        }
+        self.ssthresh = (self.cwnd * Cubic::BETA).max(2.0);
+        self.epoch_start = None;
+    }
+}
+impl CongestionControl for Cubic {
+    fn name(&self) -> &'static str {
+        "cubic"
+    }
+
+    fn on_ack(
+        &mut self,
+        acked_bytes: SizeT,
+        _bytes_in_flight: SizeT,
+        in_recovery: bool,
+        now: SizeT,
+    ) {
+        if in_recovery || acked_bytes == 0 {
+            return;
+        }
+        let acked = acked_bytes as f64 / self.mss as f64;
+        if self.in_slow_start() {
+            self.cwnd += acked.min(1.0);
+            return;
+        }
+
+        if self.epoch_start.is_none() {
+            self.start_epoch(now);
+        }
+        let t = Cubic::seconds(now - self.epoch_start.unwrap());
+
+        let alpha = if self.w_est >= self.w_max {
+            1.0
+        } else {
+            Cubic::ALPHA
+        };
+        self.w_est += alpha * acked / self.cwnd;
+
+        if self.w_cubic(t) < self.w_est {
+            self.cwnd = self.w_est;
+            return;
+        }
+
+        let target = self
+            .w_cubic(t + Cubic::seconds(self.rtt))
+            .clamp(self.cwnd, 1.5 * self.cwnd);
+        self.cwnd += (target - self.cwnd) * acked / self.cwnd;
+    }
+
+    fn on_loss(&mut self, _bytes_in_flight: SizeT, _now: SizeT) {
+        self.congestion_event();
+        self.cwnd = self.ssthresh;
+    }
+
+    fn on_timeout(&mut self, _bytes_in_flight: SizeT, _now: SizeT) {
+        self.congestion_event();
+        self.cwnd = 1.0;
+    }
+
+    fn on_rtt_sample(&mut self, rtt: SizeT) {
+        self.rtt = if self.rtt == 0 {
+            rtt
+        } else {
+            max(1, (7 * self.rtt + rtt) / 8)
+        };
+    }
+
+    fn cwnd(&self) -> SizeT {
+        (self.cwnd * self.mss as f64).round() as SizeT
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "cubic(cwnd={}, ssthresh={}, w_max={}, w_est={}, k={}ms, epoch_start={})",
+            self.cwnd(),
+            self.ssthresh(),
+            self.w_max(),
+            self.w_est(),
+            self.k(),
+            match self.epoch_start {
+                Some(t) => t.to_string(),
+                None => "None".to_string(),
+            }
+        )
+    }
+}
@@ -0,0 +1,5 @@
+pub mod bbr;
+pub mod controller;
+pub mod cubic;
+pub mod rate_sample;
+pub mod reno;
@@ -0,0 +1,34 @@
+use crate::SizeT;
+use std::cmp::max;
+
+// delivery rate sample taken on each ack, following draft-cheng-iccrg-delivery-rate-estimation
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RateSample {
+    // bytes delivered (acked) over the sampling interval
+    pub delivered: SizeT,
+    // total bytes delivered by the sender when this sample was taken
+    pub total_delivered: SizeT,
+    // total bytes delivered when the most recently acked segment was sent
+    pub prior_delivered: SizeT,
+    pub send_elapsed: SizeT,
+    pub ack_elapsed: SizeT,
+    // rtt of the most recently sent segment acked, None if it was retransmitted
+    pub rtt: Option<SizeT>,
+    pub is_app_limited: bool,
+}
+impl RateSample {
+    #[allow(dead_code)]
+    pub fn interval(&self) -> SizeT {
+        max(self.send_elapsed, self.ack_elapsed)
+    }
+
+    // bytes per ms
+    #[allow(dead_code)]
+    pub fn delivery_rate(&self) -> Option<f64> {
+        if self.interval() == 0 {
+            None
+        } else {
+            Some(self.delivered as f64 / self.interval() as f64)
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::congestion_control::controller::{initial_window, CongestionControl};
+use crate::SizeT;
+use std::cmp::{max, min};
+
+// RFC 5681 slow start and congestion avoidance, with appropriate byte counting (RFC 3465, L=1)
+#[derive(Debug)]
+pub struct Reno {
+    mss: SizeT,
+    cwnd: SizeT,
+    ssthresh: SizeT,
+    bytes_acked: SizeT,
+}
+impl Reno {
+    #[allow(dead_code)]
+    pub fn new(mss: SizeT) -> Reno {
+        Reno {
+            mss,
+            cwnd: initial_window(mss),
+            ssthresh: SizeT::MAX,
+            bytes_acked: 0,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn ssthresh(&self) -> SizeT {
+        self.ssthresh
+    }
+
+    #[allow(dead_code)]
+    pub fn in_slow_start(&self) -> bool {
+        self.cwnd < self.ssthresh
+    }
+
+    fn reduce_ssthresh(&mut self, bytes_in_flight: SizeT) {
+        self.ssthresh = max(bytes_in_flight / 2, 2 * self.mss);
+        self.bytes_acked = 0;
+    }
+}
+impl CongestionControl for Reno {
+    fn name(&self) -> &'static str {
+        "reno"
+    }
+
+    fn on_ack(
+        &mut self,
+        acked_bytes: SizeT,
+        _bytes_in_flight: SizeT,
+        in_recovery: bool,
+        _now: SizeT,
+    ) {
+        // RFC 6582: cwnd stays at ssthresh until recovery is over
+        if in_recovery || acked_bytes == 0 {
+            return;
+        }
+        if self.in_slow_start() {
+            self.cwnd += min(acked_bytes, self.mss);
+            return;
+        }
+
+        // roughly one mss per window of acknowledged data
+        self.bytes_acked += acked_bytes;
+        if self.bytes_acked >= self.cwnd {
+            self.bytes_acked -= self.cwnd;
+            self.cwnd += self.mss;
+        }
+    }
+
+    fn on_loss(&mut self, bytes_in_flight: SizeT, _now: SizeT) {
+        self.reduce_ssthresh(bytes_in_flight);
+        self.cwnd = self.ssthresh;
+    }
+
+    fn on_timeout(&mut self, bytes_in_flight: SizeT, _now: SizeT) {
+        self.reduce_ssthresh(bytes_in_flight);
+        // loss window: restart from slow start
+        self.cwnd = self.mss;
+    }
+
+    fn cwnd(&self) -> SizeT {
+        self.cwnd
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "reno(cwnd={}, ssthresh={}, slow_start={})",
+            self.cwnd,
+            self.ssthresh,
+            self.in_slow_start()
+        )
+    }
+}
@@ -15,6 +15,7 @@
 use crate::tcp_helpers::fd_adapter::{NetworkInterfaceAdapter, TCPOverUDPSocketAdapter};
 use crate::tcp_helpers::ipv4_datagram::IPv4Datagram;
 use crate::tcp_helpers::lossy_fd_adapter::LossyFdAdapter;
+use crate::tcp_helpers::tcp_listener::TCPListener;
 use crate::tcp_helpers::tcp_sponge_socket::TCPSpongeSocket;
 use crate::tcp_helpers::tuntap_adapter::{
     TCPOverIPv4OverEthernetAdapter, TCPOverIPv4OverTunFdAdapter,
@@ -30,8 +31,12 @@ pub type LossyTCPOverUDPSocketAdapter = LossyFdAdapter<TCPOverUDPSocketAdapter>;
 pub type LossyTCPOverUDPSpongeSocket = TCPSpongeSocket<LossyTCPOverUDPSocketAdapter>;
 pub type LossyTCPOverIPv4OverTunFdAdapter = LossyFdAdapter<TCPOverIPv4OverTunFdAdapter>;
 pub type LossyTCPOverIPv4SpongeSocket = TCPSpongeSocket<LossyTCPOverIPv4OverTunFdAdapter>;
+pub type TCPOverUDPListener = TCPListener<TCPOverUDPSocketAdapter>;
+pub type TCPOverIPv4Listener = TCPListener<TCPOverIPv4OverTunFdAdapter>;
+pub type TCPOverIPv4OverEthernetListener = TCPListener<TCPOverIPv4OverEthernetAdapter>;
 
 pub mod byte_stream;
+pub mod congestion_control;
 pub mod network_interface;
 pub mod router;
 pub mod stream_reassembler;
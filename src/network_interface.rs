@@ -20,10 +20,13 @@ pub struct NetworkInterface {
     ip_mac_cache: BTreeMap<u32, (SizeT, EthernetAddress)>,
     arp_request_in_flight: BTreeMap<u32, SizeT>,
     ms_total_tick: SizeT,
+    // largest datagram a frame carries
+    mtu: SizeT,
 }
 impl NetworkInterface {
     const GAP_30S: SizeT = 30 * 1000;
     const GAP_5S: SizeT = 5 * 1000;
+    pub const MTU_DFLT: SizeT = 1500;
 
     #[allow(dead_code)]
     pub fn new(ether_addr: EthernetAddress, ip_addr: Ipv4Addr) -> NetworkInterface {
@@ -40,9 +43,20 @@ impl NetworkInterface {
             ip_mac_cache: Default::default(),
             arp_request_in_flight: Default::default(),
             ms_total_tick: 0,
+            mtu: NetworkInterface::MTU_DFLT,
         }
     }
 
+    #[allow(dead_code)]
+    pub fn mtu(&self) -> SizeT {
+        self.mtu
+    }
+
+    #[allow(dead_code)]
+    pub fn set_mtu(&mut self, mtu: SizeT) {
+        self.mtu = mtu;
+    }
+
     #[allow(dead_code)]
     pub fn send_datagram(&mut self, dgram: InternetDatagram, next_hop: &Ipv4Addr) {
         let next_hop_ip = u32::from(next_hop.clone());
@@ -1,5 +1,6 @@
 use crate::network_interface::NetworkInterface;
 use crate::tcp_helpers::ethernet_frame::EthernetFrame;
+use crate::tcp_helpers::ipv4_header::IPv4Header;
 use crate::{InternetDatagram, SizeT};
 use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::net::Ipv4Addr;
@@ -62,6 +63,12 @@ pub struct Router {
     intfs: Vec<AsyncNetworkInterface>,
     // <prefix_length, <route_prefix, (Option<next_hop>, interface_num)>)
     route_map: BTreeMap<u8, HashMap<u32, (Option<Ipv4Addr>, SizeT)>>,
+    // frames an interface may have waiting to go out before it is congested
+    queue_limit: Option<SizeT>,
+    // under congestion ECN-capable datagrams are CE-marked instead of dropped
+    ecn_marking: bool,
+    dropped: SizeT,
+    ce_marked: SizeT,
 }
 impl Router {
     #[allow(dead_code)]
@@ -69,9 +76,33 @@ impl Router {
         Router {
             intfs: Default::default(),
             route_map: Default::default(),
+            queue_limit: None,
+            ecn_marking: false,
+            dropped: 0,
+            ce_marked: 0,
         }
     }
 
+    #[allow(dead_code)]
+    pub fn set_queue_limit(&mut self, limit: Option<SizeT>) {
+        self.queue_limit = limit;
+    }
+
+    #[allow(dead_code)]
+    pub fn set_ecn_marking(&mut self, ecn_marking: bool) {
+        self.ecn_marking = ecn_marking;
+    }
+
+    #[allow(dead_code)]
+    pub fn dropped(&self) -> SizeT {
+        self.dropped
+    }
+
+    #[allow(dead_code)]
+    pub fn ce_marked(&self) -> SizeT {
+        self.ce_marked
+    }
+
     #[allow(dead_code)]
     pub fn add_route(
         &mut self,
@@ -134,6 +165,18 @@ impl Router {
         });
         match found {
             Some((interface_num, next_hop)) => {
+                let congested = self
+                    .queue_limit
+                    .is_some_and(|limit| self.intfs[interface_num].frames_out().len() >= limit);
+                if congested {
+                    if self.ecn_marking && dgram.header().ecn() != IPv4Header::ECN_NOT_ECT {
+                        dgram.header_mut().set_ecn(IPv4Header::ECN_CE);
+                        self.ce_marked += 1;
+                    } else {
+                        self.dropped += 1;
+                        return;
+                    }
+                }
                 let next = next_hop.unwrap_or(Ipv4Addr::from(dst));
                 self.interface_mut(interface_num)
                     .send_datagram(dgram, &next);
@@ -113,6 +113,47 @@ impl StreamReassembler {
         self.reassemble_count == 0
     }
 
+    // unassembled bytes move to their place in the larger buffer
+    #[allow(dead_code)]
+    pub fn grow(&mut self, capacity: SizeT) {
+        if capacity <= self.capacity {
+            return;
+        }
+
+        let mut buffer = vec![0u8; capacity];
+        let valid_last: u64 = self.next_stream_index + self.output.remaining_capacity() as u64;
+        for (first, second) in &self.marker_map {
+            for i in max(*first, self.next_stream_index)..min(*second, valid_last) {
+                buffer[(i % capacity as u64) as SizeT] =
+                    self.buffer[(i % self.capacity as u64) as SizeT];
+            }
+        }
+        self.buffer = buffer;
+        self.capacity = capacity;
+        self.output.grow(capacity);
+        self.recount();
+    }
+
+    // merged [start, end) stream index ranges held beyond the next expected byte
+    #[allow(dead_code)]
+    pub fn out_of_order_ranges(&self) -> Vec<(u64, u64)> {
+        let mut ranges: Vec<(u64, u64)> = vec![];
+        let valid_last: u64 = self.next_stream_index + self.output.remaining_capacity() as u64;
+
+        for (first, second) in &self.marker_map {
+            let start = max(*first, self.next_stream_index);
+            let end = min(*second, valid_last);
+            if end <= start {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(last) if start <= last.1 => last.1 = max(last.1, end),
+                _ => ranges.push((start, end)),
+            }
+        }
+        ranges
+    }
+
     #[allow(dead_code)]
     fn reassemble(&mut self) {
         // merge the fist consecutive range
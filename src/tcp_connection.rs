@@ -1,28 +1,64 @@
 use crate::byte_stream::ByteStream;
+use crate::tcp_helpers::ipv4_header::IPv4Header;
 use crate::tcp_helpers::tcp_config::TCPConfig;
+use crate::tcp_helpers::tcp_header::TCPHeader;
 use crate::tcp_helpers::tcp_segment::TCPSegment;
-use crate::tcp_helpers::tcp_state::{TCPSenderStateSummary, TCPState};
+use crate::tcp_helpers::tcp_state::{State, StateError};
 use crate::tcp_receiver::TCPReceiver;
 use crate::tcp_sender::TCPSender;
+use crate::wrapping_integers::WrappingInt32;
 use crate::SizeT;
+use rand::{thread_rng, Rng};
+use std::cmp::{max, min};
 use std::collections::VecDeque;
 
 // for current implementation, after receiving rst (causing dual byte stream set_error), yet send & recv bytes still continue
 // so using inner byte stream error() may have implications in implementation of TCPSpongeSocket
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ResetReason {
+    PeerReset = 0,
+    RetransmitLimit,
+    KeepaliveTimeout,
+    UncleanShutdown,
+    WindowProbeLimit,
+}
+
 #[derive(Debug)]
 pub struct TCPConnection {
     cfg: TCPConfig,
     receiver: TCPReceiver,
     sender: TCPSender,
     segments_out: VecDeque<TCPSegment>,
-    linger_after_streams_finish: bool,
     total_tick: SizeT,
     last_recv_seg_tick: SizeT,
-    active: bool,
-    fin_received: bool,
-    fin_sent: bool,
-    syn_sent_or_recv: bool,
+    state: State,
+    // the last segment or transition the state machine turned down
+    state_error: Option<StateError>,
+    // window scaling is on once both SYNs carried the option
+    wscale_ok: bool,
+    snd_wscale: u8,
+    rcv_wscale: u8,
+    ts_ok: bool,
+    // random per-connection clock offset for TSval
+    ts_offset: u32,
+    // delayed acks: the last ackno sent, bytes received since, and when the ack is due
+    ackno_sent: Option<WrappingInt32>,
+    delack_bytes: SizeT,
+    ack_deadline: Option<SizeT>,
+    keepalive_probes: u32,
+    reset_reason: Option<ResetReason>,
+    // receive buffer auto-tuning: the rtt as the time it takes the peer to fill a window,
+    // the (stream index, time) that measurement waits for, and the (bytes received, time)
+    // at the start of the current rtt
+    rcv_rtt: Option<SizeT>,
+    rcv_rtt_mark: Option<(SizeT, SizeT)>,
+    rcv_space_mark: (SizeT, SizeT),
+    // a CE mark arrived: every ack carries ECE until the peer answers with CWR
+    ece_pending: bool,
+    // challenge acks this connection sent since the start of the current window
+    challenge_acks: u32,
+    challenge_ack_since: SizeT,
     #[allow(dead_code)]
     name: String,
 }
@@ -31,16 +67,29 @@ impl TCPConnection {
     pub fn new(cnf: TCPConfig) -> TCPConnection {
         TCPConnection {
             cfg: cnf.clone(),
-            receiver: TCPReceiver::new(cnf.recv_capacity),
-            sender: TCPSender::new(cnf.send_capacity, cnf.rt_timeout, cnf.fixed_isn),
+            receiver: TCPReceiver::from_config(&cnf),
+            sender: TCPSender::from_config(&cnf),
             segments_out: Default::default(),
-            linger_after_streams_finish: true,
             total_tick: 0,
             last_recv_seg_tick: 0,
-            active: true,
-            fin_received: false,
-            fin_sent: false,
-            syn_sent_or_recv: false,
+            state: State::LISTEN,
+            state_error: None,
+            wscale_ok: cnf.window_scale,
+            snd_wscale: 0,
+            rcv_wscale: 0,
+            ts_ok: cnf.timestamps,
+            ts_offset: thread_rng().gen(),
+            ackno_sent: None,
+            delack_bytes: 0,
+            ack_deadline: None,
+            keepalive_probes: 0,
+            reset_reason: None,
+            rcv_rtt: None,
+            rcv_rtt_mark: None,
+            rcv_space_mark: (0, 0),
+            ece_pending: false,
+            challenge_acks: 0,
+            challenge_ack_since: 0,
             name: "".to_string(),
         }
     }
@@ -49,55 +98,93 @@ impl TCPConnection {
     pub fn new2(cnf: TCPConfig, _name: String) -> TCPConnection {
         TCPConnection {
             cfg: cnf.clone(),
-            receiver: TCPReceiver::new(cnf.recv_capacity),
-            sender: TCPSender::new(cnf.send_capacity, cnf.rt_timeout, cnf.fixed_isn),
+            receiver: TCPReceiver::from_config(&cnf),
+            sender: TCPSender::from_config(&cnf),
             segments_out: Default::default(),
-            linger_after_streams_finish: true,
             total_tick: 0,
             last_recv_seg_tick: 0,
-            active: true,
-            fin_received: false,
-            fin_sent: false,
-            syn_sent_or_recv: false,
+            state: State::LISTEN,
+            state_error: None,
+            wscale_ok: cnf.window_scale,
+            snd_wscale: 0,
+            rcv_wscale: 0,
+            ts_ok: cnf.timestamps,
+            ts_offset: thread_rng().gen(),
+            ackno_sent: None,
+            delack_bytes: 0,
+            ack_deadline: None,
+            keepalive_probes: 0,
+            reset_reason: None,
+            rcv_rtt: None,
+            rcv_rtt_mark: None,
+            rcv_space_mark: (0, 0),
+            ece_pending: false,
+            challenge_acks: 0,
+            challenge_ack_since: 0,
             name: _name,
         }
     }
 
     #[allow(dead_code)]
     pub fn connect(&mut self) {
+        if !self.state.can_transition_to(State::SynSent) {
+            self.state_error = Some(StateError::InvalidTransition(self.state, State::SynSent));
+            return;
+        }
         self.sender.fill_window();
 
         while !self.sender.segments_out_mut().is_empty() {
-            let seg = self.sender.segments_out_mut().pop_front().unwrap();
+            let mut seg = self.sender.segments_out_mut().pop_front().unwrap();
+            self.set_options(&mut seg);
             self.segments_out.push_back(seg);
-            self.syn_sent_or_recv = true;
         }
+        self.transition(State::SynSent);
     }
 
     #[allow(dead_code)]
     pub fn write(&mut self, data: &[u8]) -> SizeT {
         let written = self.sender.stream_in_mut().write(data);
+        self.sender.set_options_length(self.options_length());
         self.sender.fill_window();
 
         while !self.sender.segments_out_mut().is_empty() {
             let mut mut_seg = self.sender.segments_out_mut().pop_front().unwrap();
+            self.set_options(&mut mut_seg);
             if self.receiver.ackno().is_some() {
                 mut_seg.header_mut().ack = true;
                 mut_seg.header_mut().ackno = self.receiver.ackno().unwrap();
-                if self.receiver.window_size() >= u16::MAX as SizeT {
-                    mut_seg.header_mut().win = u16::MAX;
+                self.ackno_sent = self.receiver.ackno();
+                self.delack_bytes = 0;
+                self.ack_deadline = None;
+                // the window in a SYN is never scaled
+                let shift = if mut_seg.header().syn {
+                    0
                 } else {
-                    mut_seg.header_mut().win = self.receiver.window_size() as u16;
+                    self.rcv_wscale
+                };
+                let win = min(self.receiver.window_size() >> shift, u16::MAX as SizeT);
+                mut_seg.header_mut().win = win as u16;
+                self.receiver.window_advertised(win << shift);
+                if !mut_seg.header().syn {
+                    mut_seg.header_mut().ece = self.ece_pending;
+                }
+                if self.sender.sack_enabled() && !mut_seg.header().syn {
+                    mut_seg.header_mut().sack_blocks = self.receiver.sack_blocks();
+                    self.receiver.clear_dsack();
+                    // a segment sized before the blocks came up gives up the ones it has no room for
+                    let payload = mut_seg.payload().size();
+                    while !mut_seg.header().sack_blocks.is_empty()
+                        && payload + mut_seg.header().header_length() - TCPHeader::LENGTH
+                            > self.sender.mss()
+                    {
+                        mut_seg.header_mut().sack_blocks.pop();
+                    }
                 }
             }
-            let fin_ = mut_seg.header().fin;
             self.segments_out.push_back(mut_seg);
-            if fin_ {
-                self.fin_sent = true;
-            }
         }
 
-        self.check_active();
+        self.advance();
 
         written
     }
@@ -124,6 +211,25 @@ impl TCPConnection {
         self.receiver.stream_out()
     }
 
+    // turning nagle off sends what it was holding back
+    #[allow(dead_code)]
+    pub fn set_nodelay(&mut self, nodelay: bool) {
+        self.sender.set_nodelay(nodelay);
+        self.write(vec![0u8; 0].as_slice());
+    }
+
+    #[allow(dead_code)]
+    pub fn set_cork(&mut self, cork: bool) {
+        self.sender.set_cork(cork);
+        self.write(vec![0u8; 0].as_slice());
+    }
+
+    // effective segment size for what we send
+    #[allow(dead_code)]
+    pub fn mss(&self) -> SizeT {
+        self.sender.mss()
+    }
+
     #[allow(dead_code)]
     pub fn bytes_in_flight(&self) -> SizeT {
         self.sender.bytes_in_flight()
@@ -140,54 +246,135 @@ impl TCPConnection {
     }
 
     #[allow(dead_code)]
-    pub fn state(&self) -> TCPState {
-        TCPState::new(
-            &self.sender,
-            &self.receiver,
-            self.active,
-            self.linger_after_streams_finish,
-        )
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    // the last segment or transition that was turned down, cleared by reading it
+    #[allow(dead_code)]
+    pub fn take_state_error(&mut self) -> Option<StateError> {
+        self.state_error.take()
     }
 
     #[allow(dead_code)]
     pub fn segment_received(&mut self, seg: &TCPSegment) {
+        if !self.segment_acceptable(seg) {
+            self.state_error = Some(StateError::UnexpectedSegment(self.state));
+            return;
+        }
+        if !self.blind_attack_check(seg) {
+            return;
+        }
+        // an old duplicate is only acked, it says nothing about the peer being alive
+        if !self.receiver.paws_check(seg) {
+            self.sender.send_empty_segment(false);
+            self.write(vec![0u8; 0].as_slice());
+            return;
+        }
         self.last_recv_seg_tick = self.total_tick;
+        self.keepalive_probes = 0;
 
+        // anything but the next in-order data is acked right away
+        let in_order = !seg.header().fin
+            && self.receiver.ackno() == Some(seg.header().seqno)
+            && self.receiver.unassembled_bytes() == 0;
         self.receiver.segment_received(seg);
+        if self.cfg.recv_capacity_max.is_some() && seg.payload().size() > 0 {
+            self.autotune_recv_capacity(seg);
+        }
 
-        if seg.header().syn && 0 == self.sender.next_seqno_absolute() {
-            self.write(vec![0u8; 0].as_slice());
-            self.syn_sent_or_recv = true;
+        if seg.header().syn && !seg.header().sack_permitted {
+            self.sender.set_sack(false);
+        }
+        // an ECN-setup SYN carries ECE and CWR, the SYN/ACK answering it only ECE
+        if seg.header().syn && !(seg.header().ece && seg.header().cwr != seg.header().ack) {
+            self.sender.set_ecn(false);
+        }
+        let mut ce = false;
+        if self.sender.ecn_enabled() && !seg.header().syn {
+            if seg.header().cwr {
+                self.ece_pending = false;
+            }
+            if seg.ecn() == IPv4Header::ECN_CE {
+                self.ece_pending = true;
+                ce = true;
+            }
+        }
+        // RFC 6691: a peer without the option is taken to accept the default of 536 bytes
+        if seg.header().syn {
+            let mss = min(
+                self.cfg.segment_size(),
+                seg.header().mss.map_or(TCPConfig::MSS_DFLT, |m| m as SizeT),
+            );
+            self.receiver.set_mss(mss);
+            if mss != self.sender.mss() {
+                self.sender.set_mss(mss);
+                self.sender
+                    .set_congestion_control(self.cfg.congestion_control.build(mss));
+            }
+        }
+        if seg.header().syn {
+            self.ts_ok = self.ts_ok && seg.header().timestamps.is_some();
+            self.receiver.set_paws(self.ts_ok);
+            self.wscale_ok = self.wscale_ok && seg.header().wscale.is_some();
+            if self.wscale_ok {
+                self.snd_wscale = min(seg.header().wscale.unwrap(), TCPConfig::MAX_WSCALE);
+                self.rcv_wscale = self.cfg.wscale();
+            }
+        }
 
+        if self.state == State::LISTEN {
+            self.write(vec![0u8; 0].as_slice());
+            self.transition(State::SynRcvd);
             return;
         }
-
-        if !self.syn_sent_or_recv {
+        // RFC 793 simultaneous open, the SYN/ACK answering the peer is our own SYN once more
+        if self.state == State::SynSent && !seg.header().ack {
+            self.sender.resend_syn();
+            self.write(vec![0u8; 0].as_slice());
             return;
         }
 
         if seg.header().rst {
-            self.active = false;
+            self.reset_reason = Some(ResetReason::PeerReset);
             self.sender.stream_in_mut().set_error();
             self.receiver.stream_out_mut().set_error();
-        }
-
-        if seg.header().fin {
-            self.fin_received = true;
-            if !self.fin_sent {
-                self.linger_after_streams_finish = false;
-            }
+            self.transition(State::RESET);
+            return;
         }
 
         if seg.header().ack {
-            self.sender
-                .ack_received(seg.header().ackno, seg.header().win);
+            if let Some((_, tsecr)) = seg.header().timestamps {
+                if self.ts_ok && tsecr != 0 {
+                    self.sender
+                        .timestamp_echo_received(tsecr.wrapping_sub(self.ts_offset) as SizeT);
+                }
+            }
+            if seg.header().ece && !seg.header().syn {
+                self.sender.ecn_echo_received();
+            }
+            self.sender.sack_received(&seg.header().sack_blocks);
+            let win = if seg.header().syn {
+                seg.header().win as u32
+            } else {
+                (seg.header().win as u32) << self.snd_wscale
+            };
+            self.sender.ack_received_with_length(
+                seg.header().ackno,
+                win,
+                seg.length_in_sequence_space(),
+            );
             self.write(vec![0u8; 0].as_slice());
         }
 
         if seg.length_in_sequence_space() > 0 {
-            self.sender.send_empty_segment(false);
-            self.write(vec![0u8; 0].as_slice());
+            // a CE mark is echoed right away
+            if self.cfg.delayed_ack && in_order && !ce && self.receiver.unassembled_bytes() == 0 {
+                self.delay_ack(seg.length_in_sequence_space());
+            } else {
+                self.sender.send_empty_segment(false);
+                self.write(vec![0u8; 0].as_slice());
+            }
         }
 
         if self.receiver.ackno().is_some()
@@ -198,25 +385,59 @@ impl TCPConnection {
             self.write(vec![0u8; 0].as_slice());
         }
 
-        self.check_active();
+        self.advance();
     }
 
     #[allow(dead_code)]
     pub fn tick(&mut self, ms_since_last_tick: SizeT) {
+        if !self.active() {
+            return;
+        }
         if self.sender.consecutive_retransmissions() >= TCPConfig::MAX_RETX_ATTEMPTS {
-            self.send_reset();
+            self.send_reset(ResetReason::RetransmitLimit);
+            return;
+        }
+        // RFC 1122 4.2.2.17: the window may stay closed for good, but not the peer go silent
+        if self.sender.unanswered_window_probes() >= TCPConfig::MAX_RETX_ATTEMPTS as SizeT {
+            self.send_reset(ResetReason::WindowProbeLimit);
             return;
         }
 
         let l_old = self.sender.segments_out_mut().len() as SizeT;
         self.total_tick += ms_since_last_tick;
+        self.sender.set_options_length(self.options_length());
         self.sender.tick(ms_since_last_tick);
         let l_new = self.sender.segments_out_mut().len() as SizeT;
         if l_new > l_old {
             self.write(vec![0u8; 0].as_slice());
         }
 
-        self.check_active();
+        if let Some(deadline) = self.ack_deadline {
+            if self.total_tick >= deadline {
+                self.sender.send_empty_segment(false);
+                self.write(vec![0u8; 0].as_slice());
+            }
+        }
+
+        // the app drained a window we had closed
+        if self.cfg.sws_avoidance
+            && self.receiver.ackno().is_some()
+            && !self.receiver.stream_out().input_ended()
+            && self.receiver.window_offered() == 0
+            && self.receiver.window_size() > 0
+        {
+            self.sender.send_empty_segment(false);
+            self.write(vec![0u8; 0].as_slice());
+        }
+
+        if self.cfg.keepalive {
+            self.keepalive();
+            if !self.active() {
+                return;
+            }
+        }
+
+        self.advance();
     }
 
     #[allow(dead_code)]
@@ -226,40 +447,253 @@ impl TCPConnection {
 
     #[allow(dead_code)]
     pub fn active(&self) -> bool {
-        self.active
+        !matches!(self.state, State::CLOSED | State::RESET)
     }
 
+    // why the connection was reset, None while it wasn't
     #[allow(dead_code)]
-    fn send_reset(&mut self) {
-        self.sender.send_empty_segment(true);
+    pub fn reset_reason(&self) -> Option<ResetReason> {
+        self.reset_reason
+    }
+
+    // only an established connection with nothing in flight is probed, anything received
+    // starts the idle time over
+    fn keepalive(&mut self) {
+        if !matches!(
+            self.state,
+            State::ESTABLISHED | State::CloseWait | State::FinWait2
+        ) || self.sender.bytes_in_flight() > 0
+        {
+            return;
+        }
+
+        let due =
+            self.cfg.keepalive_idle + self.keepalive_probes as SizeT * self.cfg.keepalive_interval;
+        if self.time_since_last_segment_received() < due {
+            return;
+        }
+        if self.keepalive_probes >= self.cfg.keepalive_probes {
+            self.send_reset(ResetReason::KeepaliveTimeout);
+            return;
+        }
+        self.keepalive_probes += 1;
+        self.sender.send_keepalive();
         self.write(vec![0u8; 0].as_slice());
-        self.sender.stream_in_mut().set_error();
-        self.receiver.stream_out_mut().set_error();
-        self.active = false;
     }
 
     #[allow(dead_code)]
-    fn check_active(&mut self) {
-        if !self.active {
+    pub fn recv_capacity(&self) -> SizeT {
+        self.receiver.capacity()
+    }
+
+    // dynamic right-sizing: once per rtt the buffer grows to twice what arrived during it,
+    // so the window stays ahead of the sender
+    fn autotune_recv_capacity(&mut self, seg: &TCPSegment) {
+        let received = self.receiver.stream_out().bytes_written();
+        let now = self.total_tick;
+
+        // an echoed timestamp is a direct sample, without it the rtt is taken as the time
+        // the peer needs to fill the window
+        let mut sample = None;
+        match (self.ts_ok, seg.header().timestamps) {
+            (true, Some((_, tsecr))) if tsecr != 0 => {
+                sample = Some(now.saturating_sub(tsecr.wrapping_sub(self.ts_offset) as SizeT));
+            }
+            _ => match self.rcv_rtt_mark {
+                None => self.rcv_rtt_mark = Some((received + self.receiver.window_size(), now)),
+                Some((target, since)) if received >= target => {
+                    sample = Some(now - since);
+                    self.rcv_rtt_mark = Some((received + self.receiver.window_size(), now));
+                }
+                _ => {}
+            },
+        }
+        if let Some(sample) = sample {
+            let sample = max(sample, 1);
+            self.rcv_rtt = Some(match self.rcv_rtt {
+                Some(rtt) => max((7 * rtt + sample) / 8, 1),
+                None => sample,
+            });
+        }
+
+        let rtt = match self.rcv_rtt {
+            Some(rtt) => rtt,
+            None => return,
+        };
+        let (space_received, space_time) = self.rcv_space_mark;
+        if now - space_time < rtt {
+            return;
+        }
+        let target = min(
+            2 * (received - space_received),
+            self.cfg.recv_capacity_max.unwrap(),
+        );
+        self.receiver.grow(target);
+        self.rcv_space_mark = (received, now);
+    }
+
+    // data already acked by a segment sent in the meantime needs no ack of its own
+    fn delay_ack(&mut self, len: SizeT) {
+        if self.ackno_sent == self.receiver.ackno() {
             return;
         }
+        self.delack_bytes += len;
+        if self.delack_bytes >= 2 * self.sender.mss() {
+            self.sender.send_empty_segment(false);
+            self.write(vec![0u8; 0].as_slice());
+        } else if self.ack_deadline.is_none() {
+            self.ack_deadline = Some(self.total_tick + self.cfg.ack_delay as SizeT);
+        }
+    }
+
+    // option bytes on the data segments sent now: the timestamps and whatever SACK
+    // blocks the receiver has to report
+    fn options_length(&self) -> SizeT {
+        let mut header = TCPHeader::new();
+        if self.ts_ok {
+            header.timestamps = Some((0, 0));
+        }
+        if self.sender.sack_enabled() {
+            header.sack_blocks = self.receiver.sack_blocks();
+        }
+        header.header_length() - TCPHeader::LENGTH
+    }
 
-        let r =
-            (0 == self.receiver.unassembled_bytes()) && self.receiver.stream_out().input_ended();
-        let s = self.fin_sent
-            && (TCPSenderStateSummary::FIN_ACKED == TCPState::state_summary_sender(&self.sender));
-        if !(r && s) {
+    fn set_options(&self, seg: &mut TCPSegment) {
+        if self.ts_ok {
+            let tsval = (self.total_tick as u32).wrapping_add(self.ts_offset);
+            seg.header_mut().timestamps = Some((tsval, self.receiver.ts_recent().unwrap_or(0)));
+        }
+        if !seg.header().syn {
             return;
         }
+        seg.header_mut().mss = Some(min(self.cfg.segment_size(), u16::MAX as SizeT) as u16);
+        if self.wscale_ok {
+            seg.header_mut().wscale = Some(self.cfg.wscale());
+        }
+        if self.sender.ecn_enabled() {
+            seg.header_mut().ece = true;
+            seg.header_mut().cwr = self.receiver.ackno().is_none();
+        }
+    }
 
-        if self.linger_after_streams_finish {
-            if self.time_since_last_segment_received() >= (10 * self.cfg.rt_timeout) as SizeT {
-                self.active = false;
+    #[allow(dead_code)]
+    fn send_reset(&mut self, reason: ResetReason) {
+        self.reset_reason = Some(reason);
+        self.sender.send_empty_segment(true);
+        self.write(vec![0u8; 0].as_slice());
+        self.sender.stream_in_mut().set_error();
+        self.receiver.stream_out_mut().set_error();
+        self.transition(State::RESET);
+    }
+
+    // LISTEN only takes a SYN, SYN_SENT a SYN or RST that acks ours if it acks anything, and a
+    // closed or reset connection nothing at all
+    fn segment_acceptable(&self, seg: &TCPSegment) -> bool {
+        match self.state {
+            State::LISTEN => seg.header().syn && !seg.header().rst,
+            State::SynSent if seg.header().rst => {
+                seg.header().ack && seg.header().ackno == self.sender.next_seqno()
             }
-        } else {
-            if self.fin_received {
-                self.active = false;
+            State::SynSent => {
+                seg.header().syn
+                    && (!seg.header().ack || seg.header().ackno == self.sender.next_seqno())
+            }
+            State::CLOSED | State::RESET => false,
+            _ => true,
+        }
+    }
+
+    // RFC 5961 once the peer's SYN is in: a RST is taken only at exactly rcv.nxt, while a RST
+    // elsewhere in the window, a SYN, or an ack of data never sent (or too old to be real)
+    // only draws a challenge ack and the segment is dropped
+    fn blind_attack_check(&mut self, seg: &TCPSegment) -> bool {
+        let rcv_nxt = match self.receiver.ackno() {
+            Some(ackno) => ackno,
+            None => return true,
+        };
+        let header = seg.header();
+        if header.rst {
+            if header.seqno == rcv_nxt {
+                return true;
             }
+            let offset = header.seqno.raw_value().wrapping_sub(rcv_nxt.raw_value());
+            if (offset as SizeT) < self.receiver.window_size() {
+                self.send_challenge_ack();
+            }
+            return false;
+        }
+        // the peer's own SYN again is left to the usual handling while ours isn't acked
+        if header.syn && !(self.state == State::SynRcvd && header.seqno == rcv_nxt - 1) {
+            self.send_challenge_ack();
+            return false;
+        }
+        if header.ack && !self.sender.ack_acceptable(header.ackno) {
+            self.send_challenge_ack();
+            return false;
+        }
+        true
+    }
+
+    fn send_challenge_ack(&mut self) {
+        if self.total_tick >= self.challenge_ack_since + TCPConfig::CHALLENGE_ACK_WINDOW {
+            self.challenge_ack_since = self.total_tick;
+            self.challenge_acks = 0;
+        }
+        if self.challenge_acks >= self.cfg.challenge_ack_limit {
+            return;
+        }
+        self.challenge_acks += 1;
+        self.sender.send_empty_segment(false);
+        self.write(vec![0u8; 0].as_slice());
+    }
+
+    fn transition(&mut self, next: State) {
+        if self.state.can_transition_to(next) {
+            self.state = next;
+        } else {
+            self.state_error = Some(StateError::InvalidTransition(self.state, next));
+        }
+    }
+
+    // follows what the sender and receiver have done since the last event, possibly over
+    // several states at once. a connection that saw the peer's FIN before sending its own
+    // closes without lingering in TIME_WAIT
+    fn advance(&mut self) {
+        let syn_acked = self.sender.next_seqno_absolute() > self.sender.bytes_in_flight() as u64;
+        let fin_sent = self.sender.stream_in().eof()
+            && self.sender.next_seqno_absolute()
+                == (self.sender.stream_in().bytes_written() + 2) as u64;
+        let fin_acked = fin_sent && self.sender.bytes_in_flight() == 0;
+        let fin_received = self.receiver.stream_out().input_ended();
+
+        loop {
+            let next = match self.state {
+                State::SynSent if self.receiver.ackno().is_some() => {
+                    if syn_acked {
+                        State::ESTABLISHED
+                    } else {
+                        State::SynRcvd
+                    }
+                }
+                State::SynRcvd if syn_acked => State::ESTABLISHED,
+                State::ESTABLISHED if fin_received => State::CloseWait,
+                State::ESTABLISHED if fin_sent => State::FinWait1,
+                State::FinWait1 if fin_received => State::CLOSING,
+                State::FinWait1 if fin_acked => State::FinWait2,
+                State::FinWait2 if fin_received => State::TimeWait,
+                State::CLOSING if fin_acked => State::TimeWait,
+                State::CloseWait if fin_sent => State::LastAck,
+                State::LastAck if fin_acked => State::CLOSED,
+                State::TimeWait
+                    if self.time_since_last_segment_received()
+                        >= (10 * self.cfg.rt_timeout) as SizeT =>
+                {
+                    State::CLOSED
+                }
+                _ => return,
+            };
+            self.transition(next);
         }
     }
 }
@@ -267,7 +701,7 @@ impl Drop for TCPConnection {
     fn drop(&mut self) {
         if self.active() {
             eprintln!("Warning: Unclean shutdown of TCPConnection\n");
-            self.send_reset();
+            self.send_reset(ResetReason::UncleanShutdown);
         }
     }
 }
@@ -1,6 +1,8 @@
 use crate::network_interface::NetworkInterface;
 use crate::tcp_helpers::ethernet_frame::EthernetFrame;
+use crate::tcp_helpers::ipv4_header::IPv4Header;
 use crate::tcp_helpers::tcp_config::FdAdapterConfig;
+use crate::tcp_helpers::tcp_header::TCPHeader;
 use crate::tcp_helpers::tcp_over_ip::TCPOverIPv4Adapter;
 use crate::tcp_helpers::tcp_segment::TCPSegment;
 use crate::util::buffer::Buffer;
@@ -12,12 +14,18 @@ use crate::SizeT;
 use std::collections::VecDeque;
 use std::net::{Ipv4Addr, SocketAddrV4};
 
+// (local, remote) addresses a segment was exchanged on
+pub type FourTuple = (SocketAddrV4, SocketAddrV4);
+
 #[derive(Debug)]
 pub struct FdAdapterBase {
     cfg: FdAdapterConfig,
     listen: bool,
 }
 impl FdAdapterBase {
+    pub const MTU_DFLT: SizeT = 1500 as SizeT;
+    pub const UDP_HEADER_LENGTH: SizeT = 8 as SizeT;
+
     #[allow(dead_code)]
     pub fn new() -> FdAdapterBase {
         FdAdapterBase {
@@ -65,6 +73,11 @@ pub trait AsFdAdapterBase {
     fn config(&self) -> &FdAdapterConfig {
         self.as_fd_adapter_base().config()
     }
+
+    // largest payload a segment can carry without exceeding the MTU, TCP over IPv4 by default
+    fn mss(&self) -> SizeT {
+        FdAdapterBase::MTU_DFLT - IPv4Header::LENGTH - TCPHeader::LENGTH
+    }
 }
 pub trait AsFdAdapterBaseMut: AsFdAdapterBase {
     fn as_fd_adapter_base_mut(&mut self) -> &mut FdAdapterBase;
@@ -92,6 +105,11 @@ pub trait AsFdAdapterBaseMut: AsFdAdapterBase {
     fn read_adp(&mut self) -> Option<TCPSegment>;
 
     fn write_adp(&mut self, seg: &mut TCPSegment);
+
+    // a segment from any peer, with the addresses it came on, for a listener to demultiplex
+    fn read_from(&mut self) -> Option<(FourTuple, TCPSegment)>;
+
+    fn write_to(&mut self, tuple: &FourTuple, seg: &mut TCPSegment);
 }
 
 #[derive(Debug)]
@@ -113,6 +131,14 @@ impl AsFdAdapterBase for TCPOverUDPSocketAdapter {
     fn as_fd_adapter_base(&self) -> &FdAdapterBase {
         &self.fd_adapter_base
     }
+
+    // datagrams go to any peer unconnected, so there is no path MTU to ask the socket for
+    fn mss(&self) -> SizeT {
+        FdAdapterBase::MTU_DFLT
+            - IPv4Header::LENGTH
+            - FdAdapterBase::UDP_HEADER_LENGTH
+            - TCPHeader::LENGTH
+    }
 }
 impl AsFdAdapterBaseMut for TCPOverUDPSocketAdapter {
     fn as_fd_adapter_base_mut(&mut self) -> &mut FdAdapterBase {
@@ -146,11 +172,23 @@ impl AsFdAdapterBaseMut for TCPOverUDPSocketAdapter {
     }
 
     fn write_adp(&mut self, seg: &mut TCPSegment) {
-        seg.header_mut().sport = self.config().source.port();
-        seg.header_mut().dport = self.config().destination.port();
+        let tuple = (self.config().source, self.config().destination);
+        self.write_to(&tuple, seg);
+    }
+
+    fn read_from(&mut self) -> Option<(FourTuple, TCPSegment)> {
+        let (source_address, payload) = self.sock.recv(65536);
+        let seg = TCPSegment::parse_new(Buffer::new(payload), 0).ok()?;
 
-        self.sock
-            .sendto(&self.fd_adapter_base.cfg.destination, &mut seg.serialize(0));
+        let remote = FdAdapterConfig::from_sockaddr(&source_address);
+        Some(((self.config().source, remote), seg))
+    }
+
+    fn write_to(&mut self, tuple: &FourTuple, seg: &mut TCPSegment) {
+        seg.header_mut().sport = tuple.0.port();
+        seg.header_mut().dport = tuple.1.port();
+
+        self.sock.sendto(&tuple.1, &mut seg.serialize(0));
     }
 }
 impl TCPOverUDPSocketAdapter {
@@ -223,6 +261,26 @@ impl AsFdAdapterBaseMut for NetworkInterfaceAdapter {
             .send_datagram(self.adapter.wrap_tcp_in_ip(seg), &self.next_hop);
         self.send_pending();
     }
+
+    fn read_from(&mut self) -> Option<(FourTuple, TCPSegment)> {
+        let mut frame = EthernetFrame::new();
+        let r = frame.parse(self.data_socket_pair.0.read(u32::MAX));
+        if r != ParseResult::NoError {
+            return None;
+        }
+
+        let ip_dgram = self.interface.recv_frame(&frame);
+
+        self.send_pending();
+
+        self.adapter.unwrap_tcp_in_ip_from(ip_dgram?)
+    }
+
+    fn write_to(&mut self, tuple: &FourTuple, seg: &mut TCPSegment) {
+        self.interface
+            .send_datagram(self.adapter.wrap_tcp_in_ip_to(tuple, seg), &self.next_hop);
+        self.send_pending();
+    }
 }
 impl NetworkInterfaceAdapter {
     #[allow(dead_code)]
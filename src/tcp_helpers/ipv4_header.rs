@@ -23,6 +23,12 @@ impl IPv4Header {
     pub const LENGTH: SizeT = 20 as SizeT;
     pub const DEFAULT_TTL: u8 = 128;
     pub const PROTO_TCP: u8 = 6;
+    // rfc 3168 codepoints in the low two bits of tos
+    pub const ECN_MASK: u8 = 0b11;
+    pub const ECN_NOT_ECT: u8 = 0b00;
+    pub const ECN_ECT1: u8 = 0b01;
+    pub const ECN_ECT0: u8 = 0b10;
+    pub const ECN_CE: u8 = 0b11;
 
     #[allow(dead_code)]
     pub fn new() -> IPv4Header {
@@ -130,6 +136,27 @@ impl IPv4Header {
         ret
     }
 
+    #[allow(dead_code)]
+    pub fn tos(&self) -> u8 {
+        self.tos
+    }
+
+    #[allow(dead_code)]
+    pub fn set_tos(&mut self, tos: u8) {
+        self.tos = tos;
+    }
+
+    #[allow(dead_code)]
+    pub fn ecn(&self) -> u8 {
+        self.tos & IPv4Header::ECN_MASK
+    }
+
+    // the DSCP bits are left as they are
+    #[allow(dead_code)]
+    pub fn set_ecn(&mut self, ecn: u8) {
+        self.tos = (self.tos & !IPv4Header::ECN_MASK) | (ecn & IPv4Header::ECN_MASK);
+    }
+
     pub fn payload_length(&self) -> u16 {
         self.len - (4 * self.hlen) as u16
     }
@@ -1,4 +1,6 @@
-use crate::tcp_helpers::fd_adapter::{AsFdAdapterBase, AsFdAdapterBaseMut, FdAdapterBase};
+use crate::tcp_helpers::fd_adapter::{
+    AsFdAdapterBase, AsFdAdapterBaseMut, FdAdapterBase, FourTuple,
+};
 use crate::tcp_helpers::tcp_config::FdAdapterConfig;
 use crate::tcp_helpers::tcp_segment::TCPSegment;
 use crate::util::file_descriptor::{AsFileDescriptor, AsFileDescriptorMut, FileDescriptor};
@@ -33,6 +35,10 @@ where
     fn as_fd_adapter_base(&self) -> &FdAdapterBase {
         self.adapter.as_fd_adapter_base()
     }
+
+    fn mss(&self) -> SizeT {
+        self.adapter.mss()
+    }
 }
 impl<AdapterT> AsFdAdapterBaseMut for LossyFdAdapter<AdapterT>
 where
@@ -49,6 +55,23 @@ where
     fn write_adp(&mut self, seg: &mut TCPSegment) {
         self.adapter.write_adp(seg);
     }
+
+    fn read_from(&mut self) -> Option<(FourTuple, TCPSegment)> {
+        let ret = self.adapter.read_from();
+        if self.should_drop(false) {
+            return None;
+        }
+
+        ret
+    }
+
+    fn write_to(&mut self, tuple: &FourTuple, seg: &mut TCPSegment) {
+        if self.should_drop(true) {
+            return;
+        }
+
+        self.adapter.write_to(tuple, seg);
+    }
 }
 impl<AdapterT> LossyFdAdapter<AdapterT>
 where
@@ -5,8 +5,11 @@ pub mod fd_adapter;
 pub mod ipv4_datagram;
 pub mod ipv4_header;
 pub mod lossy_fd_adapter;
+pub mod syn_cookie;
 pub mod tcp_config;
 pub mod tcp_header;
+pub mod tcp_listener;
+pub mod tcp_option;
 pub mod tcp_over_ip;
 pub mod tcp_segment;
 pub mod tcp_sponge_socket;
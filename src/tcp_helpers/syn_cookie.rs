@@ -0,0 +1,88 @@
+use crate::tcp_helpers::fd_adapter::FourTuple;
+use crate::wrapping_integers::WrappingInt32;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+// when the listener answers a SYN without keeping any state for it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SynCookieMode {
+    Off,
+    // only once the SYN queue is full
+    Auto,
+    Always,
+}
+
+// stateless SYN/ACK ISNs. the top 5 bits are a coarse clock, the next 3 the index of the
+// MSS in MSS_TABLE, and the low 24 a keyed hash of the connection, the peer's ISN and the
+// two fields above, so the ACK of the SYN/ACK is enough to rebuild the connection
+#[derive(Debug)]
+pub struct SynCookies {
+    keys: RandomState,
+}
+impl SynCookies {
+    pub const MSS_TABLE: [u16; 8] = [64, 256, 536, 1000, 1220, 1380, 1440, 1460];
+    // a cookie is good for MAX_AGE more periods after the one it was made in
+    pub const PERIOD_MS: u64 = 64 * 1000;
+    pub const MAX_AGE: u64 = 2;
+
+    #[allow(dead_code)]
+    pub fn new() -> SynCookies {
+        SynCookies {
+            keys: RandomState::new(),
+        }
+    }
+
+    // the mss is rounded down to the table, the peer is told of it by the ACK being accepted
+    #[allow(dead_code)]
+    pub fn make(
+        &self,
+        tuple: &FourTuple,
+        peer_isn: WrappingInt32,
+        mss: u16,
+        now_ms: u64,
+    ) -> WrappingInt32 {
+        let idx = SynCookies::MSS_TABLE
+            .iter()
+            .rposition(|m| *m <= mss)
+            .unwrap_or(0) as u32;
+        let count = now_ms / SynCookies::PERIOD_MS;
+        WrappingInt32::new(
+            ((count as u32 & 0x1f) << 27) | (idx << 24) | self.hash(tuple, peer_isn, count, idx),
+        )
+    }
+
+    // the mss the cookie was made with, None if it wasn't ours or is too old
+    #[allow(dead_code)]
+    pub fn check(
+        &self,
+        tuple: &FourTuple,
+        peer_isn: WrappingInt32,
+        cookie: WrappingInt32,
+        now_ms: u64,
+    ) -> Option<u16> {
+        let cookie = cookie.raw_value();
+        let idx = (cookie >> 24) & 0x7;
+        let now = now_ms / SynCookies::PERIOD_MS;
+        (0..=SynCookies::MAX_AGE.min(now))
+            .map(|age| now - age)
+            .find(|count| {
+                (*count as u32 & 0x1f) == cookie >> 27
+                    && self.hash(tuple, peer_isn, *count, idx) == cookie & 0xff_ffff
+            })
+            .map(|_| SynCookies::MSS_TABLE[idx as usize])
+    }
+
+    fn hash(&self, tuple: &FourTuple, peer_isn: WrappingInt32, count: u64, idx: u32) -> u32 {
+        let mut h = self.keys.build_hasher();
+        tuple.hash(&mut h);
+        peer_isn.raw_value().hash(&mut h);
+        count.hash(&mut h);
+        idx.hash(&mut h);
+        h.finish() as u32 & 0xff_ffff
+    }
+}
+impl Default for SynCookies {
+    fn default() -> Self {
+        SynCookies::new()
+    }
+}
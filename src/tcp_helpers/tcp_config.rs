@@ -1,3 +1,4 @@
+use crate::congestion_control::controller::CongestionControlAlgorithm;
 use crate::wrapping_integers::WrappingInt32;
 use crate::SizeT;
 use libc::{sockaddr, socklen_t};
@@ -12,12 +13,91 @@ pub struct TCPConfig {
     pub recv_capacity: SizeT,
     pub send_capacity: SizeT,
     pub fixed_isn: Option<WrappingInt32>,
+    pub congestion_control: CongestionControlAlgorithm,
+    pub adaptive_rto: bool,
+    pub rto_min: u16,
+    pub rto_max: u16,
+    pub fast_retransmit: bool,
+    pub sack: bool,
+    pub window_scale: bool,
+    pub timestamps: bool,
+    // segment size announced in the SYN, None to derive it from the adapter's MTU
+    pub mss: Option<SizeT>,
+    // send small segments right away instead of coalescing them with nagle
+    pub nodelay: bool,
+    // hold partial segments until uncorked or cork_timeout ms have passed
+    pub cork: bool,
+    pub cork_timeout: u16,
+    // RFC 1122/5681: ack every second full segment, or once ack_delay ms have passed
+    pub delayed_ack: bool,
+    pub ack_delay: u16,
+    // probe an idle connection after keepalive_idle ms, every keepalive_interval ms, and
+    // reset it once keepalive_probes probes went unanswered
+    pub keepalive: bool,
+    pub keepalive_idle: SizeT,
+    pub keepalive_interval: SizeT,
+    pub keepalive_probes: u32,
+    // probe a zero window from a persist timer with its own backoff, instead of
+    // sending into it as if it were one byte wide
+    pub persist: bool,
+    // rfc 1122 receiver silly window avoidance, plus window updates when a closed
+    // window opens up again
+    pub sws_avoidance: bool,
+    // receive buffer auto-tuning: recv_capacity grows with the bytes received per rtt up
+    // to this size. None keeps it fixed
+    pub recv_capacity_max: Option<SizeT>,
+    // spread segments out at a rate derived from cwnd and srtt, capped at pacing_rate_max
+    // bytes per ms. a congestion control with its own pacing rate (BBR) paces regardless
+    pub pacing: bool,
+    pub pacing_rate_max: Option<f64>,
+    // RFC 8985 RACK-TLP: losses are marked by send time against a reordering window instead
+    // of by dup acks, and a tail loss probe goes out ahead of the RTO. the loss marking
+    // needs SACK, without it dup acks still trigger fast retransmit
+    pub rack: bool,
+    // RFC 3168 ECN: negotiated on the SYN, data goes out ECT(0) and a CE mark from the
+    // network is handled like a loss without the loss
+    pub ecn: bool,
+    // RFC 5961: challenge acks answering suspect RSTs, SYNs and acks, at most this many per
+    // CHALLENGE_ACK_WINDOW. section 7 suggests a limit for the whole host, this one is counted
+    // by each connection on its own
+    pub challenge_ack_limit: u32,
 }
 impl TCPConfig {
     pub const DEFAULT_CAPACITY: SizeT = 64000 as SizeT;
     pub const MAX_PAYLOAD_SIZE: SizeT = 1000 as SizeT;
+    // RFC 879: the segment size assumed for a peer that doesn't send the MSS option
+    pub const MSS_DFLT: SizeT = 536;
     pub const TIMEOUT_DFLT: u16 = 1000;
     pub const MAX_RETX_ATTEMPTS: u32 = 8;
+    pub const RTO_MIN_DFLT: u16 = 200;
+    pub const RTO_MAX_DFLT: u16 = 60000;
+    pub const DUP_ACK_THRESHOLD: SizeT = 3;
+    pub const MAX_WSCALE: u8 = 14;
+    pub const CORK_TIMEOUT_DFLT: u16 = 200;
+    pub const ACK_DELAY_DFLT: u16 = 200;
+    pub const KEEPALIVE_IDLE_DFLT: SizeT = 7200 * 1000;
+    pub const KEEPALIVE_INTERVAL_DFLT: SizeT = 75 * 1000;
+    pub const KEEPALIVE_PROBES_DFLT: u32 = 9;
+    pub const CHALLENGE_ACK_LIMIT_DFLT: u32 = 1000;
+    pub const CHALLENGE_ACK_WINDOW: SizeT = 1000;
+
+    #[allow(dead_code)]
+    pub fn segment_size(&self) -> SizeT {
+        self.mss.unwrap_or(TCPConfig::MAX_PAYLOAD_SIZE)
+    }
+
+    // smallest shift that lets the largest receive capacity fit in the 16-bit window field
+    #[allow(dead_code)]
+    pub fn wscale(&self) -> u8 {
+        let capacity = self
+            .recv_capacity_max
+            .map_or(self.recv_capacity, |c| c.max(self.recv_capacity));
+        let mut shift = 0;
+        while shift < TCPConfig::MAX_WSCALE && (capacity >> shift) > u16::MAX as SizeT {
+            shift += 1;
+        }
+        shift
+    }
 }
 impl Default for TCPConfig {
     fn default() -> TCPConfig {
@@ -26,6 +106,32 @@ impl Default for TCPConfig {
             recv_capacity: TCPConfig::DEFAULT_CAPACITY,
             send_capacity: TCPConfig::DEFAULT_CAPACITY,
             fixed_isn: Some(WrappingInt32::new(0)),
+            congestion_control: CongestionControlAlgorithm::Disabled,
+            adaptive_rto: false,
+            rto_min: TCPConfig::RTO_MIN_DFLT,
+            rto_max: TCPConfig::RTO_MAX_DFLT,
+            fast_retransmit: false,
+            sack: false,
+            window_scale: false,
+            timestamps: false,
+            mss: None,
+            nodelay: false,
+            cork: false,
+            cork_timeout: TCPConfig::CORK_TIMEOUT_DFLT,
+            delayed_ack: false,
+            ack_delay: TCPConfig::ACK_DELAY_DFLT,
+            keepalive: false,
+            keepalive_idle: TCPConfig::KEEPALIVE_IDLE_DFLT,
+            keepalive_interval: TCPConfig::KEEPALIVE_INTERVAL_DFLT,
+            keepalive_probes: TCPConfig::KEEPALIVE_PROBES_DFLT,
+            persist: false,
+            sws_avoidance: false,
+            recv_capacity_max: None,
+            pacing: false,
+            pacing_rate_max: None,
+            rack: false,
+            ecn: false,
+            challenge_ack_limit: TCPConfig::CHALLENGE_ACK_LIMIT_DFLT,
         }
     }
 }
@@ -33,7 +139,7 @@ impl fmt::Display for TCPConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "(rt_timeout:{}, recv_capacity:{}, send_capacity:{}, isn:{})",
+            "(rt_timeout:{}, recv_capacity:{}, send_capacity:{}, isn:{}, cc:{}, adaptive_rto:{}, fast_retransmit:{}, sack:{}, window_scale:{}, timestamps:{}, mss:{}, nodelay:{}, cork:{}, delayed_ack:{}, keepalive:{}, persist:{}, sws_avoidance:{}, recv_capacity_max:{:?}, pacing:{}, rack:{}, ecn:{})",
             self.rt_timeout,
             self.recv_capacity,
             self.send_capacity,
@@ -41,7 +147,24 @@ impl fmt::Display for TCPConfig {
                 format!("{}", self.fixed_isn.unwrap())
             } else {
                 "None".to_string()
-            }
+            },
+            self.congestion_control,
+            self.adaptive_rto,
+            self.fast_retransmit,
+            self.sack,
+            self.window_scale,
+            self.timestamps,
+            self.segment_size(),
+            self.nodelay,
+            self.cork,
+            self.delayed_ack,
+            self.keepalive,
+            self.persist,
+            self.sws_avoidance,
+            self.recv_capacity_max,
+            self.pacing,
+            self.rack,
+            self.ecn
         )
     }
 }
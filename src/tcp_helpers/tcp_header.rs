@@ -1,14 +1,19 @@
+use crate::tcp_helpers::tcp_option::TCPOption;
 use crate::util::parser::{NetParser, NetUnparser, ParseResult};
 use crate::wrapping_integers::WrappingInt32;
 use crate::SizeT;
+use std::cmp::min;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct TCPHeader {
     pub sport: u16,
     pub dport: u16,
     pub seqno: WrappingInt32,
     pub ackno: WrappingInt32,
     pub(crate) doff: u8,
+    // rfc 3168 congestion window reduced and ECN-echo
+    pub cwr: bool,
+    pub ece: bool,
     urg: bool,
     pub ack: bool,
     psh: bool,
@@ -18,9 +23,21 @@ pub struct TCPHeader {
     pub win: u16,
     pub(crate) cksum: u16,
     uptr: u16,
+    pub mss: Option<u16>,
+    pub wscale: Option<u8>,
+    pub sack_permitted: bool,
+    // (TSval, TSecr)
+    pub timestamps: Option<(u32, u32)>,
+    // [left, right) edges of received blocks above the ackno
+    pub sack_blocks: Vec<(WrappingInt32, WrappingInt32)>,
+    // options of kinds we don't interpret, passed through as they are
+    pub unknown_options: Vec<TCPOption>,
 }
 impl TCPHeader {
     pub const LENGTH: SizeT = 20 as SizeT;
+    pub const MAX_LENGTH: SizeT = 60 as SizeT;
+    // 40 bytes of option space: 2 + 4 * 8
+    pub const MAX_SACK_BLOCKS: SizeT = 4;
 
     #[allow(dead_code)]
     pub fn new() -> TCPHeader {
@@ -30,6 +47,8 @@ impl TCPHeader {
             seqno: WrappingInt32::new(0),
             ackno: WrappingInt32::new(0),
             doff: (TCPHeader::LENGTH / 4) as u8,
+            cwr: false,
+            ece: false,
             urg: false,
             ack: false,
             psh: false,
@@ -39,6 +58,12 @@ impl TCPHeader {
             win: 0,
             cksum: 0,
             uptr: 0,
+            mss: None,
+            wscale: None,
+            sack_permitted: false,
+            timestamps: None,
+            sack_blocks: vec![],
+            unknown_options: vec![],
         }
     }
 
@@ -50,6 +75,8 @@ impl TCPHeader {
         self.doff = p.u8() >> 4;
 
         let fl_b = p.u8();
+        self.cwr = fl_b & 0b10000000 != 0;
+        self.ece = fl_b & 0b01000000 != 0;
         self.urg = if fl_b & 0b00100000 != 0 { true } else { false };
         self.ack = if fl_b & 0b00010000 != 0 { true } else { false };
         self.psh = if fl_b & 0b00001000 != 0 { true } else { false };
@@ -65,29 +92,165 @@ impl TCPHeader {
             return ParseResult::HeaderTooShort;
         }
 
-        // skip any options or anything extra in the header
-        p.remove_prefix(((self.doff * 4) as SizeT - TCPHeader::LENGTH) as SizeT);
-
         if p.error() {
             return p.get_error();
         }
 
+        match TCPOption::parse_all(p, (self.doff * 4) as SizeT - TCPHeader::LENGTH) {
+            Ok(options) => self.apply_options(options),
+            Err(e) => {
+                p.set_error(e);
+                return e;
+            }
+        }
+
         return ParseResult::NoError;
     }
 
+    fn apply_options(&mut self, options: Vec<TCPOption>) {
+        self.mss = None;
+        self.wscale = None;
+        self.sack_permitted = false;
+        self.timestamps = None;
+        self.sack_blocks.clear();
+        self.unknown_options.clear();
+
+        for opt in options {
+            match opt {
+                TCPOption::Mss(mss) => self.mss = Some(mss),
+                TCPOption::WindowScale(shift) => self.wscale = Some(shift),
+                TCPOption::SackPermitted => self.sack_permitted = true,
+                TCPOption::Timestamps(tsval, tsecr) => self.timestamps = Some((tsval, tsecr)),
+                TCPOption::Sack(blocks) => self.sack_blocks = blocks,
+                TCPOption::Eol | TCPOption::Nop => {}
+                TCPOption::Unknown(_, _) => self.unknown_options.push(opt),
+            }
+        }
+    }
+
+    // the options to put on the wire, NOPs aligning the multi-byte ones. what doesn't fit
+    // in the 40 bytes of option space is left out, the SACK option keeps the blocks that do
+    pub fn options(&self) -> Vec<TCPOption> {
+        let mut ret: Vec<TCPOption> = vec![];
+        if let Some(mss) = self.mss {
+            TCPHeader::push_options(&mut ret, vec![TCPOption::Mss(mss)]);
+        }
+        if let Some(shift) = self.wscale {
+            TCPHeader::push_options(
+                &mut ret,
+                vec![TCPOption::Nop, TCPOption::WindowScale(shift)],
+            );
+        }
+        if self.sack_permitted {
+            TCPHeader::push_options(
+                &mut ret,
+                vec![TCPOption::Nop, TCPOption::Nop, TCPOption::SackPermitted],
+            );
+        }
+        if let Some((tsval, tsecr)) = self.timestamps {
+            TCPHeader::push_options(
+                &mut ret,
+                vec![
+                    TCPOption::Nop,
+                    TCPOption::Nop,
+                    TCPOption::Timestamps(tsval, tsecr),
+                ],
+            );
+        }
+        let room = TCPHeader::MAX_LENGTH - TCPHeader::LENGTH - TCPHeader::options_length(&ret);
+        if !self.sack_blocks.is_empty() && room >= 4 + 8 {
+            let n = min(
+                self.sack_blocks.len(),
+                min((room - 4) / 8, TCPHeader::MAX_SACK_BLOCKS),
+            );
+            TCPHeader::push_options(
+                &mut ret,
+                vec![
+                    TCPOption::Nop,
+                    TCPOption::Nop,
+                    TCPOption::Sack(self.sack_blocks[..n].to_vec()),
+                ],
+            );
+        }
+        for opt in self.unknown_options.iter() {
+            TCPHeader::push_options(&mut ret, vec![opt.clone()]);
+        }
+        ret
+    }
+
+    fn options_length(opts: &[TCPOption]) -> SizeT {
+        opts.iter().map(|o| o.length()).sum()
+    }
+
+    // adds `opts` if they fit in what is left of the option space
+    fn push_options(ret: &mut Vec<TCPOption>, opts: Vec<TCPOption>) {
+        let len = TCPHeader::options_length(ret) + TCPHeader::options_length(&opts);
+        if TCPHeader::LENGTH + len <= TCPHeader::MAX_LENGTH {
+            ret.extend(opts);
+        }
+    }
+
+    fn serialize_options(&self) -> Vec<u8> {
+        let mut ret: Vec<u8> = vec![];
+        for opt in self.options() {
+            opt.serialize(&mut ret);
+        }
+        while !ret.len().is_multiple_of(4) {
+            TCPOption::Eol.serialize(&mut ret);
+        }
+        ret
+    }
+
+    // length of the header as serialized, options included
+    pub fn header_length(&self) -> SizeT {
+        TCPHeader::LENGTH + self.options_size().div_ceil(4) * 4
+    }
+
+    // what options() adds up to, worked out the same way without building the options
+    fn options_size(&self) -> SizeT {
+        let room = TCPHeader::MAX_LENGTH - TCPHeader::LENGTH;
+        let fit = |len: SizeT, n: SizeT| if len + n <= room { len + n } else { len };
+        let mut len = 0;
+        if self.mss.is_some() {
+            len = fit(len, TCPOption::Mss(0).length());
+        }
+        if self.wscale.is_some() {
+            len = fit(len, 1 + TCPOption::WindowScale(0).length());
+        }
+        if self.sack_permitted {
+            len = fit(len, 2 + TCPOption::SackPermitted.length());
+        }
+        if self.timestamps.is_some() {
+            len = fit(len, 2 + TCPOption::Timestamps(0, 0).length());
+        }
+        if !self.sack_blocks.is_empty() && room - len >= 4 + 8 {
+            let n = min(
+                self.sack_blocks.len(),
+                min((room - len - 4) / 8, TCPHeader::MAX_SACK_BLOCKS),
+            );
+            len = fit(len, 4 + 8 * n);
+        }
+        for opt in self.unknown_options.iter() {
+            len = fit(len, opt.length());
+        }
+        len
+    }
+
     pub fn serialize(&self) -> Vec<u8> {
-        // sanity check
-        assert!(self.doff >= 5, "TCP header too short");
+        let options = self.serialize_options();
+        let doff = ((TCPHeader::LENGTH + options.len()) / 4) as u8;
 
-        let mut ret: Vec<u8> = Vec::with_capacity((4 * self.doff) as usize);
+        let mut ret: Vec<u8> = Vec::with_capacity((4 * doff) as usize);
 
         NetUnparser::u16(&mut ret, self.sport);
         NetUnparser::u16(&mut ret, self.dport);
         NetUnparser::u32(&mut ret, self.seqno.raw_value());
         NetUnparser::u32(&mut ret, self.ackno.raw_value());
-        NetUnparser::u8(&mut ret, self.doff << 4);
+        NetUnparser::u8(&mut ret, doff << 4);
 
-        let fl_b: u8 = if self.urg { 0b00100000 } else { 0 }
+        let fl_b: u8 = if self.cwr { 0b10000000 } else { 0 }
+            | if self.ece { 0b01000000 } else { 0 }
+            | if self.urg { 0b00100000 } else { 0 }
             | if self.ack { 0b00010000 } else { 0 }
             | if self.psh { 0b00001000 } else { 0 }
             | if self.rst { 0b00000100 } else { 0 }
@@ -100,22 +263,26 @@ impl TCPHeader {
 
         NetUnparser::u16(&mut ret, self.uptr);
 
-        ret.shrink_to((4 * self.doff) as usize);
+        ret.extend(options);
+
+        ret.shrink_to((4 * doff) as usize);
 
         ret
     }
 
     pub fn to_string(&self) -> String {
-        format!("TCP source port: {}\nTCP dest port: {}\nTCP seqno: {}\nTCP ackno: {}\nTCP doff: {}\nFlags: urg: {} ack: {} psh: {} rst: {} syn: {} fin: {}\nTCP winsize: {}\nTCP cksum: {}\nTCP uptr: {}\n", self.sport, self.dport, self.seqno, self.ackno, self.doff, self.urg, self.ack, self.psh, self.rst, self.syn, self.fin, self.win, self.cksum, self.uptr)
+        format!("TCP source port: {}\nTCP dest port: {}\nTCP seqno: {}\nTCP ackno: {}\nTCP doff: {}\nFlags: cwr: {} ece: {} urg: {} ack: {} psh: {} rst: {} syn: {} fin: {}\nTCP winsize: {}\nTCP cksum: {}\nTCP uptr: {}\nMSS: {:?}\nWindow scale: {:?}\nSACK permitted: {}\nTimestamps: {:?}\nSACK blocks: {:?}\nUnknown options: {:?}\n", self.sport, self.dport, self.seqno, self.ackno, self.doff, self.cwr, self.ece, self.urg, self.ack, self.psh, self.rst, self.syn, self.fin, self.win, self.cksum, self.uptr, self.mss, self.wscale, self.sack_permitted, self.timestamps, self.sack_blocks, self.unknown_options)
     }
 
     pub fn summary(&self) -> String {
         format!(
-            "Header(flags={}{}{}{},seqno={},ack={},win={})",
+            "Header(flags={}{}{}{}{}{},seqno={},ack={},win={})",
             if self.syn { "S" } else { "" },
             if self.ack { "A" } else { "" },
             if self.rst { "R" } else { "" },
             if self.fin { "F" } else { "" },
+            if self.ece { "E" } else { "" },
+            if self.cwr { "W" } else { "" },
             self.seqno,
             self.ackno,
             self.win
@@ -127,7 +294,8 @@ impl PartialEq<Self> for TCPHeader {
     fn eq(&self, other: &Self) -> bool {
         self.seqno == other.seqno
             && self.ackno == other.ackno
-            && self.doff == other.doff
+            && self.cwr == other.cwr
+            && self.ece == other.ece
             && self.urg == other.urg
             && self.ack == other.ack
             && self.psh == other.psh
@@ -136,6 +304,12 @@ impl PartialEq<Self> for TCPHeader {
             && self.fin == other.fin
             && self.win == other.win
             && self.uptr == other.uptr
+            && self.mss == other.mss
+            && self.wscale == other.wscale
+            && self.sack_permitted == other.sack_permitted
+            && self.timestamps == other.timestamps
+            && self.sack_blocks == other.sack_blocks
+            && self.unknown_options == other.unknown_options
     }
 }
 impl Eq for TCPHeader {}
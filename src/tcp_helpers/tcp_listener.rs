@@ -0,0 +1,601 @@
+use crate::tcp_connection::TCPConnection;
+use crate::tcp_helpers::fd_adapter::{AsFdAdapterBaseMut, FourTuple};
+use crate::tcp_helpers::syn_cookie::{SynCookieMode, SynCookies};
+use crate::tcp_helpers::tcp_config::{FdAdapterConfig, TCPConfig};
+use crate::tcp_helpers::tcp_header::TCPHeader;
+use crate::tcp_helpers::tcp_segment::TCPSegment;
+use crate::tcp_helpers::tcp_sponge_socket::AsLocalStreamSocketMut;
+use crate::tcp_helpers::tcp_state::State;
+use crate::util::aeventloop::AEventLoop;
+use crate::util::buffer::Buffer;
+use crate::util::eventloop::Direction;
+use crate::util::eventloop::Result::Exit;
+use crate::util::file_descriptor::{AsFileDescriptor, AsFileDescriptorMut, FileDescriptor};
+use crate::util::socket::{AsSocketMut, LocalStreamSocket};
+use crate::util::util::{system_call, timestamp_ms};
+use crate::SizeT;
+use libc::SHUT_WR;
+use std::cmp::min;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::c_void;
+use std::net::SocketAddrV4;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+// a connection from its SYN on. the app gets the other end of thread_data once the
+// handshake completes
+#[derive(Debug)]
+struct ListenerConnection {
+    tcp: TCPConnection,
+    thread_data: LocalStreamSocket,
+    app_data: Option<LocalStreamSocket>,
+    inbound_shutdown: bool,
+    outbound_shutdown: bool,
+}
+
+#[derive(Debug)]
+struct ListenerShared {
+    cfg: TCPConfig,
+    backlog: SizeT,
+    connections: HashMap<FourTuple, ListenerConnection>,
+    // established connections waiting for accept()
+    accept_queue: VecDeque<(FourTuple, LocalStreamSocket)>,
+    // connections the event loop has no rules for yet
+    unregistered: Vec<FourTuple>,
+    syn_cookie_mode: SynCookieMode,
+    syn_cookies: SynCookies,
+    syn_cookies_sent: SizeT,
+    syn_cookies_accepted: SizeT,
+}
+impl ListenerShared {
+    // connections still in the handshake
+    fn syn_queue_len(&self) -> SizeT {
+        self.connections
+            .values()
+            .filter(|c| c.app_data.is_some())
+            .count()
+    }
+
+    // a SYN opens a connection while both queues have room, or past the SYN queue draws a
+    // SYN/ACK with a cookie for an ISN. an ACK of a valid cookie opens the connection then,
+    // or is dropped for the peer to retry while the accept queue is full. other segments for
+    // unknown connections are answered with a RST
+    fn segment_received<AdapterT>(
+        &mut self,
+        tuple: FourTuple,
+        seg: &TCPSegment,
+        adapter: &mut AdapterT,
+    ) where
+        AdapterT: AsFdAdapterBaseMut,
+    {
+        if let Some(c) = self.connections.get_mut(&tuple) {
+            c.tcp.segment_received(seg);
+            return;
+        }
+
+        let h = seg.header();
+        let cookie_mss = if h.ack && !h.syn && !h.rst && self.syn_cookie_mode != SynCookieMode::Off
+        {
+            self.syn_cookies
+                .check(&tuple, h.seqno - 1, h.ackno - 1, timestamp_ms())
+        } else {
+            None
+        };
+        if h.syn && !h.ack && !h.rst {
+            if self.accept_queue.len() >= self.backlog {
+                return;
+            }
+            let syn_queue_full = self.syn_queue_len() >= self.backlog;
+            match self.syn_cookie_mode {
+                SynCookieMode::Always => self.send_syn_cookie(tuple, seg, adapter),
+                SynCookieMode::Auto if syn_queue_full => self.send_syn_cookie(tuple, seg, adapter),
+                _ if syn_queue_full => {}
+                _ => {
+                    let cfg = self.cfg;
+                    self.open(tuple, cfg).tcp.segment_received(seg);
+                }
+            }
+        } else if let Some(mss) = cookie_mss {
+            if self.accept_queue.len() < self.backlog {
+                self.accept_syn_cookie(tuple, seg, mss);
+                self.syn_cookies_accepted += 1;
+            }
+        } else if !h.rst {
+            let mut header = TCPHeader::new();
+            header.rst = true;
+            if h.ack {
+                header.seqno = h.ackno;
+            } else {
+                header.ack = true;
+                header.ackno = h.seqno + seg.length_in_sequence_space() as u32;
+            }
+            adapter.write_to(&tuple, &mut TCPSegment::new(header, Buffer::new(vec![])));
+        }
+    }
+
+    fn open(&mut self, tuple: FourTuple, cfg: TCPConfig) -> &mut ListenerConnection {
+        let mut socks = [0; 2];
+        let ret =
+            unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, socks.as_mut_ptr()) };
+        system_call("socketpair", ret as i32, 0);
+
+        let mut c = ListenerConnection {
+            tcp: TCPConnection::new(cfg),
+            thread_data: LocalStreamSocket::new(FileDescriptor::new(socks[1])),
+            app_data: Some(LocalStreamSocket::new(FileDescriptor::new(socks[0]))),
+            inbound_shutdown: false,
+            outbound_shutdown: false,
+        };
+        c.thread_data.set_blocking(false);
+        self.unregistered.push(tuple);
+        self.connections.entry(tuple).or_insert(c)
+    }
+
+    // the SYN/ACK a connection would send, minus the options there is no room for in the
+    // cookie: window scaling, SACK, timestamps and ECN
+    fn send_syn_cookie<AdapterT>(
+        &mut self,
+        tuple: FourTuple,
+        seg: &TCPSegment,
+        adapter: &mut AdapterT,
+    ) where
+        AdapterT: AsFdAdapterBaseMut,
+    {
+        let mss = self.cfg.segment_size() as u16;
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.ack = true;
+        header.seqno = self.syn_cookies.make(
+            &tuple,
+            seg.header().seqno,
+            min(seg.header().mss.unwrap_or(TCPConfig::MSS_DFLT as u16), mss),
+            timestamp_ms(),
+        );
+        header.ackno = seg.header().seqno + 1;
+        header.win = min(self.cfg.recv_capacity, u16::MAX as SizeT) as u16;
+        header.mss = Some(mss);
+        adapter.write_to(&tuple, &mut TCPSegment::new(header, Buffer::new(vec![])));
+        self.syn_cookies_sent += 1;
+    }
+
+    // replays the handshake the cookie stands for into a new connection, which then takes
+    // the ACK like any other
+    fn accept_syn_cookie(&mut self, tuple: FourTuple, seg: &TCPSegment, mss: u16) {
+        let h = seg.header();
+        let peer_isn = h.seqno - 1;
+        let isn = h.ackno - 1;
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            window_scale: false,
+            sack: false,
+            timestamps: false,
+            ecn: false,
+            ..self.cfg
+        };
+        let mut syn = TCPHeader::new();
+        syn.syn = true;
+        syn.seqno = peer_isn;
+        syn.win = h.win;
+        syn.mss = Some(mss);
+
+        let c = self.open(tuple, cfg);
+        c.tcp
+            .segment_received(&TCPSegment::new(syn, Buffer::new(vec![])));
+        c.tcp.segments_out_mut().clear();
+        c.tcp.segment_received(seg);
+    }
+}
+
+// one end of an accepted connection, read and written like the socket of a TCPSpongeSocket
+#[derive(Debug)]
+pub struct TCPStreamSocket {
+    pub main_thread_data: Arc<Mutex<LocalStreamSocket>>,
+    tuple: FourTuple,
+}
+impl AsLocalStreamSocketMut for TCPStreamSocket {
+    fn as_socket_mut(&mut self) -> Arc<Mutex<LocalStreamSocket>> {
+        self.main_thread_data.clone()
+    }
+}
+impl TCPStreamSocket {
+    #[allow(dead_code)]
+    pub fn local_address(&self) -> SocketAddrV4 {
+        self.tuple.0
+    }
+
+    #[allow(dead_code)]
+    pub fn peer_address(&self) -> SocketAddrV4 {
+        self.tuple.1
+    }
+
+    // no more data from us, the connection closes once the peer is done as well
+    #[allow(dead_code)]
+    pub fn shutdown(&mut self) {
+        self.main_thread_data.lock().unwrap().shutdown(SHUT_WR);
+    }
+}
+
+// serves any number of peers on one adapter. incoming segments are demultiplexed by their
+// (local, remote) addresses, a SYN from a new peer starts a connection that accept() hands
+// out once it is established
+#[derive(Debug)]
+pub struct TCPListener<AdapterT> {
+    pub datagram_adapter: Arc<Mutex<AdapterT>>,
+    shared: Arc<(Mutex<ListenerShared>, Condvar)>,
+    event_loop: Arc<Mutex<AEventLoop>>,
+    listener_thread: Option<JoinHandle<()>>,
+    abort: Arc<AtomicBool>,
+}
+impl<AdapterT> Drop for TCPListener<AdapterT> {
+    fn drop(&mut self) {
+        self.abort.store(true, Ordering::SeqCst);
+        if let Some(j) = self.listener_thread.take() {
+            j.join().expect("TCPListener join during Drop");
+        }
+    }
+}
+impl<AdapterT> TCPListener<AdapterT>
+where
+    AdapterT: AsFdAdapterBaseMut + AsFileDescriptorMut + Send + 'static,
+{
+    pub const TCP_TICK_MS: SizeT = 10;
+    pub const BACKLOG_DFLT: SizeT = 16;
+
+    #[allow(dead_code)]
+    pub fn new(_adapter: AdapterT) -> TCPListener<AdapterT> {
+        TCPListener {
+            datagram_adapter: Arc::new(Mutex::new(_adapter)),
+            shared: Arc::new((
+                Mutex::new(ListenerShared {
+                    cfg: TCPConfig::default(),
+                    backlog: TCPListener::<AdapterT>::BACKLOG_DFLT,
+                    connections: HashMap::new(),
+                    accept_queue: VecDeque::new(),
+                    unregistered: vec![],
+                    syn_cookie_mode: SynCookieMode::Auto,
+                    syn_cookies: SynCookies::new(),
+                    syn_cookies_sent: 0,
+                    syn_cookies_accepted: 0,
+                }),
+                Condvar::new(),
+            )),
+            event_loop: Arc::new(Mutex::new(AEventLoop::new())),
+            listener_thread: None,
+            abort: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // `backlog` bounds both the connections still in the handshake and the established
+    // ones not accepted yet. SYNs beyond it are dropped for the peer to retry, unless SYN
+    // cookies answer them past a full SYN queue
+    #[allow(dead_code)]
+    pub fn listen(&mut self, c_tcp: &TCPConfig, c_ad: FdAdapterConfig, backlog: SizeT) {
+        assert!(self.listener_thread.is_none(), "listen() called twice");
+
+        let mut cfg = *c_tcp;
+        if cfg.mss.is_none() {
+            cfg.mss = Some(self.datagram_adapter.lock().unwrap().mss());
+        }
+        {
+            let mut s = self.shared.0.lock().unwrap();
+            s.cfg = cfg;
+            s.backlog = backlog;
+        }
+        self.datagram_adapter.lock().unwrap().set_config(c_ad);
+
+        let datagram_adapter_rc = Arc::new(Mutex::new(
+            self.datagram_adapter
+                .lock()
+                .unwrap()
+                .as_file_descriptor()
+                .clone(),
+        ));
+        let adapter_ = self.datagram_adapter.clone();
+        let shared_ = self.shared.clone();
+        let abort_ = self.abort.clone();
+        self.event_loop.lock().unwrap().add_rule(
+            datagram_adapter_rc,
+            Direction::In,
+            Box::new(move || {
+                let mut adapter = adapter_.lock().unwrap();
+                if let Some((tuple, seg)) = adapter.read_from() {
+                    let mut s = shared_.0.lock().unwrap();
+                    s.segment_received(tuple, &seg, &mut *adapter);
+                }
+            }),
+            Box::new(move || !abort_.load(Ordering::SeqCst)),
+            Box::new(|| {}),
+        );
+
+        eprintln!("DEBUG: Listening on {}...", c_ad.source);
+        let shared_ = self.shared.clone();
+        let event_loop_ = self.event_loop.clone();
+        let abort_ = self.abort.clone();
+        let adapter_ = self.datagram_adapter.clone();
+        let _ = self.listener_thread.insert(
+            thread::Builder::new()
+                .name("listener".to_string())
+                .spawn(move || listener_main(shared_, event_loop_, abort_, adapter_))
+                .unwrap(),
+        );
+    }
+
+    // SYN cookies are on once the SYN queue is full by default
+    #[allow(dead_code)]
+    pub fn set_syn_cookies(&mut self, mode: SynCookieMode) {
+        self.shared.0.lock().unwrap().syn_cookie_mode = mode;
+    }
+
+    #[allow(dead_code)]
+    pub fn syn_cookies_sent(&self) -> SizeT {
+        self.shared.0.lock().unwrap().syn_cookies_sent
+    }
+
+    // connections opened from the ACK of a cookie
+    #[allow(dead_code)]
+    pub fn syn_cookies_accepted(&self) -> SizeT {
+        self.shared.0.lock().unwrap().syn_cookies_accepted
+    }
+
+    // blocks until a connection is established, None once the listener stopped
+    #[allow(dead_code)]
+    pub fn accept(&mut self) -> Option<TCPStreamSocket> {
+        let (lock, cvar) = &*self.shared;
+        let mut s = lock.lock().unwrap();
+        loop {
+            if let Some((tuple, sock)) = s.accept_queue.pop_front() {
+                return Some(TCPStreamSocket {
+                    main_thread_data: Arc::new(Mutex::new(sock)),
+                    tuple,
+                });
+            }
+            if self.abort.load(Ordering::SeqCst) || self.listener_thread.is_none() {
+                return None;
+            }
+            s = cvar
+                .wait_timeout(s, Duration::from_millis(Self::TCP_TICK_MS as u64))
+                .unwrap()
+                .0;
+        }
+    }
+
+    // connections still in the handshake
+    #[allow(dead_code)]
+    pub fn syn_queue_len(&self) -> SizeT {
+        self.shared.0.lock().unwrap().syn_queue_len()
+    }
+
+    // established connections waiting for accept()
+    #[allow(dead_code)]
+    pub fn accept_queue_len(&self) -> SizeT {
+        self.shared.0.lock().unwrap().accept_queue.len()
+    }
+
+    // connections the listener still runs, accepted or not
+    #[allow(dead_code)]
+    pub fn connection_count(&self) -> SizeT {
+        self.shared.0.lock().unwrap().connections.len()
+    }
+
+    // stops listening, connections that are still open are dropped
+    #[allow(dead_code)]
+    pub fn close(&mut self) {
+        self.abort.store(true, Ordering::SeqCst);
+        if let Some(j) = self.listener_thread.take() {
+            j.join().expect("TCPListener thread joined");
+        }
+        let (lock, cvar) = &*self.shared;
+        let mut s = lock.lock().unwrap();
+        if s.connections.values().any(|c| c.tcp.active()) {
+            eprintln!("Warning: TCPListener closed with connections still open");
+        }
+        s.connections.clear();
+        cvar.notify_all();
+    }
+}
+
+// rules 2 and 3 of TCPSpongeSocket for one connection: the app's writes into the
+// TCPConnection and the inbound stream back to the app
+fn add_connection_rules(
+    event_loop: &mut AEventLoop,
+    shared: &Arc<(Mutex<ListenerShared>, Condvar)>,
+    tuple: FourTuple,
+    fd: FileDescriptor,
+) {
+    let fd_rc = Arc::new(Mutex::new(fd));
+
+    let shared_ = shared.clone();
+    let shared_1 = shared.clone();
+    let shared_2 = shared.clone();
+    event_loop.add_rule(
+        fd_rc.clone(),
+        Direction::In,
+        Box::new(move || {
+            let mut s = shared_.0.lock().unwrap();
+            let c = match s.connections.get_mut(&tuple) {
+                Some(c) => c,
+                None => return,
+            };
+            let data = c
+                .thread_data
+                .as_socket_mut()
+                .read(c.tcp.remaining_outbound_capacity() as u32);
+            let amount_written = c.tcp.write(data.as_slice());
+            assert_eq!(
+                amount_written,
+                data.len(),
+                "TCPConnection::write() accepted less than advertised length"
+            );
+            if c.thread_data.eof() {
+                c.tcp.end_input_stream();
+                c.outbound_shutdown = true;
+            }
+        }),
+        Box::new(move || {
+            let s = shared_1.0.lock().unwrap();
+            match s.connections.get(&tuple) {
+                Some(c) => {
+                    c.tcp.active()
+                        && !c.outbound_shutdown
+                        && c.tcp.remaining_outbound_capacity() > 0
+                }
+                None => false,
+            }
+        }),
+        Box::new(move || {
+            let mut s = shared_2.0.lock().unwrap();
+            if let Some(c) = s.connections.get_mut(&tuple) {
+                if !c.outbound_shutdown {
+                    c.tcp.end_input_stream();
+                    c.outbound_shutdown = true;
+                }
+            }
+        }),
+    );
+
+    let shared_ = shared.clone();
+    let shared_1 = shared.clone();
+    event_loop.add_rule(
+        fd_rc,
+        Direction::Out,
+        Box::new(move || {
+            let mut s = shared_.0.lock().unwrap();
+            let c = match s.connections.get_mut(&tuple) {
+                Some(c) => c,
+                None => return,
+            };
+            let inbound = c.tcp.inbound_stream_mut();
+            let amount_to_write = min(65536, inbound.buffer_size());
+            let buffer = inbound.peek_output(amount_to_write);
+            match write_to_app(&mut c.thread_data, buffer.as_slice()) {
+                Some(bytes_written) => inbound.pop_output(bytes_written),
+                None => {
+                    let all = inbound.buffer_size();
+                    inbound.pop_output(all);
+                    c.inbound_shutdown = true;
+                    return;
+                }
+            }
+
+            if inbound.eof() || inbound.error() {
+                c.thread_data.shutdown(SHUT_WR);
+                c.inbound_shutdown = true;
+            }
+        }),
+        Box::new(move || {
+            let s = shared_1.0.lock().unwrap();
+            match s.connections.get(&tuple) {
+                Some(c) => {
+                    let inbound = c.tcp.inbound_stream();
+                    !inbound.buffer_empty()
+                        || ((inbound.eof() || inbound.error()) && !c.inbound_shutdown)
+                }
+                None => false,
+            }
+        }),
+        Box::new(|| {}),
+    );
+}
+
+// the app may have closed its socket without reading everything, what arrives after that
+// is dropped instead of failing on EPIPE
+fn write_to_app(sock: &mut LocalStreamSocket, buf: &[u8]) -> Option<SizeT> {
+    if buf.is_empty() {
+        return Some(0);
+    }
+    let ret = unsafe {
+        libc::send(
+            sock.fd_num(),
+            buf.as_ptr() as *const c_void,
+            buf.len(),
+            libc::MSG_NOSIGNAL,
+        )
+    };
+    sock.register_write();
+    if ret < 0 {
+        let err = std::io::Error::last_os_error().raw_os_error();
+        if err == Some(libc::EPIPE) || err == Some(libc::ECONNRESET) {
+            return None;
+        }
+        system_call("send", ret as i32, 0);
+    }
+    Some(ret as SizeT)
+}
+
+fn listener_main<AdapterT>(
+    shared: Arc<(Mutex<ListenerShared>, Condvar)>,
+    event_loop: Arc<Mutex<AEventLoop>>,
+    abort: Arc<AtomicBool>,
+    adapter: Arc<Mutex<AdapterT>>,
+) where
+    AdapterT: AsFdAdapterBaseMut + AsFileDescriptorMut + Send + 'static,
+{
+    let mut base_time = timestamp_ms();
+
+    while !abort.load(Ordering::SeqCst) {
+        {
+            let mut event_loop_ = event_loop.lock().unwrap();
+            let mut s = shared.0.lock().unwrap();
+            for tuple in std::mem::take(&mut s.unregistered) {
+                let fd = s.connections[&tuple]
+                    .thread_data
+                    .as_file_descriptor()
+                    .clone();
+                add_connection_rules(&mut event_loop_, &shared, tuple, fd);
+            }
+        }
+
+        let ret = event_loop
+            .lock()
+            .unwrap()
+            .wait_next_event(TCPListener::<AdapterT>::TCP_TICK_MS as i32);
+        if ret == Exit {
+            break;
+        }
+
+        let next_time = timestamp_ms();
+        let elapsed = (next_time - base_time) as SizeT;
+        base_time = next_time;
+
+        let (lock, cvar) = &*shared;
+        let mut s = lock.lock().unwrap();
+        let mut adapter_ = adapter.lock().unwrap();
+        let mut established = false;
+        let ListenerShared {
+            connections,
+            accept_queue,
+            ..
+        } = &mut *s;
+        for (tuple, c) in connections.iter_mut() {
+            if c.tcp.active() {
+                c.tcp.tick(elapsed);
+            }
+            while let Some(mut seg) = c.tcp.segments_out_mut().pop_front() {
+                adapter_.write_to(tuple, &mut seg);
+            }
+            if c.app_data.is_some()
+                && !matches!(c.tcp.state(), State::LISTEN | State::SynRcvd | State::RESET)
+            {
+                accept_queue.push_back((*tuple, c.app_data.take().unwrap()));
+                established = true;
+            }
+        }
+        adapter_.tick(elapsed);
+
+        // a connection goes once closed and drained to the app, or reset in the handshake
+        connections.retain(|_, c| {
+            if c.tcp.active() || !(c.inbound_shutdown || c.app_data.is_some()) {
+                return true;
+            }
+            c.thread_data.close();
+            false
+        });
+
+        if established {
+            cvar.notify_all();
+        }
+    }
+}
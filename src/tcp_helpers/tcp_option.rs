@@ -0,0 +1,147 @@
+use crate::util::parser::{NetParser, NetUnparser, ParseResult};
+use crate::wrapping_integers::WrappingInt32;
+use crate::SizeT;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TCPOption {
+    Eol,
+    Nop,
+    Mss(u16),
+    WindowScale(u8),
+    SackPermitted,
+    // [left, right) edges
+    Sack(Vec<(WrappingInt32, WrappingInt32)>),
+    // (TSval, TSecr)
+    Timestamps(u32, u32),
+    // kind and data of an option we don't interpret, kept to be passed through
+    Unknown(u8, Vec<u8>),
+}
+impl TCPOption {
+    pub const KIND_EOL: u8 = 0;
+    pub const KIND_NOP: u8 = 1;
+    pub const KIND_MSS: u8 = 2;
+    pub const KIND_WSCALE: u8 = 3;
+    pub const KIND_SACK_PERMITTED: u8 = 4;
+    pub const KIND_SACK: u8 = 5;
+    pub const KIND_TIMESTAMPS: u8 = 8;
+
+    #[allow(dead_code)]
+    pub fn kind(&self) -> u8 {
+        match self {
+            TCPOption::Eol => TCPOption::KIND_EOL,
+            TCPOption::Nop => TCPOption::KIND_NOP,
+            TCPOption::Mss(_) => TCPOption::KIND_MSS,
+            TCPOption::WindowScale(_) => TCPOption::KIND_WSCALE,
+            TCPOption::SackPermitted => TCPOption::KIND_SACK_PERMITTED,
+            TCPOption::Sack(_) => TCPOption::KIND_SACK,
+            TCPOption::Timestamps(_, _) => TCPOption::KIND_TIMESTAMPS,
+            TCPOption::Unknown(kind, _) => *kind,
+        }
+    }
+
+    // bytes taken on the wire, kind and length included
+    #[allow(dead_code)]
+    pub fn length(&self) -> SizeT {
+        match self {
+            TCPOption::Eol | TCPOption::Nop => 1,
+            TCPOption::Mss(_) => 4,
+            TCPOption::WindowScale(_) => 3,
+            TCPOption::SackPermitted => 2,
+            TCPOption::Sack(blocks) => 2 + 8 * blocks.len(),
+            TCPOption::Timestamps(_, _) => 10,
+            TCPOption::Unknown(_, data) => 2 + data.len(),
+        }
+    }
+
+    // the options area of `len` bytes. NOP and EOL are layout only and not returned,
+    // anything after EOL is padding
+    #[allow(dead_code)]
+    pub fn parse_all(p: &mut NetParser<'_>, len: SizeT) -> Result<Vec<TCPOption>, ParseResult> {
+        let mut ret: Vec<TCPOption> = vec![];
+        let mut remaining = len;
+        while remaining > 0 {
+            let kind = p.u8();
+            remaining -= 1;
+            if kind == TCPOption::KIND_EOL {
+                p.remove_prefix(remaining);
+                break;
+            }
+            if kind == TCPOption::KIND_NOP {
+                continue;
+            }
+
+            if remaining == 0 {
+                return Err(ParseResult::BadOption);
+            }
+            let opt_len = p.u8() as SizeT;
+            remaining -= 1;
+            if opt_len < 2 || opt_len - 2 > remaining {
+                return Err(ParseResult::BadOption);
+            }
+            remaining -= opt_len - 2;
+
+            let opt = match kind {
+                TCPOption::KIND_MSS if opt_len == 4 => TCPOption::Mss(p.u16()),
+                TCPOption::KIND_WSCALE if opt_len == 3 => TCPOption::WindowScale(p.u8()),
+                TCPOption::KIND_SACK_PERMITTED if opt_len == 2 => TCPOption::SackPermitted,
+                TCPOption::KIND_SACK if opt_len > 2 && (opt_len - 2).is_multiple_of(8) => {
+                    let mut blocks = Vec::with_capacity((opt_len - 2) / 8);
+                    for _ in 0..(opt_len - 2) / 8 {
+                        let left = WrappingInt32::new(p.u32());
+                        let right = WrappingInt32::new(p.u32());
+                        blocks.push((left, right));
+                    }
+                    TCPOption::Sack(blocks)
+                }
+                TCPOption::KIND_TIMESTAMPS if opt_len == 10 => {
+                    let tsval = p.u32();
+                    let tsecr = p.u32();
+                    TCPOption::Timestamps(tsval, tsecr)
+                }
+                TCPOption::KIND_MSS
+                | TCPOption::KIND_WSCALE
+                | TCPOption::KIND_SACK_PERMITTED
+                | TCPOption::KIND_SACK
+                | TCPOption::KIND_TIMESTAMPS => return Err(ParseResult::BadOption),
+                _ => {
+                    let data: Vec<u8> = (0..opt_len - 2).map(|_| p.u8()).collect();
+                    TCPOption::Unknown(kind, data)
+                }
+            };
+            if p.error() {
+                return Err(p.get_error());
+            }
+            ret.push(opt);
+        }
+
+        if p.error() {
+            return Err(p.get_error());
+        }
+        Ok(ret)
+    }
+
+    #[allow(dead_code)]
+    pub fn serialize(&self, s: &mut Vec<u8>) {
+        NetUnparser::u8(s, self.kind());
+        match self {
+            TCPOption::Eol | TCPOption::Nop => return,
+            _ => NetUnparser::u8(s, self.length() as u8),
+        }
+        match self {
+            TCPOption::Mss(mss) => NetUnparser::u16(s, *mss),
+            TCPOption::WindowScale(shift) => NetUnparser::u8(s, *shift),
+            TCPOption::Sack(blocks) => {
+                for (left, right) in blocks {
+                    NetUnparser::u32(s, left.raw_value());
+                    NetUnparser::u32(s, right.raw_value());
+                }
+            }
+            TCPOption::Timestamps(tsval, tsecr) => {
+                NetUnparser::u32(s, *tsval);
+                NetUnparser::u32(s, *tsecr);
+            }
+            TCPOption::Unknown(_, data) => s.extend(data),
+            _ => {}
+        }
+    }
+}
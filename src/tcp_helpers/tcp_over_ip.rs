@@ -1,4 +1,4 @@
-use crate::tcp_helpers::fd_adapter::FdAdapterBase;
+use crate::tcp_helpers::fd_adapter::{FdAdapterBase, FourTuple};
 use crate::tcp_helpers::ipv4_header::IPv4Header;
 use crate::tcp_helpers::tcp_segment::TCPSegment;
 use crate::util::buffer::Buffer;
@@ -36,13 +36,15 @@ impl TCPOverIPv4Adapter {
         let pseudo_cksum = ip_dgram.header().pseudo_cksum();
         let ip_dgram_dst = ip_dgram.header().dst;
         let ip_dgram_src = ip_dgram.header().src;
+        let ecn = ip_dgram.header().ecn();
         let payload_ = ip_dgram.payload;
 
         let ret = TCPSegment::parse_new(payload_, pseudo_cksum);
         if ret.is_err() {
             return None;
         }
-        let tcp_seg = ret.ok().unwrap();
+        let mut tcp_seg = ret.ok().unwrap();
+        tcp_seg.set_ecn(ecn);
 
         if tcp_seg.header().dport != self.fd_adapter_base.config().source.port() {
             return None;
@@ -69,16 +71,60 @@ impl TCPOverIPv4Adapter {
         Some(tcp_seg)
     }
 
+    // any TCP segment to our port, whoever sent it. when bound to no particular address the
+    // local one is taken from the datagram
+    #[allow(dead_code)]
+    pub fn unwrap_tcp_in_ip_from(
+        &self,
+        ip_dgram: InternetDatagram,
+    ) -> Option<(FourTuple, TCPSegment)> {
+        let local = self.fd_adapter_base.config().source;
+        if !local.ip().is_unspecified() && ip_dgram.header().dst != u32::from(*local.ip()) {
+            return None;
+        }
+        if ip_dgram.header().proto != IPv4Header::PROTO_TCP {
+            return None;
+        }
+
+        let pseudo_cksum = ip_dgram.header().pseudo_cksum();
+        let dst = Ipv4Addr::from(ip_dgram.header().dst);
+        let src = Ipv4Addr::from(ip_dgram.header().src);
+        let ecn = ip_dgram.header().ecn();
+
+        let mut tcp_seg = TCPSegment::parse_new(ip_dgram.payload, pseudo_cksum).ok()?;
+        tcp_seg.set_ecn(ecn);
+        if tcp_seg.header().dport != local.port() {
+            return None;
+        }
+
+        let tuple = (
+            SocketAddrV4::new(dst, local.port()),
+            SocketAddrV4::new(src, tcp_seg.header().sport),
+        );
+        Some((tuple, tcp_seg))
+    }
+
     #[allow(dead_code)]
     pub fn wrap_tcp_in_ip(&mut self, seg: &mut TCPSegment) -> InternetDatagram {
-        seg.header_mut().sport = self.fd_adapter_base.config().source.port();
-        seg.header_mut().dport = self.fd_adapter_base.config().destination.port();
+        let tuple = (
+            self.fd_adapter_base.config().source,
+            self.fd_adapter_base.config().destination,
+        );
+        self.wrap_tcp_in_ip_to(&tuple, seg)
+    }
+
+    #[allow(dead_code)]
+    pub fn wrap_tcp_in_ip_to(&self, tuple: &FourTuple, seg: &mut TCPSegment) -> InternetDatagram {
+        seg.header_mut().sport = tuple.0.port();
+        seg.header_mut().dport = tuple.1.port();
 
         let mut header = IPv4Header::new();
-        header.src = u32::from(self.fd_adapter_base.config().source.ip().clone());
-        header.dst = u32::from(self.fd_adapter_base.config().destination.ip().clone());
-        header.len =
-            ((header.hlen * 4 + seg.header().doff * 4) as SizeT + seg.payload().size()) as u16;
+        header.src = u32::from(*tuple.0.ip());
+        header.dst = u32::from(*tuple.1.ip());
+        header.set_ecn(seg.ecn());
+        header.len = ((header.hlen * 4) as SizeT
+            + seg.header().header_length()
+            + seg.payload().size()) as u16;
 
         let check_sum = header.pseudo_cksum();
         InternetDatagram::new(header, Buffer::new(seg.serialize(check_sum)))
@@ -8,6 +8,8 @@ use crate::SizeT;
 pub struct TCPSegment {
     header: TCPHeader,
     payload: Buffer,
+    // ECN codepoint of the datagram carrying it, not part of the segment on the wire
+    ecn: u8,
 }
 impl TCPSegment {
     #[allow(dead_code)]
@@ -15,6 +17,7 @@ impl TCPSegment {
         TCPSegment {
             header: head,
             payload: load,
+            ecn: 0,
         }
     }
 
@@ -23,6 +26,7 @@ impl TCPSegment {
         let mut t = TCPSegment {
             header: TCPHeader::new(),
             payload: bytes,
+            ecn: 0,
         };
         let r = t.parse(checksum);
         match r {
@@ -49,6 +53,7 @@ impl TCPSegment {
     #[allow(dead_code)]
     pub fn serialize(&mut self, _datagram_layer_checksum: u32) -> Vec<u8> {
         let header_out = &mut self.header;
+        header_out.doff = (header_out.header_length() / 4) as u8;
 
         // calculate checksum -- taken over entire segment
         let mut check = InternetChecksum::new(_datagram_layer_checksum);
@@ -85,12 +90,23 @@ impl TCPSegment {
     pub fn payload_mut(&mut self) -> &mut Buffer {
         &mut self.payload
     }
+
+    #[allow(dead_code)]
+    pub fn ecn(&self) -> u8 {
+        self.ecn
+    }
+
+    #[allow(dead_code)]
+    pub fn set_ecn(&mut self, ecn: u8) {
+        self.ecn = ecn;
+    }
 }
 impl Clone for TCPSegment {
     fn clone(&self) -> Self {
         TCPSegment {
             header: self.header.clone(),
             payload: self.payload.clone(),
+            ecn: self.ecn,
         }
     }
 }
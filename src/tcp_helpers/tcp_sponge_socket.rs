@@ -2,7 +2,7 @@ use crate::tcp_connection::TCPConnection;
 use crate::tcp_helpers::ethernet_header::EthernetAddress;
 use crate::tcp_helpers::fd_adapter::AsFdAdapterBaseMut;
 use crate::tcp_helpers::tcp_config::{FdAdapterConfig, TCPConfig};
-use crate::tcp_helpers::tcp_state::{State, TCPState};
+use crate::tcp_helpers::tcp_state::State;
 use crate::tcp_helpers::tuntap_adapter::{
     TCPOverIPv4OverEthernetAdapter, TCPOverIPv4OverTunFdAdapter,
 };
@@ -107,11 +107,11 @@ where
 
     #[allow(dead_code)]
     fn initialize_tcp(&mut self, config: &TCPConfig) {
-        let _ = self
-            .tcp
-            .lock()
-            .unwrap()
-            .insert(TCPConnection::new(config.clone()));
+        let mut cfg = config.clone();
+        if cfg.mss.is_none() {
+            cfg.mss = Some(self.datagram_adapter.lock().unwrap().mss());
+        }
+        let _ = self.tcp.lock().unwrap().insert(TCPConnection::new(cfg));
 
         let mut event_loop_ = self.event_loop.lock().unwrap();
 
@@ -236,6 +236,7 @@ where
             Direction::Out,
             Box::new(move || {
                 let mut l = tcp_.lock().unwrap();
+                let reason = l.as_ref().unwrap().reset_reason();
 
                 let inbound = l.as_mut().unwrap().inbound_stream_mut();
                 let amount_to_write = min(65536, inbound.buffer_size());
@@ -247,8 +248,8 @@ where
                     thread_data_.lock().unwrap().shutdown(SHUT_WR);
                     inbound_shutdown_.store(true, Ordering::SeqCst);
 
-                    eprintln!("DEBUG: Inbound stream from {} finished {}", adapter_.lock().unwrap().config().destination.to_string(), if inbound.error() {"with an error/reset."} else {"cleanly."});
-                    if l.as_ref().unwrap().state() == TCPState::from(State::TimeWait) {
+                    eprintln!("DEBUG: Inbound stream from {} finished {}", adapter_.lock().unwrap().config().destination.to_string(), if inbound.error() {format!("with an error/reset ({:?}).", reason)} else {"cleanly.".to_string()});
+                    if l.as_ref().unwrap().state() == State::TimeWait {
                         eprintln!("DEBUG: Waiting for lingering segments (e.g. retransmissions of FIN) from peer...");
                     }
                 }
@@ -298,6 +299,21 @@ where
         eprintln!("done.");
     }
 
+    // both only apply once the connection exists, before that set them in the TCPConfig
+    #[allow(dead_code)]
+    pub fn set_nodelay(&mut self, nodelay: bool) {
+        if let Some(tcp) = self.tcp.lock().unwrap().as_mut() {
+            tcp.set_nodelay(nodelay);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn set_cork(&mut self, cork: bool) {
+        if let Some(tcp) = self.tcp.lock().unwrap().as_mut() {
+            tcp.set_cork(cork);
+        }
+    }
+
     #[allow(dead_code)]
     pub fn connect(&mut self, c_tcp: &TCPConfig, c_ad: FdAdapterConfig) {
         assert!(
@@ -312,15 +328,15 @@ where
         eprintln!("DEBUG: Connecting to {}...", c_ad.destination.to_string());
         self.tcp.lock().unwrap().as_mut().unwrap().connect();
 
-        let expected_state = TCPState::from(State::SynSent);
+        let expected_state = State::SynSent;
         assert_eq!(
             self.tcp.lock().unwrap().as_ref().unwrap().state(),
             expected_state,
             "{}",
             format!(
-                "After TCPConnection::connect(), state was {} but expected {}",
-                self.tcp.lock().unwrap().as_ref().unwrap().state().name(),
-                expected_state.name()
+                "After TCPConnection::connect(), state was {:?} but expected {:?}",
+                self.tcp.lock().unwrap().as_ref().unwrap().state(),
+                expected_state
             )
         );
 
@@ -330,9 +346,7 @@ where
         let abort_ = self.abort.clone();
         let adapter_ = self.datagram_adapter.clone();
         tcp_loop(
-            Box::new(move || {
-                tcp_.lock().unwrap().as_ref().unwrap().state() == TCPState::from(State::SynSent)
-            }),
+            Box::new(move || tcp_.lock().unwrap().as_ref().unwrap().state() == State::SynSent),
             event_loop_,
             abort_,
             tcp_1,
@@ -385,9 +399,7 @@ where
         tcp_loop(
             Box::new(move || {
                 let s = tcp_.lock().unwrap().as_ref().unwrap().state();
-                s == TCPState::from(State::LISTEN)
-                    || s == TCPState::from(State::SynRcvd)
-                    || s == TCPState::from(State::SynSent)
+                s == State::LISTEN || s == State::SynRcvd || s == State::SynSent
             }),
             event_loop_,
             abort_,
@@ -488,7 +500,7 @@ fn tcp_main<AdapterT>(
     if !tcp_.as_ref().unwrap().active() {
         eprintln!(
             "DEBUG: TCP connection finished {}",
-            if tcp_.as_ref().unwrap().state() == TCPState::from(State::RESET) {
+            if tcp_.as_ref().unwrap().state() == State::RESET {
                 "uncleanly"
             } else {
                 "cleanly."
@@ -16,6 +16,34 @@ pub enum State {
     CLOSED,
     RESET,
 }
+impl State {
+    // the moves RFC 793 allows, any open state may also be reset
+    #[allow(dead_code)]
+    pub fn can_transition_to(&self, next: State) -> bool {
+        use State::*;
+        match (*self, next) {
+            (CLOSED, _) | (RESET, _) => false,
+            (_, RESET) => true,
+            (LISTEN, SynRcvd) | (LISTEN, SynSent) => true,
+            (SynSent, SynRcvd) | (SynSent, ESTABLISHED) => true,
+            (SynRcvd, ESTABLISHED) => true,
+            (ESTABLISHED, FinWait1) | (ESTABLISHED, CloseWait) => true,
+            (FinWait1, FinWait2) | (FinWait1, CLOSING) => true,
+            (FinWait2, TimeWait) | (CLOSING, TimeWait) => true,
+            (CloseWait, LastAck) => true,
+            (LastAck, CLOSED) | (TimeWait, CLOSED) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StateError {
+    // a segment that has no place in this state, it was dropped
+    UnexpectedSegment(State),
+    // a move the state machine does not allow, the state was left as it was
+    InvalidTransition(State, State),
+}
 
 #[derive(Debug)]
 pub struct TCPState {
@@ -25,29 +53,6 @@ pub struct TCPState {
     linger_after_streams_finish: bool,
 }
 impl TCPState {
-    #[allow(dead_code)]
-    pub fn new(
-        sender_: &TCPSender,
-        receiver_: &TCPReceiver,
-        active_: bool,
-        linger_: bool,
-    ) -> TCPState {
-        TCPState {
-            sender: TCPState::state_summary_sender(sender_).to_string(),
-            receiver: TCPState::state_summary(receiver_).to_string(),
-            active: active_,
-            linger_after_streams_finish: if active_ { linger_ } else { false },
-        }
-    }
-
-    #[allow(dead_code)]
-    pub fn name(&self) -> String {
-        format!(
-            "sender=`{}`, receiver=`{}`, active={}, linger_after_streams_finish={}",
-            self.sender, self.receiver, self.active, self.linger_after_streams_finish
-        )
-    }
-
     pub fn state_summary(receiver: &TCPReceiver) -> &str {
         if receiver.stream_out().error() {
             TCPReceiverStateSummary::ERROR
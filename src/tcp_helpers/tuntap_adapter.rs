@@ -1,8 +1,11 @@
 use crate::network_interface::NetworkInterface;
 use crate::tcp_helpers::ethernet_frame::EthernetFrame;
 use crate::tcp_helpers::ethernet_header::EthernetAddress;
-use crate::tcp_helpers::fd_adapter::{AsFdAdapterBase, AsFdAdapterBaseMut, FdAdapterBase};
+use crate::tcp_helpers::fd_adapter::{
+    AsFdAdapterBase, AsFdAdapterBaseMut, FdAdapterBase, FourTuple,
+};
 use crate::tcp_helpers::ipv4_header::IPv4Header;
+use crate::tcp_helpers::tcp_header::TCPHeader;
 use crate::tcp_helpers::tcp_over_ip::TCPOverIPv4Adapter;
 use crate::tcp_helpers::tcp_segment::TCPSegment;
 use crate::util::buffer::Buffer;
@@ -31,6 +34,10 @@ impl AsFdAdapterBase for TCPOverIPv4OverTunFdAdapter {
     fn as_fd_adapter_base(&self) -> &FdAdapterBase {
         &self.ip_adapter.fd_adapter_base
     }
+
+    fn mss(&self) -> SizeT {
+        self.tun.mtu() - IPv4Header::LENGTH - TCPHeader::LENGTH
+    }
 }
 impl AsFdAdapterBaseMut for TCPOverIPv4OverTunFdAdapter {
     fn as_fd_adapter_base_mut(&mut self) -> &mut FdAdapterBase {
@@ -53,6 +60,26 @@ impl AsFdAdapterBaseMut for TCPOverIPv4OverTunFdAdapter {
             true,
         );
     }
+
+    fn read_from(&mut self) -> Option<(FourTuple, TCPSegment)> {
+        let t = self.tun.read(u32::MAX);
+        let mut ip_dgram = InternetDatagram::new(IPv4Header::new(), Buffer::new(t));
+        if ip_dgram.parse(0) != ParseResult::NoError {
+            None
+        } else {
+            self.ip_adapter.unwrap_tcp_in_ip_from(ip_dgram)
+        }
+    }
+
+    fn write_to(&mut self, tuple: &FourTuple, seg: &mut TCPSegment) {
+        self.tun.write(
+            self.ip_adapter
+                .wrap_tcp_in_ip_to(tuple, seg)
+                .serialize()
+                .as_slice(),
+            true,
+        );
+    }
 }
 impl TCPOverIPv4OverTunFdAdapter {
     #[allow(dead_code)]
@@ -95,6 +122,10 @@ impl AsFdAdapterBase for TCPOverIPv4OverEthernetAdapter {
     fn as_fd_adapter_base(&self) -> &FdAdapterBase {
         &self.ip_adapter.fd_adapter_base
     }
+
+    fn mss(&self) -> SizeT {
+        self.interface.mtu() - IPv4Header::LENGTH - TCPHeader::LENGTH
+    }
 }
 impl AsFdAdapterBaseMut for TCPOverIPv4OverEthernetAdapter {
     fn as_fd_adapter_base_mut(&mut self) -> &mut FdAdapterBase {
@@ -126,6 +157,28 @@ impl AsFdAdapterBaseMut for TCPOverIPv4OverEthernetAdapter {
 
         self.send_pending();
     }
+
+    fn read_from(&mut self) -> Option<(FourTuple, TCPSegment)> {
+        let mut frame = EthernetFrame::new();
+
+        let r = frame.parse(self.tap.read(u32::MAX));
+        if r != ParseResult::NoError {
+            return None;
+        }
+
+        let ip_dgram = self.interface.recv_frame(&frame);
+
+        self.send_pending();
+
+        self.ip_adapter.unwrap_tcp_in_ip_from(ip_dgram?)
+    }
+
+    fn write_to(&mut self, tuple: &FourTuple, seg: &mut TCPSegment) {
+        let ip_dgram = self.ip_adapter.wrap_tcp_in_ip_to(tuple, seg);
+        self.interface.send_datagram(ip_dgram, &self.next_hop);
+
+        self.send_pending();
+    }
 }
 impl TCPOverIPv4OverEthernetAdapter {
     #[allow(dead_code)]
@@ -141,6 +194,8 @@ impl TCPOverIPv4OverEthernetAdapter {
             interface: NetworkInterface::new(eth_address, ip_address),
             next_hop: next_hop_,
         };
+        let mtu = t.tap.mtu();
+        t.interface.set_mtu(mtu);
 
         let dummy = EthernetFrame::new();
         t.tap_mut().write(dummy.serialize().as_slice(), true);
@@ -1,8 +1,12 @@
 use crate::byte_stream::ByteStream;
 use crate::stream_reassembler::StreamReassembler;
+use crate::tcp_helpers::tcp_config::TCPConfig;
+use crate::tcp_helpers::tcp_header::TCPHeader;
 use crate::tcp_helpers::tcp_segment::TCPSegment;
 use crate::wrapping_integers::WrappingInt32;
 use crate::SizeT;
+use std::cmp::min;
+use std::collections::VecDeque;
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -11,6 +15,17 @@ pub struct TCPReceiver {
     reassembler: StreamReassembler,
     syn: (u32, u64, bool),
     fin: (u32, u64, bool),
+    // stream index ranges of out-of-order segments, most recent first
+    recent_ranges: VecDeque<(u64, u64)>,
+    dsack: Option<(u64, u64)>,
+    paws: bool,
+    ts_recent: Option<u32>,
+    paws_rejected: bool,
+    // rfc 1122 receiver sws avoidance: the right edge of the last advertised window, as a
+    // stream index, only moves once it can move by min(mss, capacity / 2)
+    sws: bool,
+    mss: SizeT,
+    wnd_right: SizeT,
 }
 impl TCPReceiver {
     #[allow(dead_code)]
@@ -20,9 +35,25 @@ impl TCPReceiver {
             reassembler: StreamReassembler::new(_capacity),
             syn: (0, 0, false),
             fin: (0, 0, false),
+            recent_ranges: VecDeque::new(),
+            dsack: None,
+            paws: false,
+            ts_recent: None,
+            paws_rejected: false,
+            sws: false,
+            mss: TCPConfig::MAX_PAYLOAD_SIZE,
+            wnd_right: 0,
         }
     }
 
+    #[allow(dead_code)]
+    pub fn from_config(cfg: &TCPConfig) -> TCPReceiver {
+        let mut receiver = TCPReceiver::new(cfg.recv_capacity);
+        receiver.sws = cfg.sws_avoidance;
+        receiver.mss = cfg.segment_size();
+        receiver
+    }
+
     #[allow(dead_code)]
     pub fn ackno(&self) -> Option<WrappingInt32> {
         if !self.syn.2 {
@@ -46,7 +77,58 @@ impl TCPReceiver {
 
     #[allow(dead_code)]
     pub fn window_size(&self) -> SizeT {
-        self.stream_out().remaining_capacity()
+        let available = self.stream_out().remaining_capacity();
+        if !self.sws {
+            return available;
+        }
+
+        let offered = self
+            .wnd_right
+            .saturating_sub(self.stream_out().bytes_written());
+        if available.saturating_sub(offered) >= min(self.mss, self.capacity / 2) {
+            available
+        } else {
+            offered
+        }
+    }
+
+    // the window that went out in an ack, as window_size() (before scaling)
+    #[allow(dead_code)]
+    pub fn window_advertised(&mut self, window: SizeT) {
+        self.wnd_right = self.stream_out().bytes_written() + window;
+    }
+
+    // what is left of the last advertised window
+    #[allow(dead_code)]
+    pub fn window_offered(&self) -> SizeT {
+        self.wnd_right
+            .saturating_sub(self.stream_out().bytes_written())
+    }
+
+    #[allow(dead_code)]
+    pub fn capacity(&self) -> SizeT {
+        self.capacity
+    }
+
+    // receive buffer auto-tuning only ever grows the buffer
+    #[allow(dead_code)]
+    pub fn grow(&mut self, capacity: SizeT) {
+        if capacity <= self.capacity {
+            return;
+        }
+        self.capacity = capacity;
+        self.reassembler.grow(capacity);
+    }
+
+    #[allow(dead_code)]
+    pub fn set_sws_avoidance(&mut self, sws: bool) {
+        self.sws = sws;
+    }
+
+    // segment size the peer sends with, the unit of sws avoidance
+    #[allow(dead_code)]
+    pub fn set_mss(&mut self, mss: SizeT) {
+        self.mss = mss;
     }
 
     #[allow(dead_code)]
@@ -54,8 +136,87 @@ impl TCPReceiver {
         self.reassembler.unassembled_bytes()
     }
 
+    // rfc 2018 blocks for the next ack: a D-SACK block (rfc 2883) for the last duplicate first,
+    // then the block holding the most recently received segment and the most recently reported ones
+    #[allow(dead_code)]
+    pub fn sack_blocks(&self) -> Vec<(WrappingInt32, WrappingInt32)> {
+        if !self.syn.2 {
+            return vec![];
+        }
+
+        let ranges = self.reassembler.out_of_order_ranges();
+        let mut blocks: Vec<(u64, u64)> = vec![];
+        let mut n_dsack = 0;
+        if let Some(d) = self.dsack {
+            blocks.push(d);
+            n_dsack = 1;
+        }
+        let recent = self.recent_ranges.iter().filter_map(|(l, _)| {
+            ranges
+                .iter()
+                .find(|(first, second)| first <= l && l < second)
+        });
+        for range in recent.chain(ranges.iter().rev()) {
+            if blocks.len() >= TCPHeader::MAX_SACK_BLOCKS {
+                break;
+            }
+            if !blocks[n_dsack..].contains(range) {
+                blocks.push(*range);
+            }
+        }
+
+        let isn = WrappingInt32::new(self.syn.0);
+        blocks
+            .iter()
+            .map(|(l, r)| {
+                (
+                    WrappingInt32::wrap(l + 1, &isn),
+                    WrappingInt32::wrap(r + 1, &isn),
+                )
+            })
+            .collect()
+    }
+
+    // rfc 7323 PAWS, on once both sides agreed on timestamps
+    #[allow(dead_code)]
+    pub fn set_paws(&mut self, paws: bool) {
+        self.paws = paws;
+    }
+
+    // TSval to echo
+    #[allow(dead_code)]
+    pub fn ts_recent(&self) -> Option<u32> {
+        self.ts_recent
+    }
+
+    // whether the last segment was dropped as an old duplicate
+    #[allow(dead_code)]
+    pub fn paws_rejected(&self) -> bool {
+        self.paws_rejected
+    }
+
+    // false for an old duplicate, one whose TSval is behind the last one recorded
+    #[allow(dead_code)]
+    pub fn paws_check(&self, seg: &TCPSegment) -> bool {
+        match (seg.header().timestamps, self.ts_recent) {
+            (Some((tsval, _)), Some(ts_recent))
+                if self.paws && !seg.header().rst && !seg.header().syn =>
+            {
+                (tsval.wrapping_sub(ts_recent) as i32) >= 0
+            }
+            _ => true,
+        }
+    }
+
+    // a D-SACK block is reported once
+    #[allow(dead_code)]
+    pub fn clear_dsack(&mut self) {
+        self.dsack = None;
+    }
+
     #[allow(dead_code)]
     pub fn segment_received(&mut self, seg: &TCPSegment) {
+        self.paws_rejected = false;
         let seq_no: u32 = seg.header().seqno.raw_value();
         if seg.header().syn {
             self.syn = (seq_no, 0, true);
@@ -79,11 +240,21 @@ impl TCPReceiver {
         } else {
             0
         };
-        let tw: SizeT = if self.window_size() == 0 {
-            0
-        } else {
-            self.window_size() - 1
-        };
+
+        if !self.paws_check(seg) {
+            self.paws_rejected = true;
+            return;
+        }
+        if let Some((tsval, _)) = seg.header().timestamps {
+            // the segment covers the last ack sent
+            if seg.header().syn || abs_seq_no <= next_valid_seq_no {
+                self.ts_recent = Some(tsval);
+            }
+        }
+
+        // what fits is accepted, even beyond a window held back by sws avoidance
+        let capacity = self.stream_out().remaining_capacity();
+        let tw: SizeT = if capacity == 0 { 0 } else { capacity - 1 };
         // discard segments out of current wnd range
         if abs_seq_no > (next_valid_seq_no + (tw as u64)) {
             return;
@@ -123,10 +294,33 @@ impl TCPReceiver {
             stream_index = abs_seq_no - 1;
         }
 
+        self.dsack = None;
+        let written = self.stream_out().bytes_written() as u64;
+        let end_index = stream_index + seg.payload().size() as u64;
+        if seg.payload().size() > 0 {
+            if stream_index < written {
+                self.dsack = Some((stream_index, min(end_index, written)));
+            } else if self
+                .reassembler
+                .out_of_order_ranges()
+                .iter()
+                .any(|(first, second)| *first <= stream_index && end_index <= *second)
+            {
+                self.dsack = Some((stream_index, end_index));
+            }
+        }
+
         if seg.payload().size() > 0 || _fin {
             self.reassembler
                 .push_substring(seg.payload().str(), stream_index, _fin);
         }
+
+        if seg.payload().size() > 0 && stream_index > written {
+            self.recent_ranges.push_front((stream_index, end_index));
+        }
+        let next_index = self.stream_out().bytes_written() as u64;
+        self.recent_ranges.retain(|(l, _)| *l >= next_index);
+        self.recent_ranges.truncate(2 * TCPHeader::MAX_SACK_BLOCKS);
     }
 
     #[allow(dead_code)]
@@ -1,22 +1,44 @@
 use crate::byte_stream::ByteStream;
+use crate::congestion_control::controller::CongestionControl;
+use crate::congestion_control::rate_sample::RateSample;
+use crate::tcp_helpers::ipv4_header::IPv4Header;
 use crate::tcp_helpers::tcp_config::TCPConfig;
 use crate::tcp_helpers::tcp_header::TCPHeader;
 use crate::tcp_helpers::tcp_segment::TCPSegment;
 use crate::util::buffer::Buffer;
+use crate::util::rtt_estimator::RttEstimator;
 use crate::util::tcp_timer::TcpTimer;
 use crate::wrapping_integers::WrappingInt32;
 use crate::SizeT;
-use std::collections::{BTreeMap, LinkedList, VecDeque};
+use std::cmp::{max, min};
+use std::collections::{BTreeMap, BTreeSet, LinkedList, VecDeque};
+
+// a segment in flight, with what the sender knew when (re)transmitting it
+#[derive(Debug, Clone)]
+struct OutstandingSegment {
+    segment: TCPSegment,
+    sent_time: SizeT,
+    delivered: SizeT,
+    delivered_time: SizeT,
+    first_sent_time: SizeT,
+    is_app_limited: bool,
+    retransmitted: bool,
+    sacked: bool,
+    // marked lost by RACK and not retransmitted since
+    lost: bool,
+}
 
 #[derive(Debug)]
 pub struct TCPSender {
     isn: WrappingInt32,
     segments_out: VecDeque<TCPSegment>,
-    outstanding: BTreeMap<u64, TCPSegment>,
+    outstanding: BTreeMap<u64, OutstandingSegment>,
     stream: ByteStream,
     timer: TcpTimer,
     initial_retransmission_timeout: u32,
     retransmission_timeout: u32,
+    rtt: RttEstimator,
+    adaptive_rto: bool,
     ms_total_tick: SizeT,
     consecutive_retransmissions: SizeT,
     next_abs_seq_no: u64,
@@ -24,9 +46,84 @@ pub struct TCPSender {
     last_ack_no: WrappingInt32,
     wnd_left_abs_no: u64,
     wnd_right_abs_no: u64,
-    window_size: u16,
+    window_size: u32,
+    // largest window the peer has offered, MAX.SND.WND of RFC 5961
+    max_window: u32,
+    mss: SizeT,
+    // bytes of TCP options the segments now being sent carry, taken off the payload
+    options_length: SizeT,
+    congestion_control: Option<Box<dyn CongestionControl>>,
+    delivered: SizeT,
+    delivered_time: SizeT,
+    first_sent_time: SizeT,
+    app_limited: SizeT,
+    rate_sample: Option<RateSample>,
+    fast_retransmit: bool,
+    dup_acks: SizeT,
+    in_recovery: bool,
+    recover: u64,
+    fast_retransmits: SizeT,
+    partial_acks: SizeT,
+    ts_echo: Option<SizeT>,
+    sack: bool,
+    high_rxt: u64,
+    sack_retransmits: SizeT,
+    // the scoreboard, kept up to date as segments are sent, sacked, acked and retransmitted
+    // so that pipe and the loss check don't rescan everything outstanding. a segment is lost
+    // once DUP_ACK_THRESHOLD sacked segments start above it, that is below the start of the
+    // DUP_ACK_THRESHOLD-th highest sacked segment, lost_boundary
+    outstanding_bytes: SizeT,
+    sacked_starts: BTreeSet<u64>,
+    sacked_bytes: SizeT,
+    lost_boundary: u64,
+    // unsacked bytes below lost_boundary, below high_rxt, and marked lost by RACK
+    lost_bytes: SizeT,
+    rxt_bytes: SizeT,
+    rack_lost_bytes: SizeT,
+    nagle: bool,
+    cork: bool,
+    cork_timeout: SizeT,
+    // when the partial segment held by the cork was first held back
+    corked_since: Option<SizeT>,
+    persist: bool,
+    in_persist: bool,
+    persist_timer: TcpTimer,
+    persist_timeout: u32,
+    window_probes: SizeT,
+    // probes sent since the last ack, the connection gives up on a peer that answers none
+    unanswered_window_probes: SizeT,
+    pacing: bool,
+    pacing_rate_max: Option<f64>,
+    // earliest time the next data segment may go out, in fractional ms
+    next_send_time: f64,
+    // data was held back by pacing, so the schedule may be caught up on
+    pacing_held: bool,
+    rack: bool,
+    // send time and end of the most recently sent segment known to be delivered, with its rtt
+    rack_xmit_ts: SizeT,
+    rack_end_seq: u64,
+    rack_rtt: SizeT,
+    rack_min_rtt: Option<SizeT>,
+    // highest end of a delivered segment
+    rack_fack: u64,
+    reordering_seen: bool,
+    rack_timer: TcpTimer,
+    tlp_timer: TcpTimer,
+    // end of the tail loss probe in flight, there is one per episode
+    tlp_end_seq: Option<u64>,
+    tail_loss_probes: SizeT,
+    ecn: bool,
+    // ECE is ignored until what was in flight at the last reduction is acked
+    ecn_recover: u64,
+    cwr_pending: bool,
+    ecn_reductions: SizeT,
 }
 impl TCPSender {
+    // a cwnd-derived pacing rate runs ahead of cwnd / srtt so pacing doesn't hold back growth
+    pub const PACING_GAIN: f64 = 2.0;
+    // worst case delayed ack the probe timeout allows for when a single segment is in flight
+    pub const TLP_MAX_ACK_DELAY: SizeT = 200;
+
     #[allow(dead_code)]
     pub fn new(_capacity: SizeT, retx_timeout: u16, fixed_isn: Option<WrappingInt32>) -> TCPSender {
         TCPSender {
@@ -37,6 +134,12 @@ impl TCPSender {
             timer: TcpTimer::new(retx_timeout as u32),
             initial_retransmission_timeout: retx_timeout as u32,
             retransmission_timeout: retx_timeout as u32,
+            rtt: RttEstimator::new(
+                retx_timeout as u32,
+                TCPConfig::RTO_MIN_DFLT as u32,
+                TCPConfig::RTO_MAX_DFLT as u32,
+            ),
+            adaptive_rto: false,
             ms_total_tick: 0,
             consecutive_retransmissions: 0,
             next_abs_seq_no: 0,
@@ -45,6 +148,230 @@ impl TCPSender {
             wnd_left_abs_no: 0,
             wnd_right_abs_no: 0,
             window_size: 1,
+            max_window: 0,
+            mss: TCPConfig::MAX_PAYLOAD_SIZE,
+            options_length: 0,
+            congestion_control: None,
+            delivered: 0,
+            delivered_time: 0,
+            first_sent_time: 0,
+            app_limited: 0,
+            rate_sample: None,
+            fast_retransmit: false,
+            dup_acks: 0,
+            in_recovery: false,
+            recover: 0,
+            fast_retransmits: 0,
+            partial_acks: 0,
+            ts_echo: None,
+            sack: false,
+            high_rxt: 0,
+            sack_retransmits: 0,
+            outstanding_bytes: 0,
+            sacked_starts: BTreeSet::new(),
+            sacked_bytes: 0,
+            lost_boundary: 0,
+            lost_bytes: 0,
+            rxt_bytes: 0,
+            rack_lost_bytes: 0,
+            nagle: false,
+            cork: false,
+            cork_timeout: TCPConfig::CORK_TIMEOUT_DFLT as SizeT,
+            corked_since: None,
+            persist: false,
+            in_persist: false,
+            persist_timer: TcpTimer::new(retx_timeout as u32),
+            persist_timeout: retx_timeout as u32,
+            window_probes: 0,
+            unanswered_window_probes: 0,
+            pacing: false,
+            pacing_rate_max: None,
+            next_send_time: 0.0,
+            pacing_held: false,
+            rack: false,
+            rack_xmit_ts: 0,
+            rack_end_seq: 0,
+            rack_rtt: 0,
+            rack_min_rtt: None,
+            rack_fack: 0,
+            reordering_seen: false,
+            rack_timer: TcpTimer::new(retx_timeout as u32),
+            tlp_timer: TcpTimer::new(retx_timeout as u32),
+            tlp_end_seq: None,
+            tail_loss_probes: 0,
+            ecn: false,
+            ecn_recover: 0,
+            cwr_pending: false,
+            ecn_reductions: 0,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn from_config(cfg: &TCPConfig) -> TCPSender {
+        let mut sender = TCPSender::new(cfg.send_capacity, cfg.rt_timeout, cfg.fixed_isn);
+        sender.mss = cfg.segment_size();
+        sender.congestion_control = cfg.congestion_control.build(sender.mss);
+        sender.rtt = RttEstimator::new(
+            cfg.rt_timeout as u32,
+            cfg.rto_min as u32,
+            cfg.rto_max as u32,
+        );
+        sender.adaptive_rto = cfg.adaptive_rto;
+        sender.fast_retransmit = cfg.fast_retransmit;
+        sender.sack = cfg.sack;
+        sender.nagle = !cfg.nodelay;
+        sender.cork = cfg.cork;
+        sender.cork_timeout = cfg.cork_timeout as SizeT;
+        sender.persist = cfg.persist;
+        sender.pacing = cfg.pacing;
+        sender.pacing_rate_max = cfg.pacing_rate_max;
+        sender.rack = cfg.rack;
+        sender.ecn = cfg.ecn;
+        sender
+    }
+
+    #[allow(dead_code)]
+    pub fn set_congestion_control(&mut self, cc: Option<Box<dyn CongestionControl>>) {
+        self.congestion_control = cc;
+    }
+
+    #[allow(dead_code)]
+    pub fn congestion_control(&self) -> Option<&dyn CongestionControl> {
+        self.congestion_control.as_deref()
+    }
+
+    #[allow(dead_code)]
+    pub fn set_mss(&mut self, mss: SizeT) {
+        self.mss = max(mss, 1);
+    }
+
+    #[allow(dead_code)]
+    pub fn mss(&self) -> SizeT {
+        self.mss
+    }
+
+    // RFC 6691: the MSS counts options as part of the payload
+    #[allow(dead_code)]
+    pub fn set_options_length(&mut self, len: SizeT) {
+        self.options_length = len;
+    }
+
+    // payload of a full sized segment
+    fn segment_size(&self) -> SizeT {
+        max(self.mss.saturating_sub(self.options_length), 1)
+    }
+
+    // turned off when the peer's SYN didn't carry SACK-permitted
+    #[allow(dead_code)]
+    pub fn set_sack(&mut self, sack: bool) {
+        self.sack = sack;
+    }
+
+    #[allow(dead_code)]
+    pub fn sack_enabled(&self) -> bool {
+        self.sack
+    }
+
+    // turned off when the handshake didn't negotiate ECN
+    #[allow(dead_code)]
+    pub fn set_ecn(&mut self, ecn: bool) {
+        self.ecn = ecn;
+    }
+
+    #[allow(dead_code)]
+    pub fn ecn_enabled(&self) -> bool {
+        self.ecn
+    }
+
+    #[allow(dead_code)]
+    pub fn ecn_reductions(&self) -> SizeT {
+        self.ecn_reductions
+    }
+
+    #[allow(dead_code)]
+    pub fn set_nodelay(&mut self, nodelay: bool) {
+        self.nagle = !nodelay;
+    }
+
+    #[allow(dead_code)]
+    pub fn nodelay(&self) -> bool {
+        !self.nagle
+    }
+
+    // uncorking lets the held partial segment go with the next fill_window
+    #[allow(dead_code)]
+    pub fn set_cork(&mut self, cork: bool) {
+        self.cork = cork;
+        if !cork {
+            self.corked_since = None;
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn corked(&self) -> bool {
+        self.cork
+    }
+
+    #[allow(dead_code)]
+    pub fn set_pacing(&mut self, pacing: bool) {
+        self.pacing = pacing;
+    }
+
+    // bytes per ms, None when segments aren't paced. a controller that paces (BBR) is always
+    // followed, the others only get a rate derived from cwnd with pacing on
+    #[allow(dead_code)]
+    pub fn pacing_rate(&self) -> Option<f64> {
+        let cc_rate = self
+            .congestion_control
+            .as_ref()
+            .and_then(|cc| cc.pacing_rate());
+        if !self.pacing && cc_rate.is_none() {
+            return None;
+        }
+
+        let rate = match self.congestion_control.as_ref() {
+            Some(cc) => cc_rate.or_else(|| {
+                self.rtt
+                    .srtt()
+                    .map(|srtt| TCPSender::PACING_GAIN * cc.cwnd() as f64 / max(srtt, 1) as f64)
+            }),
+            None => None,
+        };
+        match (rate, self.pacing_rate_max) {
+            (Some(rate), Some(cap)) => Some(rate.min(cap)),
+            (rate, cap) => rate.or(cap),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn tail_loss_probes(&self) -> SizeT {
+        self.tail_loss_probes
+    }
+
+    // a segment sent earlier was delivered after one sent later, which widens the
+    // reordering window RACK allows for
+    #[allow(dead_code)]
+    pub fn reordering_seen(&self) -> bool {
+        self.reordering_seen
+    }
+
+    #[allow(dead_code)]
+    pub fn window_probes(&self) -> SizeT {
+        self.window_probes
+    }
+
+    #[allow(dead_code)]
+    pub fn unanswered_window_probes(&self) -> SizeT {
+        self.unanswered_window_probes
+    }
+
+    // the interval to the next window probe, None when not probing
+    #[allow(dead_code)]
+    pub fn persist_timeout(&self) -> Option<u32> {
+        if self.in_persist {
+            Some(self.persist_timeout)
+        } else {
+            None
         }
     }
 
@@ -59,13 +386,147 @@ impl TCPSender {
     }
 
     #[allow(dead_code)]
-    pub fn ack_received(&mut self, ackno: WrappingInt32, window_size: u16) {
+    pub fn ack_received(&mut self, ackno: WrappingInt32, window_size: u32) {
+        self.ack_received_with_length(ackno, window_size, 0);
+    }
+
+    // marks the outstanding segments fully covered by a block on the scoreboard,
+    // to be called before ack_received for the same segment
+    #[allow(dead_code)]
+    pub fn sack_received(&mut self, blocks: &[(WrappingInt32, WrappingInt32)]) {
+        if !self.sack {
+            return;
+        }
+
+        for (left, right) in blocks {
+            let abs_left = WrappingInt32::unwrap(left, &self.isn, self.check_point);
+            let abs_right = WrappingInt32::unwrap(right, &self.isn, self.check_point);
+            // D-SACK or bogus block
+            if abs_right <= abs_left
+                || abs_left < self.wnd_left_abs_no
+                || abs_right > self.next_abs_seq_no
+            {
+                continue;
+            }
+            let mut newly_sacked: Vec<(u64, SizeT, SizeT, bool, bool)> = vec![];
+            for (first, second) in self.outstanding.range_mut(abs_left..abs_right) {
+                let len = second.segment.length_in_sequence_space();
+                if first + len as u64 <= abs_right && !second.sacked {
+                    newly_sacked.push((
+                        *first,
+                        len,
+                        second.sent_time,
+                        second.retransmitted,
+                        second.lost,
+                    ));
+                    second.sacked = true;
+                    second.lost = false;
+                }
+            }
+            for (first, len, sent_time, retransmitted, lost) in newly_sacked {
+                self.sacked_starts.insert(first);
+                self.sacked_bytes += len;
+                self.unsacked_removed(first, len, lost);
+                self.rack_update(first + len as u64, sent_time, retransmitted);
+            }
+        }
+        self.update_lost_boundary();
+    }
+
+    // a segment that counted as unsacked is sacked or acked
+    fn unsacked_removed(&mut self, first: u64, len: SizeT, rack_lost: bool) {
+        if first < self.lost_boundary {
+            self.lost_bytes -= len;
+        }
+        if first < self.high_rxt {
+            self.rxt_bytes -= len;
+        }
+        if rack_lost {
+            self.rack_lost_bytes -= len;
+        }
+    }
+
+    // the boundary only moves up: sacks raise it, and what cumulative acks take off the
+    // scoreboard lies below everything still outstanding
+    fn update_lost_boundary(&mut self) {
+        let boundary = match self
+            .sacked_starts
+            .iter()
+            .rev()
+            .nth(TCPConfig::DUP_ACK_THRESHOLD - 1)
+        {
+            Some(b) if *b > self.lost_boundary => *b,
+            _ => return,
+        };
+        self.lost_bytes += self
+            .outstanding
+            .range(self.lost_boundary..boundary)
+            .filter(|(_, o)| !o.sacked)
+            .map(|(_, o)| o.segment.length_in_sequence_space())
+            .sum::<SizeT>();
+        self.lost_boundary = boundary;
+    }
+
+    fn raise_high_rxt(&mut self, high_rxt: u64) {
+        if high_rxt <= self.high_rxt {
+            return;
+        }
+        self.rxt_bytes += self
+            .outstanding
+            .range(self.high_rxt..high_rxt)
+            .filter(|(_, o)| !o.sacked)
+            .map(|(_, o)| o.segment.length_in_sequence_space())
+            .sum::<SizeT>();
+        self.high_rxt = high_rxt;
+    }
+
+    fn reset_high_rxt(&mut self) {
+        self.high_rxt = 0;
+        self.rxt_bytes = 0;
+    }
+
+    // rfc 3168 6.1.2: the peer saw a CE mark. cwnd is reduced as for a loss, at most once per
+    // window and not during loss recovery, and the next new data carries CWR. to be called
+    // before ack_received for the same segment
+    #[allow(dead_code)]
+    pub fn ecn_echo_received(&mut self) {
+        if !self.ecn || self.in_recovery || self.wnd_left_abs_no < self.ecn_recover {
+            return;
+        }
+
+        self.ecn_recover = self.next_abs_seq_no;
+        self.ecn_reductions += 1;
+        self.cwr_pending = true;
+        let in_flight = self.bytes_in_flight();
+        if let Some(cc) = self.congestion_control.as_mut() {
+            cc.on_loss(in_flight, self.ms_total_tick);
+        }
+    }
+
+    // send time echoed in the timestamps option, to be called before ack_received for the
+    // same segment. unlike karn's rule it gives a sample for retransmitted segments too
+    #[allow(dead_code)]
+    pub fn timestamp_echo_received(&mut self, echoed: SizeT) {
+        if echoed <= self.ms_total_tick {
+            self.ts_echo = Some(echoed);
+        }
+    }
+
+    // seg_len: sequence space taken by the segment carrying the ack, which
+    // disqualifies it as a duplicate ack
+    #[allow(dead_code)]
+    pub fn ack_received_with_length(
+        &mut self,
+        ackno: WrappingInt32,
+        window_size: u32,
+        seg_len: SizeT,
+    ) {
         let abs_ack_no = WrappingInt32::unwrap(&ackno, &self.isn, self.check_point);
 
         let last_abs_ack_no = WrappingInt32::unwrap(&self.last_ack_no, &self.isn, self.check_point);
         // let last_abs_ack_no = self.wnd_left_abs_no;
         if abs_ack_no > last_abs_ack_no {
-            self.retransmission_timeout = self.initial_retransmission_timeout;
+            self.retransmission_timeout = self.base_rto();
             self.consecutive_retransmissions = 0;
             if !self.outstanding.is_empty() {
                 self.timer
@@ -77,39 +538,148 @@ impl TCPSender {
             // Impossible ackno (beyond next seqno) is ignored or repeated ack
             return;
         }
+        self.unanswered_window_probes = 0;
 
         let mut list: LinkedList<u64> = LinkedList::new();
         for (first, second) in self.outstanding.iter() {
-            if (first + (second.length_in_sequence_space() as u64) - 1) < abs_ack_no {
+            if (first + (second.segment.length_in_sequence_space() as u64) - 1) < abs_ack_no {
                 list.push_back(*first);
             }
         }
+        let mut acked_segments: Vec<OutstandingSegment> = Vec::with_capacity(list.len());
         for n in list {
-            self.outstanding.remove(&n);
+            let o = self.outstanding.remove(&n).unwrap();
+            let len = o.segment.length_in_sequence_space();
+            self.outstanding_bytes -= len;
+            if o.sacked {
+                self.sacked_starts.remove(&n);
+                self.sacked_bytes -= len;
+            } else {
+                self.unsacked_removed(n, len, o.lost);
+                self.rack_update(n + len as u64, o.sent_time, o.retransmitted);
+            }
+            acked_segments.push(o);
+        }
+        if self.tlp_end_seq.is_some_and(|end| abs_ack_no >= end) {
+            self.tlp_end_seq = None;
         }
         if self.outstanding.is_empty() {
             self.timer.stop();
         }
 
+        let ts_rtt = self.ts_echo.take().map(|t| self.ms_total_tick - t);
+        if let Some(mut rs) = self.take_rate_sample(&acked_segments) {
+            if ts_rtt.is_some() {
+                rs.rtt = ts_rtt;
+                self.rate_sample = Some(rs);
+            }
+            // karn: no rtt sample when the acked segment had been retransmitted
+            if let Some(r) = rs.rtt {
+                self.rtt.sample(r);
+                if let Some(cc) = self.congestion_control.as_mut() {
+                    cc.on_rtt_sample(r);
+                }
+                if self.adaptive_rto {
+                    self.retransmission_timeout = self.rtt.rto();
+                    if !self.outstanding.is_empty() {
+                        self.timer
+                            .restart(self.ms_total_tick, self.retransmission_timeout);
+                    }
+                }
+            }
+            if let Some(cc) = self.congestion_control.as_mut() {
+                cc.on_rate_sample(&rs);
+            }
+        }
+
+        let was_in_recovery = self.in_recovery;
+        if abs_ack_no > self.wnd_left_abs_no {
+            self.dup_acks = 0;
+            if self.in_recovery {
+                if abs_ack_no > self.recover {
+                    self.in_recovery = false;
+                    if let Some(cc) = self.congestion_control.as_mut() {
+                        cc.on_recovery_end(self.ms_total_tick);
+                    }
+                } else if !self.rack_loss_detection() {
+                    // newreno partial ack: the segment right after it was lost too
+                    self.partial_acks += 1;
+                    if !self.sack || *self.outstanding.keys().next().unwrap() >= self.high_rxt {
+                        self.retransmit_first_outstanding();
+                    }
+                    self.retransmit_sack_holes();
+                    self.timer
+                        .restart(self.ms_total_tick, self.retransmission_timeout);
+                }
+            }
+        } else if !self.rack_loss_detection()
+            && seg_len == 0
+            && !self.outstanding.is_empty()
+            && self.wnd_left_abs_no > 0
+            && window_size == self.window_size
+        {
+            self.dup_acks += 1;
+            let lost = self.dup_acks >= TCPConfig::DUP_ACK_THRESHOLD
+                || (self.sack && self.is_lost(self.wnd_left_abs_no));
+            if (self.fast_retransmit || self.sack)
+                && lost
+                && !self.in_recovery
+                && self.wnd_left_abs_no > self.recover
+            {
+                self.enter_fast_recovery();
+            } else if self.in_recovery {
+                self.retransmit_sack_holes();
+            }
+        }
+
+        // the SYN itself doesn't count towards window growth, the controller decides what
+        // an ack during recovery does
+        let acked = abs_ack_no.saturating_sub(max(self.wnd_left_abs_no, 1)) as SizeT;
+        let in_flight = self.bytes_in_flight();
+        if let Some(cc) = self.congestion_control.as_mut() {
+            cc.on_ack(acked, in_flight, was_in_recovery, self.ms_total_tick);
+        }
+
+        if self.rack_loss_detection() && self.rack_detect_loss() {
+            self.rack_recover();
+        }
+
         // What should I do if the window size is zero? If the receiver has announced a
         // window size of zero, the fill window method should act like the window size is one.
         // When filling window, treat a '0' window size as equal to '1' but don't back off RTO
         // so when _window_size == 0, then (_wnd_right_abs_no-_wnd_left_abs_no+1)==1
+        // with the persist timer a zero window stays closed and only the window probes go out
         self.window_size = window_size;
+        self.max_window = max(self.max_window, window_size);
         self.last_ack_no = ackno;
         self.wnd_left_abs_no =
             WrappingInt32::unwrap(&self.last_ack_no, &self.isn, self.check_point);
         // self.wnd_left_abs_no = abs_ack_no;
-        self.wnd_right_abs_no = self.wnd_left_abs_no
-            + (if self.window_size == 0 {
-                1
-            } else {
-                self.window_size
-            }) as u64
-            - 1;
+        self.wnd_right_abs_no = if self.window_size == 0 && self.persist {
+            self.wnd_left_abs_no.saturating_sub(1)
+        } else {
+            self.wnd_left_abs_no
+                + (if self.window_size == 0 {
+                    1
+                } else {
+                    self.window_size
+                }) as u64
+                - 1
+        };
         if self.wnd_left_abs_no > self.check_point && self.window_size > 0 {
             self.check_point = self.wnd_left_abs_no - 1;
         }
+
+        self.update_persist();
+        self.arm_tlp();
+    }
+
+    // RFC 5961 5.2: an ack is taken if it falls between MAX.SND.WND before SND.UNA and SND.NXT
+    #[allow(dead_code)]
+    pub fn ack_acceptable(&self, ackno: WrappingInt32) -> bool {
+        let abs_ack_no = WrappingInt32::unwrap(&ackno, &self.isn, self.check_point);
+        abs_ack_no <= self.next_abs_seq_no
+            && abs_ack_no + self.max_window as u64 >= self.wnd_left_abs_no
     }
 
     #[allow(dead_code)]
@@ -123,32 +693,57 @@ impl TCPSender {
         ));
     }
 
+    // simultaneous open: the peer's SYN crossed ours, which goes out again to be acked along
+    // with it
+    #[allow(dead_code)]
+    pub fn resend_syn(&mut self) {
+        if self.wnd_left_abs_no == 0 && self.outstanding.contains_key(&0) {
+            self.retransmit(0);
+        }
+    }
+
+    // an already acked seqno with no data, which the peer answers with an ack
+    #[allow(dead_code)]
+    pub fn send_keepalive(&mut self) {
+        self.segments_out.push_back(TCPSender::build_segment(
+            vec![],
+            false,
+            false,
+            false,
+            WrappingInt32::wrap(self.next_abs_seq_no, &self.isn) - 1,
+        ));
+    }
+
     #[allow(dead_code)]
     pub fn fill_window(&mut self) {
         // previous way of matching (let state = TCPState::state_summary_sender(&self)) when error would prevent further sending
         if self.next_abs_seq_no == 0 {
-            let seg = TCPSender::build_segment(vec![], true, false, false, self.isn.clone());
-            let n_ = self.next_abs_seq_no + seg.length_in_sequence_space() as u64;
-            self.segments_out.push_back(seg.clone());
-            self.outstanding.insert(self.next_abs_seq_no, seg);
-            self.next_abs_seq_no = n_;
-            self.timer
-                .start(self.ms_total_tick, self.retransmission_timeout);
+            let mut seg = TCPSender::build_segment(vec![], true, false, false, self.isn.clone());
+            seg.header_mut().sack_permitted = self.sack;
+            self.send_segment(seg);
         } else if self.next_abs_seq_no == self.bytes_in_flight() as u64 {
         } else if !self.stream_in().eof()
             || (self.next_abs_seq_no < (self.stream_in().bytes_written() + 2) as u64)
         {
+            let right_abs_no = self.send_right_abs_no();
             let mut fin = false;
-            while !self.stream.buffer_empty() && self.next_abs_seq_no <= self.wnd_right_abs_no {
-                let gap: SizeT = (self.wnd_right_abs_no - self.next_abs_seq_no + 1) as SizeT;
-                let vec = vec![TCPConfig::MAX_PAYLOAD_SIZE, gap, self.stream.buffer_size()];
+            let mut held = false;
+            while !self.stream.buffer_empty() && self.next_abs_seq_no <= right_abs_no {
+                let gap: SizeT = (right_abs_no - self.next_abs_seq_no + 1) as SizeT;
+                let vec = vec![self.segment_size(), gap, self.stream.buffer_size()];
                 let readable: SizeT = *vec.iter().min().unwrap();
+                if self.hold_partial_segment() {
+                    break;
+                }
+                if !self.pacing_allows() {
+                    held = true;
+                    break;
+                }
                 let data = self.stream.read(readable);
-                if self.stream.eof()
-                    && (self.next_abs_seq_no + readable as u64) <= self.wnd_right_abs_no
-                {
+                if self.stream.eof() && (self.next_abs_seq_no + readable as u64) <= right_abs_no {
                     fin = true;
                 }
+                self.paced(data.len());
                 let seg = TCPSender::build_segment(
                     data,
                     false,
@@ -156,14 +751,10 @@ impl TCPSender {
                     false,
                     WrappingInt32::wrap(self.next_abs_seq_no, &self.isn),
                 );
-                let n_ = self.next_abs_seq_no + seg.length_in_sequence_space() as u64;
-                self.segments_out.push_back(seg.clone());
-                self.outstanding.insert(self.next_abs_seq_no, seg);
-                self.next_abs_seq_no = n_;
-                self.timer
-                    .start(self.ms_total_tick, self.retransmission_timeout);
+                self.send_segment(seg);
             }
-            if fin == false && self.stream.eof() && self.next_abs_seq_no <= self.wnd_right_abs_no {
+            self.pacing_held = held;
+            if fin == false && self.stream.eof() && self.next_abs_seq_no <= right_abs_no {
                 let seg = TCPSender::build_segment(
                     vec![],
                     false,
@@ -171,12 +762,20 @@ impl TCPSender {
                     false,
                     WrappingInt32::wrap(self.next_abs_seq_no, &self.isn),
                 );
-                let n_ = self.next_abs_seq_no + seg.length_in_sequence_space() as u64;
-                self.segments_out.push_back(seg.clone());
-                self.outstanding.insert(self.next_abs_seq_no, seg);
-                self.next_abs_seq_no = n_;
-                self.timer
-                    .start(self.ms_total_tick, self.retransmission_timeout);
+                self.send_segment(seg);
+            }
+
+            if self.stream.buffer_empty() {
+                self.corked_since = None;
+            }
+            self.update_persist();
+
+            // nothing more to send although the windows allow it: the application is the bottleneck
+            if self.stream.buffer_empty()
+                && !self.stream.eof()
+                && self.next_abs_seq_no <= right_abs_no
+            {
+                self.app_limited = max(self.delivered + self.bytes_in_flight(), 1);
             }
         }
     }
@@ -186,18 +785,67 @@ impl TCPSender {
         self.ms_total_tick = self.ms_total_tick + ms_since_last_tick;
         let expired = self.timer.expire(self.ms_total_tick);
 
+        if let Some(since) = self.corked_since {
+            if self.ms_total_tick >= since + self.cork_timeout {
+                self.fill_window();
+            }
+        }
+
+        // paced segments are released as their time comes
+        if !self.stream.buffer_empty()
+            && self.pacing_rate().is_some()
+            && self.next_send_time <= self.ms_total_tick as f64
+        {
+            self.fill_window();
+        }
+
+        // the retransmission timer sits out a zero window, what is outstanding goes as a probe
+        if self.in_persist {
+            if self.persist_timer.expire(self.ms_total_tick) {
+                self.send_window_probe();
+            }
+            return;
+        }
+
         if self.outstanding.is_empty() {
             self.timer.stop();
             return;
         }
 
+        if !expired && self.rack {
+            if self.rack_timer.expire(self.ms_total_tick) {
+                self.rack_timer.stop();
+                if self.rack_detect_loss() {
+                    self.rack_recover();
+                }
+            }
+            if self.tlp_timer.expire(self.ms_total_tick) {
+                self.send_loss_probe();
+            }
+        }
+
         if expired {
-            let _entry = self.outstanding.iter().next().unwrap();
-            // todo: clone here
-            self.segments_out.push_back(_entry.1.clone());
+            self.retransmit_first_outstanding();
+            self.rack_timer.stop();
+            self.tlp_timer.stop();
+            self.tlp_end_seq = None;
+            // anything sent so far may be retransmitted again, don't take its dup acks for loss
+            self.in_recovery = false;
+            self.reset_high_rxt();
+            self.dup_acks = 0;
+            self.recover = self.next_abs_seq_no - 1;
             if self.window_size > 0 {
-                self.retransmission_timeout = self.retransmission_timeout * 2;
+                self.retransmission_timeout = if self.adaptive_rto {
+                    self.rtt.backoff(self.retransmission_timeout)
+                } else {
+                    self.retransmission_timeout * 2
+                };
                 self.consecutive_retransmissions += 1;
+
+                let in_flight = self.bytes_in_flight();
+                if let Some(cc) = self.congestion_control.as_mut() {
+                    cc.on_timeout(in_flight, self.ms_total_tick);
+                }
             }
             self.timer
                 .restart(self.ms_total_tick, self.retransmission_timeout);
@@ -206,11 +854,7 @@ impl TCPSender {
 
     #[allow(dead_code)]
     pub fn bytes_in_flight(&self) -> SizeT {
-        let mut bytes = 0;
-        for (_first, _second) in self.outstanding.iter() {
-            bytes = bytes + _second.length_in_sequence_space();
-        }
-        return bytes;
+        self.outstanding_bytes
     }
 
     #[allow(dead_code)]
@@ -238,6 +882,536 @@ impl TCPSender {
         WrappingInt32::wrap(self.next_abs_seq_no, &self.isn)
     }
 
+    #[allow(dead_code)]
+    pub fn srtt(&self) -> Option<SizeT> {
+        self.rtt.srtt()
+    }
+
+    #[allow(dead_code)]
+    pub fn rttvar(&self) -> Option<SizeT> {
+        self.rtt.rttvar()
+    }
+
+    // current retransmission timeout, including any backoff
+    #[allow(dead_code)]
+    pub fn rto(&self) -> u32 {
+        self.retransmission_timeout
+    }
+
+    #[allow(dead_code)]
+    pub fn dup_acks(&self) -> SizeT {
+        self.dup_acks
+    }
+
+    #[allow(dead_code)]
+    pub fn fast_retransmits(&self) -> SizeT {
+        self.fast_retransmits
+    }
+
+    #[allow(dead_code)]
+    pub fn partial_acks(&self) -> SizeT {
+        self.partial_acks
+    }
+
+    #[allow(dead_code)]
+    pub fn in_fast_recovery(&self) -> bool {
+        self.in_recovery
+    }
+
+    #[allow(dead_code)]
+    pub fn sack_retransmits(&self) -> SizeT {
+        self.sack_retransmits
+    }
+
+    #[allow(dead_code)]
+    pub fn sacked_bytes(&self) -> SizeT {
+        self.sacked_bytes
+    }
+
+    // rfc 6675 pipe: what is estimated to be still in the network. with RACK a
+    // retransmission stands in for the lost segment. without SACK every dup ack is a segment
+    // that left the network, and the resent head takes the place of the lost original
+    #[allow(dead_code)]
+    pub fn pipe(&self) -> SizeT {
+        if !self.sack {
+            let left = self.dup_acks * self.segment_size();
+            return self.outstanding_bytes.saturating_sub(left);
+        }
+        let unsacked = self.outstanding_bytes - self.sacked_bytes;
+        if self.rack_loss_detection() {
+            return unsacked - self.rack_lost_bytes;
+        }
+        unsacked - self.lost_bytes + if self.in_recovery { self.rxt_bytes } else { 0 }
+    }
+
+    #[allow(dead_code)]
+    pub fn delivered(&self) -> SizeT {
+        self.delivered
+    }
+
+    #[allow(dead_code)]
+    pub fn last_rate_sample(&self) -> Option<&RateSample> {
+        self.rate_sample.as_ref()
+    }
+
+    fn base_rto(&self) -> u32 {
+        if self.adaptive_rto && self.rtt.has_sample() {
+            self.rtt.rto()
+        } else {
+            self.initial_retransmission_timeout
+        }
+    }
+
+    fn enter_fast_recovery(&mut self) {
+        self.in_recovery = true;
+        self.recover = self.next_abs_seq_no - 1;
+        self.fast_retransmits += 1;
+
+        let in_flight = self.bytes_in_flight();
+        if let Some(cc) = self.congestion_control.as_mut() {
+            cc.on_loss(in_flight, self.ms_total_tick);
+        }
+        self.reset_high_rxt();
+        self.retransmit_first_outstanding();
+        self.retransmit_sack_holes();
+    }
+
+    // a segment is deemed lost once DUP_ACK_THRESHOLD segments above it were sacked
+    fn is_lost(&self, abs_seq_no: u64) -> bool {
+        abs_seq_no < self.lost_boundary
+    }
+
+    // rfc 6675 NextSeg rule 1: resend lost holes above high_rxt, one time each, while pipe allows
+    fn retransmit_sack_holes(&mut self) {
+        if !self.sack || !self.in_recovery || self.rack {
+            return;
+        }
+
+        if self.high_rxt >= self.lost_boundary {
+            return;
+        }
+        let holes: Vec<u64> = self
+            .outstanding
+            .range(self.high_rxt..self.lost_boundary)
+            .filter(|(_, second)| !second.sacked)
+            .map(|(first, _)| *first)
+            .collect();
+        for abs_seq_no in holes {
+            if let Some(cc) = self.congestion_control.as_ref() {
+                if self.pipe() >= cc.cwnd() {
+                    break;
+                }
+            }
+            self.sack_retransmits += 1;
+            self.retransmit(abs_seq_no);
+        }
+    }
+
+    fn retransmit_first_outstanding(&mut self) {
+        let abs_seq_no = *self.outstanding.keys().next().unwrap();
+        self.retransmit(abs_seq_no);
+    }
+
+    fn retransmit(&mut self, abs_seq_no: u64) {
+        let now = self.ms_total_tick;
+        let (delivered, delivered_time, first_sent_time, app_limited) = (
+            self.delivered,
+            self.delivered_time,
+            self.first_sent_time,
+            self.app_limited != 0,
+        );
+        let len = self.outstanding[&abs_seq_no]
+            .segment
+            .length_in_sequence_space();
+        self.raise_high_rxt(abs_seq_no + len as u64);
+        let _entry = self.outstanding.get_mut(&abs_seq_no).unwrap();
+        if _entry.lost {
+            self.rack_lost_bytes -= len;
+        }
+        _entry.sent_time = now;
+        _entry.delivered = delivered;
+        _entry.delivered_time = delivered_time;
+        _entry.first_sent_time = first_sent_time;
+        _entry.is_app_limited = app_limited;
+        _entry.retransmitted = true;
+        _entry.lost = false;
+        // todo: clone here
+        let mut seg = _entry.segment.clone();
+        // rfc 3168 6.1.5: retransmissions aren't ECN-capable
+        seg.set_ecn(IPv4Header::ECN_NOT_ECT);
+        self.segments_out.push_back(seg);
+    }
+
+    // the persist timer runs while the peer's window is zero and there is something to send.
+    // when the window reopens the retransmission timer takes over again
+    fn update_persist(&mut self) {
+        if !self.persist || self.window_size > 0 || self.wnd_left_abs_no == 0 {
+            if self.in_persist {
+                self.in_persist = false;
+                self.persist_timer.stop();
+                if !self.outstanding.is_empty() {
+                    self.timer
+                        .restart(self.ms_total_tick, self.retransmission_timeout);
+                }
+            }
+            return;
+        }
+
+        if !self.in_persist {
+            self.in_persist = true;
+            self.persist_timeout = self.base_rto();
+        }
+        let fin_pending =
+            self.stream.eof() && self.next_abs_seq_no < (self.stream.bytes_written() + 2) as u64;
+        if !self.outstanding.is_empty() || !self.stream.buffer_empty() || fin_pending {
+            self.persist_timer
+                .start(self.ms_total_tick, self.persist_timeout);
+        }
+    }
+
+    // the first byte outstanding if any, else one new byte or the FIN. probes back off
+    // on their own and don't count as retransmissions
+    fn send_window_probe(&mut self) {
+        if let Some(entry) = self.outstanding.values_mut().next() {
+            // the byte is sent again, so the segment gives no RTT sample
+            entry.retransmitted = true;
+            let seg = &entry.segment;
+            let mut probe = if seg.payload().size() > 1 {
+                TCPSender::build_segment(
+                    seg.payload().str()[..1].to_vec(),
+                    false,
+                    false,
+                    false,
+                    seg.header().seqno,
+                )
+            } else {
+                seg.clone()
+            };
+            probe.set_ecn(IPv4Header::ECN_NOT_ECT);
+            self.segments_out.push_back(probe);
+        } else if !self.stream.buffer_empty() {
+            let data = self.stream.read(1);
+            let seg = TCPSender::build_segment(
+                data,
+                false,
+                false,
+                false,
+                WrappingInt32::wrap(self.next_abs_seq_no, &self.isn),
+            );
+            self.send_segment(seg);
+        } else if self.stream.eof()
+            && self.next_abs_seq_no < (self.stream.bytes_written() + 2) as u64
+        {
+            let seg = TCPSender::build_segment(
+                vec![],
+                false,
+                true,
+                false,
+                WrappingInt32::wrap(self.next_abs_seq_no, &self.isn),
+            );
+            self.send_segment(seg);
+        } else {
+            self.persist_timer.stop();
+            return;
+        }
+
+        self.window_probes += 1;
+        self.unanswered_window_probes += 1;
+        self.persist_timeout = self.rtt.backoff(self.persist_timeout);
+        self.persist_timer
+            .restart(self.ms_total_tick, self.persist_timeout);
+    }
+
+    fn pacing_allows(&self) -> bool {
+        self.pacing_rate().is_none() || self.next_send_time <= self.ms_total_tick as f64
+    }
+
+    // held data keeps to its schedule even if the tick was coarser than the interval, but
+    // an idle sender starts over from now and doesn't build up credit for a burst
+    fn paced(&mut self, len: SizeT) {
+        if let Some(rate) = self.pacing_rate() {
+            let now = self.ms_total_tick as f64;
+            if !self.pacing_held && self.next_send_time < now {
+                self.next_send_time = now;
+            }
+            self.next_send_time += len as f64 / rate;
+        }
+    }
+
+    // less than a segment's worth of data is buffered: with nagle it waits for the
+    // outstanding data to be acked, with the cork for the cork timeout
+    fn hold_partial_segment(&mut self) -> bool {
+        if self.stream.buffer_size() >= self.segment_size() || self.stream.input_ended() {
+            return false;
+        }
+        if self.cork {
+            let since = *self.corked_since.get_or_insert(self.ms_total_tick);
+            return self.ms_total_tick < since + self.cork_timeout;
+        }
+        self.nagle && self.bytes_in_flight() > 0
+    }
+
+    fn send_segment(&mut self, mut seg: TCPSegment) {
+        if self.ecn && seg.payload().size() > 0 {
+            seg.set_ecn(IPv4Header::ECN_ECT0);
+            seg.header_mut().cwr = self.cwr_pending;
+            self.cwr_pending = false;
+        }
+        if self.outstanding.is_empty() {
+            self.first_sent_time = self.ms_total_tick;
+            self.delivered_time = self.ms_total_tick;
+        }
+
+        let n_ = self.next_abs_seq_no + seg.length_in_sequence_space() as u64;
+        self.outstanding_bytes += seg.length_in_sequence_space();
+        self.segments_out.push_back(seg.clone());
+        self.outstanding.insert(
+            self.next_abs_seq_no,
+            OutstandingSegment {
+                segment: seg,
+                sent_time: self.ms_total_tick,
+                delivered: self.delivered,
+                delivered_time: self.delivered_time,
+                first_sent_time: self.first_sent_time,
+                is_app_limited: self.app_limited != 0,
+                retransmitted: false,
+                sacked: false,
+                lost: false,
+            },
+        );
+        self.next_abs_seq_no = n_;
+        self.timer
+            .start(self.ms_total_tick, self.retransmission_timeout);
+        self.arm_tlp();
+    }
+
+    // RACK only knows a later segment was delivered through SACK, without it losses are left
+    // to dup acks and NewReno. the tail loss probe works either way
+    fn rack_loss_detection(&self) -> bool {
+        self.rack && self.sack
+    }
+
+    // rfc 8985 6.2: the most recently sent of the delivered segments sets the rack time.
+    // a retransmission acked faster than min_rtt was probably the original being delivered
+    fn rack_update(&mut self, end_seq: u64, sent_time: SizeT, retransmitted: bool) {
+        if !self.rack {
+            return;
+        }
+
+        let rtt = self.ms_total_tick - sent_time;
+        if retransmitted && self.rack_min_rtt.is_some_and(|min_rtt| rtt < min_rtt) {
+            return;
+        }
+        self.rack_min_rtt = Some(self.rack_min_rtt.map_or(rtt, |min_rtt| min(min_rtt, rtt)));
+        if sent_time > self.rack_xmit_ts
+            || (sent_time == self.rack_xmit_ts && end_seq > self.rack_end_seq)
+        {
+            self.rack_xmit_ts = sent_time;
+            self.rack_end_seq = end_seq;
+            self.rack_rtt = rtt;
+        }
+
+        if !retransmitted && end_seq < self.rack_fack {
+            self.reordering_seen = true;
+        }
+        self.rack_fack = max(self.rack_fack, end_seq);
+    }
+
+    // none while no reordering was seen and the loss is evident, else a quarter of min_rtt
+    fn rack_reo_wnd(&self) -> SizeT {
+        let sacked = self.sacked_starts.len();
+        if !self.reordering_seen && (self.in_recovery || sacked >= TCPConfig::DUP_ACK_THRESHOLD) {
+            return 0;
+        }
+        min(
+            self.rack_min_rtt.unwrap_or(0) / 4,
+            self.rtt.srtt().unwrap_or(SizeT::MAX),
+        )
+    }
+
+    // segments sent before the rack time and still not delivered a reordering window after
+    // rack_rtt are lost. the reorder timer fires when the next one would be. true when
+    // something new was marked lost
+    fn rack_detect_loss(&mut self) -> bool {
+        let reo_wnd = self.rack_reo_wnd();
+        let now = self.ms_total_tick;
+        let (xmit_ts, end_seq, rack_rtt) = (self.rack_xmit_ts, self.rack_end_seq, self.rack_rtt);
+        let mut timeout: SizeT = 0;
+        let mut detected = false;
+        for (first, second) in self.outstanding.iter_mut() {
+            if second.sacked || second.lost {
+                continue;
+            }
+            let end = first + second.segment.length_in_sequence_space() as u64;
+            if second.sent_time > xmit_ts || (second.sent_time == xmit_ts && end >= end_seq) {
+                continue;
+            }
+            let deadline = second.sent_time + rack_rtt + reo_wnd;
+            if deadline <= now {
+                second.lost = true;
+                self.rack_lost_bytes += end as SizeT - *first as SizeT;
+                detected = true;
+            } else {
+                timeout = max(timeout, deadline - now);
+            }
+        }
+
+        if timeout > 0 {
+            self.rack_timer.restart(now, timeout as u32);
+        } else {
+            self.rack_timer.stop();
+        }
+        detected
+    }
+
+    // the first loss in a window is a congestion event, the lost segments are resent as far
+    // as the congestion window allows
+    fn rack_recover(&mut self) {
+        if !self.in_recovery && self.wnd_left_abs_no > self.recover {
+            self.in_recovery = true;
+            self.recover = self.next_abs_seq_no - 1;
+            self.fast_retransmits += 1;
+            self.tlp_timer.stop();
+
+            let in_flight = self.bytes_in_flight();
+            if let Some(cc) = self.congestion_control.as_mut() {
+                cc.on_loss(in_flight, self.ms_total_tick);
+            }
+        }
+
+        let lost: Vec<u64> = self
+            .outstanding
+            .iter()
+            .filter(|(_, o)| o.lost)
+            .map(|(first, _)| *first)
+            .collect();
+        for abs_seq_no in lost {
+            if let Some(cc) = self.congestion_control.as_ref() {
+                if self.pipe() >= cc.cwnd() {
+                    break;
+                }
+            }
+            self.retransmit(abs_seq_no);
+        }
+    }
+
+    // rfc 8985 7.2: two srtts, plus the peer's delayed ack when a single segment is in
+    // flight. not armed for the SYN, in recovery, or while a probe is out, and left to the
+    // RTO when it wouldn't fire first
+    fn arm_tlp(&mut self) {
+        if !self.rack
+            || self.in_recovery
+            || self.tlp_end_seq.is_some()
+            || self.outstanding.is_empty()
+            || self.wnd_left_abs_no == 0
+        {
+            self.tlp_timer.stop();
+            return;
+        }
+
+        let pto = match self.rtt.srtt() {
+            Some(srtt) if self.outstanding.len() == 1 => 2 * srtt + TCPSender::TLP_MAX_ACK_DELAY,
+            Some(srtt) => max(2 * srtt, 1),
+            None => TCPConfig::TIMEOUT_DFLT as SizeT,
+        };
+        if pto >= self.retransmission_timeout as SizeT {
+            self.tlp_timer.stop();
+            return;
+        }
+        self.tlp_timer.restart(self.ms_total_tick, pto as u32);
+    }
+
+    // rfc 8985 7.3: a new segment if the peer's window allows it, else the last one sent
+    // again. either way its ack tells RACK about a lost tail
+    fn send_loss_probe(&mut self) {
+        let before = self.next_abs_seq_no;
+        if !self.stream.buffer_empty() && self.next_abs_seq_no <= self.wnd_right_abs_no {
+            let gap = (self.wnd_right_abs_no - self.next_abs_seq_no + 1) as SizeT;
+            let readable = min(min(self.segment_size(), gap), self.stream.buffer_size());
+            let data = self.stream.read(readable);
+            let fin = self.stream.eof()
+                && (self.next_abs_seq_no + readable as u64) <= self.wnd_right_abs_no;
+            let seg = TCPSender::build_segment(
+                data,
+                false,
+                fin,
+                false,
+                WrappingInt32::wrap(self.next_abs_seq_no, &self.isn),
+            );
+            self.send_segment(seg);
+        }
+        if self.next_abs_seq_no == before {
+            match self.outstanding.keys().next_back() {
+                Some(&abs_seq_no) => self.retransmit(abs_seq_no),
+                None => {
+                    self.tlp_timer.stop();
+                    return;
+                }
+            }
+        }
+
+        self.tail_loss_probes += 1;
+        self.tlp_end_seq = Some(self.next_abs_seq_no);
+        self.tlp_timer.stop();
+        self.timer
+            .restart(self.ms_total_tick, self.retransmission_timeout);
+    }
+
+    // the sample is taken from the most recently sent of the newly acked segments, which is
+    // not the highest one when a hole below it was retransmitted
+    fn take_rate_sample(&mut self, acked_segments: &[OutstandingSegment]) -> Option<RateSample> {
+        if acked_segments.is_empty() {
+            return None;
+        }
+
+        let now = self.ms_total_tick;
+        for seg in acked_segments {
+            self.delivered += seg.segment.length_in_sequence_space();
+        }
+        self.delivered_time = now;
+
+        let last = acked_segments
+            .iter()
+            .max_by_key(|seg| (seg.sent_time, seg.delivered))
+            .unwrap();
+        self.first_sent_time = last.sent_time;
+        if self.app_limited != 0 && self.delivered > self.app_limited {
+            self.app_limited = 0;
+        }
+
+        self.rate_sample = Some(RateSample {
+            delivered: self.delivered - last.delivered,
+            total_delivered: self.delivered,
+            prior_delivered: last.delivered,
+            send_elapsed: last.sent_time - last.first_sent_time,
+            ack_elapsed: now - last.delivered_time,
+            rtt: if last.retransmitted {
+                None
+            } else {
+                Some(now - last.sent_time)
+            },
+            is_app_limited: last.is_app_limited,
+        });
+        self.rate_sample
+    }
+
+    // the peer's receive window further limited by the congestion window. in recovery the
+    // congestion window is measured against pipe rather than what is outstanding, so new data
+    // keeps going out as the dup acks come in
+    fn send_right_abs_no(&self) -> u64 {
+        match self.congestion_control.as_ref() {
+            Some(cc) if self.in_recovery => min(
+                self.wnd_right_abs_no,
+                self.next_abs_seq_no + cc.cwnd().saturating_sub(self.pipe()) as u64 - 1,
+            ),
+            Some(cc) => min(
+                self.wnd_right_abs_no,
+                self.wnd_left_abs_no + max(cc.cwnd(), 1) as u64 - 1,
+            ),
+            None => self.wnd_right_abs_no,
+        }
+    }
+
     fn build_segment(
         data: Vec<u8>,
         syn: bool,
@@ -3,6 +3,7 @@ pub mod buffer;
 pub mod eventloop;
 pub mod file_descriptor;
 pub mod parser;
+pub mod rtt_estimator;
 pub mod socket;
 pub mod tcp_timer;
 pub mod tun;
@@ -9,6 +9,7 @@ pub enum ParseResult {
     WrongIPVersion,
     HeaderTooShort,
     TruncatedPacket,
+    BadOption,
     Unsupported,
 }
 
@@ -20,6 +21,7 @@ pub fn as_string(r: ParseResult) -> String {
         ParseResult::WrongIPVersion => "WrongIPVersion".to_string(),
         ParseResult::HeaderTooShort => "HeaderTooShort".to_string(),
         ParseResult::TruncatedPacket => "TruncatedPacket".to_string(),
+        ParseResult::BadOption => "BadOption".to_string(),
         _ => panic!("Unsupported"),
     }
 }
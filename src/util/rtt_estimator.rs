@@ -0,0 +1,74 @@
+use crate::SizeT;
+
+// RFC 6298 smoothed round trip time and retransmission timeout, all in ms
+#[derive(Debug)]
+pub struct RttEstimator {
+    srtt: Option<f64>,
+    rttvar: f64,
+    rto: u32,
+    rto_min: u32,
+    rto_max: u32,
+}
+impl RttEstimator {
+    pub const ALPHA: f64 = 1.0 / 8.0;
+    pub const BETA: f64 = 1.0 / 4.0;
+    pub const K: f64 = 4.0;
+    // clock granularity of the sender's tick
+    pub const G: f64 = 1.0;
+
+    #[allow(dead_code)]
+    pub fn new(initial_rto: u32, rto_min: u32, rto_max: u32) -> RttEstimator {
+        RttEstimator {
+            srtt: None,
+            rttvar: 0.0,
+            rto: initial_rto,
+            rto_min,
+            rto_max,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn sample(&mut self, rtt: SizeT) {
+        let r = rtt as f64;
+        match self.srtt {
+            None => {
+                self.srtt = Some(r);
+                self.rttvar = r / 2.0;
+            }
+            Some(srtt) => {
+                self.rttvar = (1.0 - RttEstimator::BETA) * self.rttvar
+                    + RttEstimator::BETA * (srtt - r).abs();
+                self.srtt = Some((1.0 - RttEstimator::ALPHA) * srtt + RttEstimator::ALPHA * r);
+            }
+        }
+
+        let rto = self.srtt.unwrap() + f64::max(RttEstimator::G, RttEstimator::K * self.rttvar);
+        self.rto = (rto.ceil() as u32).clamp(self.rto_min, self.rto_max);
+    }
+
+    // rto before any backoff
+    #[allow(dead_code)]
+    pub fn rto(&self) -> u32 {
+        self.rto
+    }
+
+    #[allow(dead_code)]
+    pub fn backoff(&self, rto: u32) -> u32 {
+        rto.saturating_mul(2).min(self.rto_max)
+    }
+
+    #[allow(dead_code)]
+    pub fn srtt(&self) -> Option<SizeT> {
+        self.srtt.map(|s| s.round() as SizeT)
+    }
+
+    #[allow(dead_code)]
+    pub fn rttvar(&self) -> Option<SizeT> {
+        self.srtt.map(|_| self.rttvar.round() as SizeT)
+    }
+
+    #[allow(dead_code)]
+    pub fn has_sample(&self) -> bool {
+        self.srtt.is_some()
+    }
+}
@@ -1,5 +1,6 @@
 use crate::util::file_descriptor::{AsFileDescriptor, AsFileDescriptorMut, FileDescriptor};
 use crate::util::util::system_call;
+use crate::SizeT;
 use libc::{c_char, c_int, c_ulong};
 use std::fmt::Debug;
 
@@ -15,6 +16,8 @@ pub const IFF_NO_PI: c_int = 0x1000;
 struct ifreq {
     ifr_name: [c_char; libc::IF_NAMESIZE],
     ifr_data: c_int, /* ifr_ifindex or ifr_mtu */
+    // the rest of the kernel's union, which it copies back whole
+    ifr_pad: [u8; 20],
 }
 
 // A FileDescriptor to a [Linux TUN/TAP](https://www.kernel.org/doc/Documentation/networking/tuntap.txt) device
@@ -23,6 +26,7 @@ struct ifreq {
 #[derive(Debug)]
 pub struct TunTapFD {
     fd: FileDescriptor,
+    mtu: SizeT,
 }
 impl AsFileDescriptor for TunTapFD {
     fn as_file_descriptor(&self) -> &FileDescriptor {
@@ -45,6 +49,7 @@ impl TunTapFD {
         let mut ifreq_ = ifreq {
             ifr_name: [0; libc::IF_NAMESIZE],
             ifr_data: 0,
+            ifr_pad: [0; 20],
         };
         ifreq_.ifr_data = (if is_tun_ { IFF_TUN } else { IFF_TAP } | IFF_NO_PI);
         for (i, byte) in devname_.as_bytes().iter().enumerate() {
@@ -54,7 +59,27 @@ impl TunTapFD {
             unsafe { libc::ioctl(fd_desc.fd_num(), TUNSETIFF as _, &mut ifreq_ as *mut ifreq) };
         system_call("ioctl", io_ as i32, 0);
 
-        TunTapFD { fd: fd_desc }
+        // the device only answers MTU queries through a socket
+        let sock = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+        let sock_desc = FileDescriptor::new(system_call("socket", sock, 0));
+        let io_ = unsafe {
+            libc::ioctl(
+                sock_desc.fd_num(),
+                libc::SIOCGIFMTU as _,
+                &mut ifreq_ as *mut ifreq,
+            )
+        };
+        system_call("ioctl", io_ as i32, 0);
+
+        TunTapFD {
+            fd: fd_desc,
+            mtu: ifreq_.ifr_data as SizeT,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn mtu(&self) -> SizeT {
+        self.mtu
     }
 }
 
@@ -105,6 +130,11 @@ impl TunFD {
             tun_fd: TunTapFD::new(&devname_.to_string(), true),
         }
     }
+
+    #[allow(dead_code)]
+    pub fn mtu(&self) -> SizeT {
+        self.tun_fd.mtu()
+    }
 }
 
 #[derive(Debug)]
@@ -138,4 +168,9 @@ impl TapFD {
             tap_fd: TunTapFD::new(&devname_.to_string(), false),
         }
     }
+
+    #[allow(dead_code)]
+    pub fn mtu(&self) -> SizeT {
+        self.tap_fd.mtu()
+    }
 }
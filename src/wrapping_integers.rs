@@ -92,28 +92,28 @@ impl ops::Add<WrappingInt32> for WrappingInt32 {
     type Output = WrappingInt32;
 
     fn add(self, rhs: WrappingInt32) -> Self::Output {
-        WrappingInt32::new(self.raw_value() + rhs.raw_value())
+        WrappingInt32::new(self.raw_value().wrapping_add(rhs.raw_value()))
     }
 }
 impl ops::Add<u32> for WrappingInt32 {
     type Output = WrappingInt32;
 
     fn add(self, rhs: u32) -> Self::Output {
-        WrappingInt32::new(self.raw_value() + rhs)
+        WrappingInt32::new(self.raw_value().wrapping_add(rhs))
     }
 }
 impl ops::Sub<WrappingInt32> for WrappingInt32 {
     type Output = WrappingInt32;
 
     fn sub(self, rhs: WrappingInt32) -> Self::Output {
-        WrappingInt32::new(self.raw_value() - rhs.raw_value())
+        WrappingInt32::new(self.raw_value().wrapping_sub(rhs.raw_value()))
     }
 }
 impl ops::Sub<u32> for WrappingInt32 {
     type Output = WrappingInt32;
 
     fn sub(self, rhs: u32) -> Self::Output {
-        WrappingInt32::new(self.raw_value() - rhs)
+        WrappingInt32::new(self.raw_value().wrapping_sub(rhs))
     }
 }
 
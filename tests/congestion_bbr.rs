@@ -0,0 +1,157 @@
+use rust_sponge::congestion_control::bbr::{Bbr, BbrMode};
+use rust_sponge::congestion_control::controller::CongestionControl;
+use rust_sponge::congestion_control::rate_sample::RateSample;
+use rust_sponge::SizeT;
+
+const MSS: SizeT = 1000;
+
+// one round trip of `rtt` ms delivering at `rate` bytes/ms, acked in a single sample
+fn round(bbr: &mut Bbr, now: &mut SizeT, delivered: &mut SizeT, rate: SizeT, rtt: SizeT) {
+    recovery_round(bbr, now, delivered, rate, rtt, false);
+}
+
+fn recovery_round(
+    bbr: &mut Bbr,
+    now: &mut SizeT,
+    delivered: &mut SizeT,
+    rate: SizeT,
+    rtt: SizeT,
+    in_recovery: bool,
+) {
+    let prior_delivered = *delivered;
+    *now += rtt;
+    *delivered += rate * rtt;
+    bbr.on_rate_sample(&RateSample {
+        delivered: rate * rtt,
+        total_delivered: *delivered,
+        prior_delivered,
+        send_elapsed: 0,
+        ack_elapsed: rtt,
+        rtt: Some(rtt),
+        is_app_limited: false,
+    });
+    bbr.on_ack(rate * rtt, 0, in_recovery, *now);
+}
+
+#[test]
+fn congestion_bbr() {
+    {
+        let mut bbr = Bbr::new(MSS);
+        assert_eq!(bbr.mode(), BbrMode::Startup);
+        assert!(bbr.pacing_rate().is_none());
+
+        let (mut now, mut delivered) = (0, 0);
+        round(&mut bbr, &mut now, &mut delivered, 100, 50);
+        assert_eq!(bbr.min_rtt(), Some(50));
+        assert_eq!(bbr.btl_bw(), 100.0);
+        assert_eq!(bbr.bdp(), Some(5000));
+        assert_eq!(
+            bbr.pacing_rate().unwrap(),
+            Bbr::HIGH_GAIN * 100.0,
+            "{}",
+            bbr.summary()
+        );
+
+        // bandwidth plateaus: three rounds without 25% growth fill the pipe
+        for _ in 0..3 {
+            round(&mut bbr, &mut now, &mut delivered, 100, 50);
+        }
+        assert!(bbr.filled_pipe(), "{}", bbr.summary());
+        // nothing left in flight, so drain is over immediately
+        assert_eq!(bbr.mode(), BbrMode::ProbeBw, "{}", bbr.summary());
+        assert_eq!(bbr.cwnd(), 10000, "{}", bbr.summary());
+
+        // a faster path shows up in the max filter right away
+        round(&mut bbr, &mut now, &mut delivered, 200, 50);
+        assert_eq!(bbr.btl_bw(), 200.0);
+
+        // ... and the old maximum ages out after ten rounds
+        for _ in 0..Bbr::BTL_BW_FILTER_ROUNDS {
+            round(&mut bbr, &mut now, &mut delivered, 50, 60);
+        }
+        assert_eq!(bbr.btl_bw(), 50.0, "{}", bbr.summary());
+        assert_eq!(bbr.min_rtt(), Some(50));
+    }
+
+    {
+        let mut bbr = Bbr::new(MSS);
+        let (mut now, mut delivered) = (0, 0);
+        round(&mut bbr, &mut now, &mut delivered, 100, 50);
+
+        // no lower rtt seen for 10s: probe for it with a minimal window
+        while now <= Bbr::MIN_RTT_FILTER_MS + 50 {
+            round(&mut bbr, &mut now, &mut delivered, 100, 80);
+        }
+        assert_eq!(bbr.mode(), BbrMode::ProbeRtt, "{}", bbr.summary());
+        assert_eq!(bbr.cwnd(), 4 * MSS);
+
+        let mut rounds = 0;
+        while bbr.mode() == BbrMode::ProbeRtt {
+            round(&mut bbr, &mut now, &mut delivered, 100, 80);
+            rounds += 1;
+        }
+        assert!(rounds <= 3, "{}", bbr.summary());
+        assert_eq!(bbr.min_rtt(), Some(80));
+    }
+
+    {
+        // samples without an rtt (retransmitted segments) can't refresh the min_rtt, leaving
+        // ProbeRTT must not be followed by going right back into it
+        let mut bbr = Bbr::new(MSS);
+        let (mut now, mut delivered) = (0, 0);
+        round(&mut bbr, &mut now, &mut delivered, 100, 50);
+        while bbr.mode() != BbrMode::ProbeRtt || now < Bbr::MIN_RTT_FILTER_MS + 500 {
+            let prior_delivered = delivered;
+            now += 50;
+            delivered += 5000;
+            bbr.on_rate_sample(&RateSample {
+                delivered: 5000,
+                total_delivered: delivered,
+                prior_delivered,
+                send_elapsed: 0,
+                ack_elapsed: 50,
+                rtt: None,
+                is_app_limited: false,
+            });
+            bbr.on_ack(5000, 0, false, now);
+        }
+        assert_eq!(bbr.mode(), BbrMode::ProbeRtt, "{}", bbr.summary());
+        assert_eq!(bbr.min_rtt(), Some(50));
+
+        bbr.on_ack(MSS, 0, false, now + Bbr::PROBE_RTT_DURATION_MS);
+        assert_ne!(bbr.mode(), BbrMode::ProbeRtt, "{}", bbr.summary());
+        // an ack without a rate sample right after doesn't take it back into ProbeRTT
+        bbr.on_ack(MSS, 0, false, now + Bbr::PROBE_RTT_DURATION_MS + 1);
+        assert_ne!(bbr.mode(), BbrMode::ProbeRtt, "{}", bbr.summary());
+    }
+
+    {
+        // the model is updated through recovery, and cwnd comes back once it is over
+        let mut bbr = Bbr::new(MSS);
+        let (mut now, mut delivered) = (0, 0);
+        for _ in 0..4 {
+            round(&mut bbr, &mut now, &mut delivered, 100, 50);
+        }
+        assert_eq!(bbr.cwnd(), 10000, "{}", bbr.summary());
+
+        bbr.on_loss(3000, now);
+        assert_eq!(bbr.cwnd(), 4 * MSS);
+        bbr.on_recovery_end(now);
+        assert_eq!(bbr.cwnd(), 10000, "{}", bbr.summary());
+
+        bbr.on_loss(3000, now);
+        recovery_round(&mut bbr, &mut now, &mut delivered, 200, 50, true);
+        assert_eq!(bbr.btl_bw(), 200.0);
+        // packet conservation, what was delivered goes out again
+        assert_eq!(bbr.cwnd(), 10000, "{}", bbr.summary());
+        bbr.on_recovery_end(now);
+        round(&mut bbr, &mut now, &mut delivered, 200, 50);
+        assert_eq!(bbr.cwnd(), 20000, "{}", bbr.summary());
+    }
+
+    {
+        let mut bbr = Bbr::new(MSS);
+        bbr.on_timeout(8000, 0);
+        assert_eq!(bbr.cwnd(), MSS);
+    }
+}
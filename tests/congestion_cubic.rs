@@ -0,0 +1,86 @@
+use rust_sponge::congestion_control::controller::CongestionControl;
+use rust_sponge::congestion_control::cubic::Cubic;
+use rust_sponge::SizeT;
+
+const MSS: SizeT = 1000;
+
+// a full window of segments acked once per `rtt` ms, until `until`
+fn ack_rounds(cubic: &mut Cubic, now: &mut SizeT, rtt: SizeT, until: SizeT) {
+    cubic.on_rtt_sample(rtt);
+    while *now < until {
+        *now += rtt;
+        for _ in 0..(cubic.cwnd() / MSS) {
+            cubic.on_ack(MSS, cubic.cwnd(), false, *now);
+        }
+    }
+}
+
+#[test]
+fn congestion_cubic() {
+    {
+        let mut cubic = Cubic::new(MSS);
+        assert_eq!(cubic.cwnd(), 4 * MSS);
+        assert!(cubic.in_slow_start());
+
+        let mut now = 0;
+        for _ in 0..16 {
+            now += 1;
+            cubic.on_ack(MSS, cubic.cwnd(), false, now);
+        }
+        assert_eq!(cubic.cwnd(), 20 * MSS);
+
+        cubic.on_loss(cubic.cwnd(), now);
+        assert_eq!(cubic.w_max(), 20 * MSS);
+        assert_eq!(cubic.cwnd(), 14 * MSS);
+        assert_eq!(cubic.ssthresh(), 14 * MSS);
+        assert!(!cubic.in_slow_start());
+        assert!(cubic.epoch_start().is_none());
+
+        // the first ack after the reduction starts a new epoch
+        now += 10;
+        cubic.on_ack(MSS, cubic.cwnd(), false, now);
+        assert_eq!(cubic.epoch_start(), Some(now));
+        // K = cbrt((20 - 14) / 0.4) seconds
+        assert_eq!(cubic.k(), 2466);
+
+        // concave region: approaches w_max but stays below it before K
+        let start = now;
+        ack_rounds(&mut cubic, &mut now, 500, start + 2000);
+        assert!(cubic.cwnd() > 14 * MSS, "{}", cubic.summary());
+        assert!(cubic.cwnd() <= 20 * MSS, "{}", cubic.summary());
+
+        // convex region: probes beyond w_max after K
+        ack_rounds(&mut cubic, &mut now, 500, start + 4000);
+        assert!(cubic.cwnd() > 20 * MSS, "{}", cubic.summary());
+    }
+
+    {
+        let mut cubic = Cubic::new(MSS);
+        let mut now = 0;
+        for _ in 0..16 {
+            now += 1;
+            cubic.on_ack(MSS, cubic.cwnd(), false, now);
+        }
+        cubic.on_loss(cubic.cwnd(), now);
+        // fast convergence: a second loss below the previous w_max lowers it further
+        cubic.on_loss(cubic.cwnd(), now);
+        assert_eq!(cubic.w_max(), 11900);
+        assert_eq!(cubic.cwnd(), 9800);
+
+        cubic.on_timeout(cubic.cwnd(), now);
+        assert_eq!(cubic.cwnd(), MSS);
+        assert!(cubic.in_slow_start());
+    }
+
+    {
+        // with a tiny w_max the reno-friendly estimate dominates
+        let mut cubic = Cubic::new(MSS);
+        let mut now = 0;
+        cubic.on_loss(cubic.cwnd(), now);
+        now += 1;
+        cubic.on_ack(MSS, cubic.cwnd(), false, now);
+        ack_rounds(&mut cubic, &mut now, 10, 200);
+        assert!(cubic.in_reno_friendly_region(now), "{}", cubic.summary());
+        assert!(cubic.w_est() >= cubic.cwnd() - 1, "{}", cubic.summary());
+    }
+}
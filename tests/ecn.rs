@@ -0,0 +1,280 @@
+use rust_sponge::congestion_control::controller::CongestionControlAlgorithm;
+use rust_sponge::network_interface::NetworkInterface;
+use rust_sponge::router::{AsyncNetworkInterface, Router};
+use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_helpers::ipv4_header::IPv4Header;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_over_ip::TCPOverIPv4Adapter;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::tcp_sender::TCPSender;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::InternetDatagram;
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+fn pop(conn: &mut TCPConnection) -> TCPSegment {
+    conn.segments_out_mut()
+        .pop_front()
+        .expect("a segment was expected")
+}
+
+// the client's SYN and the server's SYN/ACK, then the client's ack delivered
+fn handshake(client: &mut TCPConnection, server: &mut TCPConnection) -> (TCPSegment, TCPSegment) {
+    client.connect();
+    let syn = pop(client);
+    server.segment_received(&syn);
+    let syn_ack = pop(server);
+    client.segment_received(&syn_ack);
+    let ack = pop(client);
+    assert_eq!(ack.ecn(), IPv4Header::ECN_NOT_ECT);
+    server.segment_received(&ack);
+    (syn, syn_ack)
+}
+
+fn datagram(ecn: u8) -> InternetDatagram {
+    let mut header = IPv4Header::new();
+    header.src = u32::from(Ipv4Addr::new(10, 0, 0, 2));
+    header.dst = u32::from(Ipv4Addr::new(10, 0, 1, 2));
+    header.len = (IPv4Header::LENGTH + 4) as u16;
+    header.set_ecn(ecn);
+    InternetDatagram::new(header, Buffer::new(vec![1, 2, 3, 4]))
+}
+
+#[test]
+fn t_ecn() {
+    {
+        // the DSCP bits survive setting the codepoint
+        let mut dgram = datagram(IPv4Header::ECN_NOT_ECT);
+        dgram.header_mut().set_tos(0xb8);
+        dgram.header_mut().set_ecn(IPv4Header::ECN_ECT0);
+        assert_eq!(dgram.header().tos(), 0xba);
+        let mut parsed = InternetDatagram::new(IPv4Header::new(), Buffer::new(dgram.serialize()));
+        assert!(parsed.parse(0) == rust_sponge::util::parser::ParseResult::NoError);
+        assert_eq!(parsed.header().ecn(), IPv4Header::ECN_ECT0);
+        assert_eq!(parsed.header().tos(), 0xba);
+
+        let mut header = TCPHeader::new();
+        header.ack = true;
+        header.ece = true;
+        header.cwr = true;
+        let mut seg = TCPSegment::new(header.clone(), Buffer::new(vec![]));
+        let bytes = seg.serialize(0);
+        assert_eq!(bytes[13], 0b11010000);
+        let parsed = TCPSegment::parse_new(Buffer::new(bytes), 0).unwrap();
+        assert!(parsed.header().ece && parsed.header().cwr);
+        assert!(*parsed.header() == header);
+    }
+
+    {
+        // the codepoint crosses the IP layer in both directions
+        let a = SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 1000);
+        let b = SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 2), 2000);
+        let mut tx = TCPOverIPv4Adapter::new();
+        tx.fd_adapter_base.config_mut().source = a;
+        tx.fd_adapter_base.config_mut().destination = b;
+        let mut rx = TCPOverIPv4Adapter::new();
+        rx.fd_adapter_base.config_mut().source = b;
+        rx.fd_adapter_base.config_mut().destination = a;
+
+        let mut seg = TCPSegment::new(TCPHeader::new(), Buffer::new(b"abc".to_vec()));
+        seg.set_ecn(IPv4Header::ECN_ECT0);
+        let mut dgram = tx.wrap_tcp_in_ip(&mut seg);
+        assert_eq!(dgram.header().ecn(), IPv4Header::ECN_ECT0);
+        dgram.header_mut().set_ecn(IPv4Header::ECN_CE);
+        let received = rx.unwrap_tcp_in_ip(dgram).unwrap();
+        assert_eq!(received.ecn(), IPv4Header::ECN_CE);
+        assert_eq!(received.payload().str(), b"abc");
+    }
+
+    {
+        let cfg = TCPConfig {
+            ecn: true,
+            nodelay: true,
+            ..Default::default()
+        };
+        let mut client = TCPConnection::new(cfg);
+        let mut server = TCPConnection::new(cfg);
+        let (syn, syn_ack) = handshake(&mut client, &mut server);
+        assert!(syn.header().ece && syn.header().cwr);
+        assert_eq!(syn.ecn(), IPv4Header::ECN_NOT_ECT);
+        assert!(syn_ack.header().ece && !syn_ack.header().cwr);
+
+        client.write(b"hello");
+        let mut data = pop(&mut client);
+        assert_eq!(data.ecn(), IPv4Header::ECN_ECT0);
+        assert!(!data.header().ece && !data.header().cwr);
+
+        // the CE mark is echoed on every ack until the sender answers with CWR
+        data.set_ecn(IPv4Header::ECN_CE);
+        server.segment_received(&data);
+        let ack = pop(&mut server);
+        assert!(ack.header().ece);
+        server.write(b"hi");
+        let reply = pop(&mut server);
+        assert!(reply.header().ece);
+        assert_eq!(reply.ecn(), IPv4Header::ECN_ECT0);
+
+        client.segment_received(&ack);
+        client.segment_received(&reply);
+        let _ = pop(&mut client);
+        client.write(b"world");
+        let data = pop(&mut client);
+        assert!(data.header().cwr);
+        server.segment_received(&data);
+        let ack = pop(&mut server);
+        assert!(!ack.header().ece);
+
+        client.write(b"!");
+        assert!(!pop(&mut client).header().cwr);
+    }
+
+    {
+        // either side without ECN: no ECN-setup SYN/ACK, nothing ECT
+        for (client_ecn, server_ecn) in [(true, false), (false, true)] {
+            let mut client = TCPConnection::new(TCPConfig {
+                ecn: client_ecn,
+                nodelay: true,
+                ..Default::default()
+            });
+            let mut server = TCPConnection::new(TCPConfig {
+                ecn: server_ecn,
+                nodelay: true,
+                ..Default::default()
+            });
+            let (syn, syn_ack) = handshake(&mut client, &mut server);
+            assert_eq!(syn.header().ece, client_ecn);
+            assert!(!syn_ack.header().ece && !syn_ack.header().cwr);
+
+            client.write(b"hello");
+            let mut data = pop(&mut client);
+            assert_eq!(data.ecn(), IPv4Header::ECN_NOT_ECT);
+            server.write(b"hi");
+            assert_eq!(pop(&mut server).ecn(), IPv4Header::ECN_NOT_ECT);
+
+            data.set_ecn(IPv4Header::ECN_CE);
+            server.segment_received(&data);
+            assert!(!pop(&mut server).header().ece);
+        }
+    }
+
+    {
+        // ECE cuts cwnd once per window, retransmissions go out not ECT
+        let isn = WrappingInt32::new(0);
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            ecn: true,
+            congestion_control: CongestionControlAlgorithm::Reno,
+            nodelay: true,
+            ..Default::default()
+        };
+        let mut sender = TCPSender::from_config(&cfg);
+        sender.fill_window();
+        sender.ack_received(isn + 1, 60000);
+        sender.segments_out_mut().clear();
+        sender.stream_in_mut().write(&[b'a'; 4000]);
+        sender.fill_window();
+        assert_eq!(sender.segments_out().len(), 4);
+        assert!(sender
+            .segments_out()
+            .iter()
+            .all(|seg| seg.ecn() == IPv4Header::ECN_ECT0 && !seg.header().cwr));
+        sender.segments_out_mut().clear();
+
+        let cwnd = sender.congestion_control().unwrap().cwnd();
+        sender.ecn_echo_received();
+        sender.ack_received(isn + 1001, 60000);
+        let reduced = sender.congestion_control().unwrap().cwnd();
+        assert!(reduced < cwnd);
+        assert_eq!(sender.ecn_reductions(), 1);
+        sender.ecn_echo_received();
+        sender.ack_received(isn + 2001, 60000);
+        assert_eq!(sender.ecn_reductions(), 1);
+
+        sender.stream_in_mut().write(&[b'b'; 10]);
+        sender.fill_window();
+        let seg = sender.segments_out_mut().pop_front().unwrap();
+        assert!(seg.header().cwr);
+        assert_eq!(seg.ecn(), IPv4Header::ECN_ECT0);
+
+        sender.tick(cfg.rt_timeout as usize);
+        let rtx = sender.segments_out_mut().pop_front().unwrap();
+        assert_eq!(rtx.header().seqno, isn + 2001);
+        assert_eq!(rtx.ecn(), IPv4Header::ECN_NOT_ECT);
+
+        // once the reduced window is acked, ECE counts again
+        sender.ack_received(isn + 4011, 60000);
+        sender.ecn_echo_received();
+        assert_eq!(sender.ecn_reductions(), 2);
+    }
+
+    {
+        // a congested interface CE-marks what is ECN-capable and drops the rest
+        let mut router = Router::new();
+        router.add_interface(AsyncNetworkInterface::new(NetworkInterface::new(
+            [2, 0, 0, 0, 0, 1],
+            Ipv4Addr::new(10, 0, 0, 1),
+        )));
+        router.add_interface(AsyncNetworkInterface::new(NetworkInterface::new(
+            [2, 0, 0, 0, 1, 1],
+            Ipv4Addr::new(10, 0, 1, 1),
+        )));
+        router.add_route(u32::from(Ipv4Addr::new(10, 0, 1, 0)), 24, None, 1);
+
+        // the host behind interface 1 asks for the router, which learns its address
+        let mut host = NetworkInterface::new([2, 0, 0, 0, 1, 2], Ipv4Addr::new(10, 0, 1, 2));
+        host.send_datagram(
+            datagram(IPv4Header::ECN_NOT_ECT),
+            &Ipv4Addr::new(10, 0, 1, 1),
+        );
+        let arp_request = host.frames_out_mut().pop_front().unwrap();
+        router.interface_mut(1).recv_frame(&arp_request);
+        router.interface_mut(1).frames_out_mut().clear();
+
+        router.set_queue_limit(Some(2));
+        router.set_ecn_marking(true);
+        for ecn in [
+            IPv4Header::ECN_ECT0,
+            IPv4Header::ECN_NOT_ECT,
+            IPv4Header::ECN_ECT1,
+            IPv4Header::ECN_NOT_ECT,
+        ] {
+            router
+                .interface_mut(0)
+                .datagrams_out_mut()
+                .push_back(datagram(ecn));
+        }
+        router.route();
+
+        let forwarded: Vec<u8> = router
+            .interface_mut(1)
+            .frames_out()
+            .iter()
+            .map(|frame| {
+                let mut dgram = InternetDatagram::new(IPv4Header::new(), frame.payload().clone());
+                dgram.parse(0);
+                dgram.header().ecn()
+            })
+            .collect();
+        assert_eq!(
+            forwarded,
+            vec![
+                IPv4Header::ECN_ECT0,
+                IPv4Header::ECN_NOT_ECT,
+                IPv4Header::ECN_CE
+            ]
+        );
+        assert_eq!(router.ce_marked(), 1);
+        assert_eq!(router.dropped(), 1);
+
+        // without marking everything over the limit is dropped
+        router.set_ecn_marking(false);
+        router
+            .interface_mut(0)
+            .datagrams_out_mut()
+            .push_back(datagram(IPv4Header::ECN_ECT0));
+        router.route();
+        assert_eq!(router.interface_mut(1).frames_out().len(), 3);
+        assert_eq!(router.dropped(), 2);
+    }
+}
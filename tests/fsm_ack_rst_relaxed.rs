@@ -1,7 +1,7 @@
 use crate::tcp_fsm_test_harness::*;
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
 use rust_sponge::tcp_helpers::tcp_state::State::{LISTEN, RESET};
-use rust_sponge::tcp_helpers::tcp_state::{State, TCPState};
 use rust_sponge::wrapping_integers::WrappingInt32;
 use rust_sponge::SizeT;
 
@@ -13,10 +13,7 @@ fn ack_listen_test(cfg: &TCPConfig, seqno: WrappingInt32, ackno: WrappingInt32,
     // any ACK should result in a RST
     test.send_ack(seqno, ackno, Option::None);
 
-    test.execute(
-        &mut ExpectState::new(TCPState::from(LISTEN)),
-        "".to_string(),
-    );
+    test.execute(&mut ExpectState::new(LISTEN), "".to_string());
     test.execute(
         &mut ExpectNoSegment {},
         "test 3 failed: ACKs in LISTEN should be ignored".to_string(),
@@ -35,10 +32,7 @@ fn ack_rst_syn_sent_test(
     // any ACK should result in a RST
     test.send_ack(seqno, ackno, Option::None);
 
-    test.execute(
-        &mut ExpectState::new(TCPState::from(State::SynSent)),
-        "".to_string(),
-    );
+    test.execute(&mut ExpectState::new(State::SynSent), "".to_string());
     test.execute(
         &mut ExpectNoSegment {},
         "test 3 failed: bad ACKs in SYN_SENT should be ignored".to_string(),
@@ -118,7 +112,7 @@ fn fsm_ack_rst_relaxed() {
         );
 
         test_1.send_rst(base_seq + 1, Option::None);
-        test_1.execute(&mut ExpectState::new(TCPState::from(RESET)), "".to_string());
+        test_1.execute(&mut ExpectState::new(RESET), "".to_string());
     }
 
     // test #2: in LISTEN, send RSTs
@@ -162,7 +156,7 @@ fn fsm_ack_rst_relaxed() {
         // good ACK with RST should result in a RESET but no RST segment sent
         test_4.send_rst(base_seq, Option::Some(base_seq + 1));
 
-        test_4.execute(&mut ExpectState::new(TCPState::from(RESET)), "".to_string());
+        test_4.execute(&mut ExpectState::new(RESET), "".to_string());
         test_4.execute(
             &mut ExpectNoSegment {},
             "test 4 failed: RST with good ackno should RESET the connection".to_string(),
@@ -1,10 +1,10 @@
 use crate::tcp_fsm_test_harness::*;
 use rand::Rng;
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
 use rust_sponge::tcp_helpers::tcp_state::State::{
     CloseWait, LastAck, CLOSED, ESTABLISHED, LISTEN, RESET,
 };
-use rust_sponge::tcp_helpers::tcp_state::{State, TCPState};
 use rust_sponge::wrapping_integers::WrappingInt32;
 use rust_sponge::SizeT;
 
@@ -103,6 +103,6 @@ fn fsm_ack_rst_win_relaxed() {
         );
 
         test_1.send_rst(base_seq + 1, Option::None);
-        test_1.execute(&mut ExpectState::new(TCPState::from(RESET)), "".to_string());
+        test_1.execute(&mut ExpectState::new(RESET), "".to_string());
     }
 }
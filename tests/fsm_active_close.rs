@@ -1,7 +1,7 @@
 use crate::tcp_fsm_test_harness::*;
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
 use rust_sponge::tcp_helpers::tcp_state::State::{TimeWait, CLOSED, CLOSING};
-use rust_sponge::tcp_helpers::tcp_state::{State, TCPState};
 use rust_sponge::wrapping_integers::WrappingInt32;
 use rust_sponge::SizeT;
 
@@ -22,23 +22,14 @@ fn fsm_active_close() {
             &mut Tick::new((10 * cfg.rt_timeout - 1) as SizeT),
             "".to_string(),
         );
-        test_1.execute(
-            &mut ExpectState::new(TCPState::from(State::TimeWait)),
-            "".to_string(),
-        );
+        test_1.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
         test_1.execute(&mut Tick::new(1), "".to_string());
-        test_1.execute(
-            &mut ExpectNotInState::new(TCPState::from(State::TimeWait)),
-            "".to_string(),
-        );
+        test_1.execute(&mut ExpectNotInState::new(State::TimeWait), "".to_string());
         test_1.execute(
             &mut Tick::new((10 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_1.execute(
-            &mut ExpectState::new(TCPState::from(State::CLOSED)),
-            "".to_string(),
-        );
+        test_1.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
     }
 
     // test #2: start in CLOSING, send ack, time out
@@ -51,29 +42,17 @@ fn fsm_active_close() {
             "".to_string(),
         );
         test_2.execute(ExpectOneSegment::new().with_fin(true), "".to_string());
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(State::CLOSING)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(State::CLOSING), "".to_string());
         test_2.send_ack(WrappingInt32::new(2), WrappingInt32::new(2), Option::None);
         test_2.execute(&mut ExpectNoSegment {}, "".to_string());
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(State::TimeWait)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
         test_2.execute(
             &mut Tick::new((10 * cfg.rt_timeout - 1) as SizeT),
             "".to_string(),
         );
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(State::TimeWait)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
         test_2.execute(&mut Tick::new(2), "".to_string());
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(State::CLOSED)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
     }
 
     // test #3: start in FIN_WAIT_2, send FIN, time out
@@ -85,10 +64,7 @@ fn fsm_active_close() {
             &mut Tick::new((4 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(State::FinWait2)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(State::FinWait2), "".to_string());
         let rx_seqno = WrappingInt32::new(1);
         test_3.send_fin(rx_seqno, Option::Some(WrappingInt32::new(2)));
         let ack_expect = rx_seqno + 1;
@@ -99,18 +75,12 @@ fn fsm_active_close() {
                 .with_ackno(ack_expect),
             "test 3 failed: wrong ACK for FIN".to_string(),
         );
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(State::TimeWait)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
         test_3.execute(
             &mut Tick::new((10 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(State::CLOSED)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
     }
 
     // test #4: start in FIN_WAIT_1, ack, FIN, time out
@@ -146,10 +116,7 @@ fn fsm_active_close() {
             &mut Tick::new((10 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_4.execute(
-            &mut ExpectState::new(TCPState::from(State::CLOSED)),
-            "".to_string(),
-        );
+        test_4.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
     }
 
     // test 5: start in FIN_WAIT_1, ack, FIN, FIN again, time out
@@ -160,17 +127,11 @@ fn fsm_active_close() {
         // ACK the FIN
         let rx_seqno = WrappingInt32::new(1);
         test_5.send_ack(rx_seqno, WrappingInt32::new(2), Option::None);
-        test_5.execute(
-            &mut ExpectState::new(TCPState::from(State::FinWait2)),
-            "".to_string(),
-        );
+        test_5.execute(&mut ExpectState::new(State::FinWait2), "".to_string());
         test_5.execute(&mut Tick::new(5), "".to_string());
 
         test_5.send_fin(rx_seqno, Option::Some(WrappingInt32::new(2)));
-        test_5.execute(
-            &mut ExpectState::new(TCPState::from(State::TimeWait)),
-            "".to_string(),
-        );
+        test_5.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
         test_5.execute(&mut ExpectLingerTimer::new(0), "".to_string());
         let ack_expect = rx_seqno + 1;
         test_5.execute(&mut Tick::new(1), "".to_string());
@@ -203,10 +164,7 @@ fn fsm_active_close() {
             "test 5 failed: no ACK for 2nd FIN".to_string(),
         );
 
-        test_5.execute(
-            &mut ExpectState::new(TCPState::from(State::TimeWait)),
-            "".to_string(),
-        );
+        test_5.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
 
         // tick the timer and see what happens---a 2nd FIN in TIME_WAIT should reset the wait timer!
         // (this is an edge case of "throw it away and send another ack" for out-of-window segs)
@@ -222,10 +180,7 @@ fn fsm_active_close() {
 
         test_5.execute(&mut ExpectNoSegment {}, "".to_string());
         test_5.execute(&mut Tick::new(10), "".to_string());
-        test_5.execute(
-            &mut ExpectState::new(TCPState::from(State::CLOSED)),
-            "".to_string(),
-        );
+        test_5.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
     }
 
     // test 6: start in ESTABLISHED, get FIN, get FIN re-tx, send FIN, get ACK, send ACK, time out
@@ -263,10 +218,7 @@ fn fsm_active_close() {
         let ack_expect = rx_seqno + 1;
         test_6.execute(&mut Tick::new(1), "".to_string());
 
-        test_6.execute(
-            &mut ExpectState::new(TCPState::from(CLOSING)),
-            "".to_string(),
-        );
+        test_6.execute(&mut ExpectState::new(CLOSING), "".to_string());
         test_6.execute(
             ExpectOneSegment::new()
                 .with_ack(true)
@@ -276,17 +228,11 @@ fn fsm_active_close() {
 
         test_6.send_ack(ack_expect, seg2_hdr.seqno + 1, Option::None);
         test_6.execute(&mut Tick::new(1), "".to_string());
-        test_6.execute(
-            &mut ExpectState::new(TCPState::from(TimeWait)),
-            "".to_string(),
-        );
+        test_6.execute(&mut ExpectState::new(TimeWait), "".to_string());
         test_6.execute(
             &mut Tick::new((10 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_6.execute(
-            &mut ExpectState::new(TCPState::from(CLOSED)),
-            "".to_string(),
-        );
+        test_6.execute(&mut ExpectState::new(CLOSED), "".to_string());
     }
 }
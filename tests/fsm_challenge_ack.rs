@@ -0,0 +1,194 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod tcp_fsm_test_harness;
+
+fn expect_challenge_ack(
+    test: &mut TCPTestHarness,
+    seqno: WrappingInt32,
+    ackno: WrappingInt32,
+    msg: &str,
+) {
+    test.execute(
+        ExpectOneSegment::new()
+            .with_no_flags()
+            .with_ack(true)
+            .with_seqno(seqno)
+            .with_ackno(ackno)
+            .with_payload_size(0),
+        msg.to_string(),
+    );
+    test.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+}
+
+#[test]
+fn fsm_challenge_ack() {
+    let cfg = TCPConfig {
+        ..Default::default()
+    };
+    let tx_isn = WrappingInt32::new(1 << 31);
+    let rx_isn = WrappingInt32::new(12345);
+
+    // test #1: a RST in the window but not at rcv.nxt draws a challenge ack, one at rcv.nxt
+    // resets
+    {
+        let mut test_1 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+
+        test_1.send_rst(rx_isn + 2, Option::None);
+        expect_challenge_ack(
+            &mut test_1,
+            tx_isn + 1,
+            rx_isn + 1,
+            "test 1 failed: no challenge ack for in-window RST",
+        );
+        test_1.send_rst(rx_isn + 100, Option::Some(tx_isn + 1));
+        expect_challenge_ack(
+            &mut test_1,
+            tx_isn + 1,
+            rx_isn + 1,
+            "test 1 failed: no challenge ack for in-window RST",
+        );
+
+        test_1.send_rst(rx_isn + 1, Option::None);
+        test_1.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_1.execute(&mut ExpectState::new(State::RESET), "".to_string());
+    }
+
+    // test #2: a RST outside the window is dropped silently
+    {
+        let mut test_2 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+
+        test_2.send_rst(rx_isn, Option::None);
+        test_2.send_rst(rx_isn - 1000, Option::None);
+        test_2.send_rst(rx_isn + 1 + cfg.recv_capacity as u32, Option::None);
+        test_2.execute(
+            &mut ExpectNoSegment {},
+            "test 2 failed: RST outside the window was answered".to_string(),
+        );
+        test_2.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+    }
+
+    // test #3: a SYN in a synchronized state draws a challenge ack and is dropped
+    {
+        let mut test_3 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+
+        test_3.send_syn(rx_isn + 10, Option::None);
+        expect_challenge_ack(
+            &mut test_3,
+            tx_isn + 1,
+            rx_isn + 1,
+            "test 3 failed: no challenge ack for SYN",
+        );
+
+        // the peer's SYN/ACK once more, our ACK of it was lost
+        test_3.send_syn(rx_isn, Option::Some(tx_isn + 1));
+        expect_challenge_ack(
+            &mut test_3,
+            tx_isn + 1,
+            rx_isn + 1,
+            "test 3 failed: no ACK for retransmitted SYN/ACK",
+        );
+    }
+
+    // test #4: data acking what was never sent is dropped with a challenge ack
+    {
+        let mut test_4 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+
+        test_4.send_data(rx_isn + 1, tx_isn + 100, "spoofed");
+        expect_challenge_ack(
+            &mut test_4,
+            tx_isn + 1,
+            rx_isn + 1,
+            "test 4 failed: no challenge ack for ack beyond snd.nxt",
+        );
+        test_4.execute(&mut ExpectNoData {}, "".to_string());
+        test_4.execute(&mut ExpectUnassembledBytes::new(0), "".to_string());
+
+        test_4.send_data(rx_isn + 1, tx_isn + 1, "real");
+        test_4.execute(
+            ExpectOneSegment::new()
+                .with_ack(true)
+                .with_ackno(rx_isn + 5),
+            "".to_string(),
+        );
+        test_4.execute(
+            ExpectData::new().with_data("real".to_string()),
+            "".to_string(),
+        );
+    }
+
+    // test #5: an ack more than the largest offered window behind snd.una is dropped too
+    {
+        let mut test_5 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+
+        for i in 0..3 {
+            test_5.execute(&mut Write::new("x".repeat(100)), "".to_string());
+            test_5.execute(
+                ExpectOneSegment::new()
+                    .with_payload_size(100)
+                    .with_seqno(tx_isn + 1 + 100 * i),
+                "".to_string(),
+            );
+            test_5.send_ack(rx_isn + 1, tx_isn + 101 + 100 * i, Option::None);
+        }
+        test_5.execute(&mut ExpectNoSegment {}, "".to_string());
+
+        test_5.send_data(rx_isn + 1, tx_isn + 1, "old");
+        expect_challenge_ack(
+            &mut test_5,
+            tx_isn + 301,
+            rx_isn + 1,
+            "test 5 failed: no challenge ack for ack too old",
+        );
+        test_5.execute(&mut ExpectNoData {}, "".to_string());
+
+        // a plain duplicate is fine
+        test_5.send_data(rx_isn + 1, tx_isn + 201, "dup");
+        test_5.execute(
+            ExpectOneSegment::new()
+                .with_ack(true)
+                .with_ackno(rx_isn + 4),
+            "".to_string(),
+        );
+        test_5.execute(
+            ExpectData::new().with_data("dup".to_string()),
+            "".to_string(),
+        );
+    }
+
+    // test #6: challenge acks are rate limited per window, on each connection
+    {
+        let cfg = TCPConfig {
+            challenge_ack_limit: 2,
+            ..Default::default()
+        };
+        let mut test_6 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+
+        for _ in 0..2 {
+            test_6.send_rst(rx_isn + 2, Option::None);
+            expect_challenge_ack(&mut test_6, tx_isn + 1, rx_isn + 1, "");
+        }
+        test_6.send_rst(rx_isn + 2, Option::None);
+        test_6.execute(
+            &mut ExpectNoSegment {},
+            "test 6 failed: challenge ack over the limit".to_string(),
+        );
+
+        test_6.execute(
+            &mut Tick::new(TCPConfig::CHALLENGE_ACK_WINDOW - 1),
+            "".to_string(),
+        );
+        test_6.send_syn(rx_isn + 2, Option::None);
+        test_6.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_6.execute(&mut Tick::new(1), "".to_string());
+        test_6.send_syn(rx_isn + 2, Option::None);
+        expect_challenge_ack(
+            &mut test_6,
+            tx_isn + 1,
+            rx_isn + 1,
+            "test 6 failed: limit not reset after the window",
+        );
+    }
+}
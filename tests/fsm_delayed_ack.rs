@@ -0,0 +1,123 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn t_fsm_delayed_ack() {
+    let cfg = TCPConfig {
+        delayed_ack: true,
+        ack_delay: 100,
+        ..Default::default()
+    };
+    let tx_isn = WrappingInt32::new(1 << 31);
+    let rx_isn = WrappingInt32::new(5000);
+    let x = |len: usize| "x".repeat(len);
+
+    // by default every segment is acked right away
+    {
+        let mut test_1 = TCPTestHarness::in_established(&TCPConfig::default(), tx_isn, rx_isn);
+        test_1.send_data(rx_isn + 1, tx_isn + 1, &x(10));
+        test_1.send_data(rx_isn + 11, tx_isn + 1, &x(10));
+        test_1.execute(
+            ExpectSegment::new()
+                .with_ackno(rx_isn + 11)
+                .with_payload_size(0),
+            "".to_string(),
+        );
+        test_1.execute(
+            ExpectOneSegment::new()
+                .with_ackno(rx_isn + 21)
+                .with_payload_size(0),
+            "".to_string(),
+        );
+    }
+
+    // a lone segment waits for the delay timer
+    {
+        let mut test_2 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_2.send_data(rx_isn + 1, tx_isn + 1, &x(10));
+        test_2.execute(&mut Tick::new(60), "".to_string());
+        test_2.send_data(rx_isn + 11, tx_isn + 1, &x(10));
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_2.execute(&mut Tick::new(39), "".to_string());
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_2.execute(&mut Tick::new(1), "".to_string());
+        test_2.execute(
+            ExpectOneSegment::new()
+                .with_ackno(rx_isn + 21)
+                .with_payload_size(0),
+            "test 2 failed: no ack once the delay is up".to_string(),
+        );
+        test_2.execute(&mut Tick::new(200), "".to_string());
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+    }
+
+    // every second full segment is acked at once
+    {
+        let mut test_3 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_3.send_data(rx_isn + 1, tx_isn + 1, &x(1000));
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_3.send_data(rx_isn + 1001, tx_isn + 1, &x(1000));
+        test_3.execute(
+            ExpectOneSegment::new()
+                .with_ackno(rx_isn + 2001)
+                .with_payload_size(0),
+            "test 3 failed: second full segment not acked".to_string(),
+        );
+        test_3.execute(&mut Tick::new(100), "".to_string());
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+    }
+
+    // out-of-order data, the segment filling the hole and a FIN are acked at once
+    {
+        let mut test_4 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_4.send_data(rx_isn + 1, tx_isn + 1, &x(10));
+        test_4.send_data(rx_isn + 21, tx_isn + 1, &x(10));
+        test_4.execute(
+            ExpectOneSegment::new()
+                .with_ackno(rx_isn + 11)
+                .with_payload_size(0),
+            "test 4 failed: out-of-order data not acked".to_string(),
+        );
+        test_4.send_data(rx_isn + 11, tx_isn + 1, &x(10));
+        test_4.execute(
+            ExpectOneSegment::new()
+                .with_ackno(rx_isn + 31)
+                .with_payload_size(0),
+            "test 4 failed: filled hole not acked".to_string(),
+        );
+        test_4.execute(
+            SendSegment::default()
+                .with_ack(true)
+                .with_fin(true)
+                .with_seqno(rx_isn + 31)
+                .with_ackno(tx_isn + 1)
+                .with_win(TCPTestHarness::DEFAULT_TEST_WINDOW as u16)
+                .with_data(x(10)),
+            "".to_string(),
+        );
+        test_4.execute(
+            ExpectOneSegment::new()
+                .with_ackno(rx_isn + 42)
+                .with_payload_size(0),
+            "test 4 failed: FIN not acked".to_string(),
+        );
+    }
+
+    // outgoing data carries the ack
+    {
+        let mut test_5 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_5.send_data(rx_isn + 1, tx_isn + 1, &x(10));
+        test_5.execute(&mut Write::new("reply".to_string()), "".to_string());
+        test_5.execute(
+            ExpectOneSegment::new()
+                .with_ackno(rx_isn + 11)
+                .with_payload_size(5),
+            "test 5 failed: reply doesn't carry the ack".to_string(),
+        );
+        test_5.execute(&mut Tick::new(100), "".to_string());
+        test_5.execute(&mut ExpectNoSegment {}, "".to_string());
+    }
+}
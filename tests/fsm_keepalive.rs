@@ -0,0 +1,106 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_connection::ResetReason;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn t_fsm_keepalive() {
+    let cfg = TCPConfig {
+        keepalive: true,
+        keepalive_idle: 1000,
+        keepalive_interval: 100,
+        keepalive_probes: 3,
+        ..Default::default()
+    };
+    let tx_isn = WrappingInt32::new(1 << 31);
+    let rx_isn = WrappingInt32::new(5000);
+    // a probe is an empty ACK one below our next seqno
+    let probe = || {
+        let mut e = ExpectOneSegment::new();
+        e.with_no_flags()
+            .with_ack(true)
+            .with_seqno(tx_isn)
+            .with_ackno(rx_isn + 1)
+            .with_payload_size(0);
+        e
+    };
+
+    // off by default
+    {
+        let mut test_1 = TCPTestHarness::in_established(&TCPConfig::default(), tx_isn, rx_isn);
+        test_1.execute(
+            &mut Tick::new(TCPConfig::KEEPALIVE_IDLE_DFLT * 2),
+            "".to_string(),
+        );
+        test_1.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_1.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+    }
+
+    // an answered probe starts the idle time over
+    {
+        let mut test_2 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_2.execute(&mut Tick::new(999), "".to_string());
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_2.execute(&mut Tick::new(1), "".to_string());
+        test_2.execute(&mut probe(), "test 2 failed: no probe".to_string());
+        test_2.execute(&mut Tick::new(50), "".to_string());
+        test_2.send_ack(rx_isn + 1, tx_isn + 1, None);
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_2.execute(&mut Tick::new(999), "".to_string());
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_2.execute(&mut Tick::new(1), "".to_string());
+        test_2.execute(&mut probe(), "test 2 failed: no probe".to_string());
+        test_2.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+    }
+
+    // unanswered probes reset the connection
+    {
+        let mut test_3 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_3.execute(&mut Tick::new(1000), "".to_string());
+        test_3.execute(&mut probe(), "".to_string());
+        test_3.execute(&mut Tick::new(100), "".to_string());
+        test_3.execute(&mut probe(), "".to_string());
+        test_3.execute(&mut Tick::new(100), "".to_string());
+        test_3.execute(&mut probe(), "".to_string());
+        test_3.execute(&mut Tick::new(99), "".to_string());
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_3.execute(&mut ExpectResetReason::new(None), "".to_string());
+        test_3.execute(&mut Tick::new(1), "".to_string());
+        test_3.execute(
+            ExpectOneSegment::new().with_rst(true),
+            "test 3 failed: no RST after the last probe".to_string(),
+        );
+        test_3.execute(&mut ExpectState::new(State::RESET), "".to_string());
+        test_3.execute(
+            &mut ExpectResetReason::new(Some(ResetReason::KeepaliveTimeout)),
+            "".to_string(),
+        );
+    }
+
+    // no probes while data is in flight
+    {
+        let mut test_4 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_4.execute(&mut Write::new("hello".to_string()), "".to_string());
+        test_4.execute(ExpectOneSegment::new().with_payload_size(5), "".to_string());
+        test_4.execute(&mut Tick::new(1000), "".to_string());
+        test_4.execute(
+            ExpectOneSegment::new()
+                .with_seqno(tx_isn + 1)
+                .with_payload_size(5),
+            "test 4 failed: expected the retransmission, not a probe".to_string(),
+        );
+    }
+
+    {
+        let mut test_5 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_5.send_rst(rx_isn + 1, Some(tx_isn + 1));
+        test_5.execute(&mut ExpectState::new(State::RESET), "".to_string());
+        test_5.execute(
+            &mut ExpectResetReason::new(Some(ResetReason::PeerReset)),
+            "".to_string(),
+        );
+    }
+}
@@ -10,8 +10,11 @@ mod tcp_fsm_test_harness;
 #[test]
 fn fsm_loopback() {
     const NREPS: u32 = 64;
+    // full segments of MAX_PAYLOAD_SIZE, as the peer's SYN announces it
+    let mss = Some(TCPConfig::MAX_PAYLOAD_SIZE as u16);
 
     let mut cfg = TCPConfig {
+        nodelay: true,
         ..Default::default()
     };
     cfg.recv_capacity = 65000;
@@ -21,7 +24,8 @@ fn fsm_loopback() {
     // non-overlapping out-of-order segments
     for rep_no in 0..NREPS {
         let rx_offset = WrappingInt32::new(rand::thread_rng().gen_range(0..=u32::MAX));
-        let mut test_1 = TCPTestHarness::in_established(&cfg, rx_offset - 1, rx_offset - 1);
+        let mut test_1 =
+            TCPTestHarness::in_established_with_mss(&cfg, rx_offset - 1, rx_offset - 1, mss);
         test_1.send_ack(rx_offset, rx_offset, Option::Some(65000));
 
         let d: String = (0..cfg.recv_capacity)
@@ -14,6 +14,7 @@ fn fsm_loopback_win() {
     const NREPS: u32 = 32;
 
     let mut cfg = TCPConfig {
+        nodelay: true,
         ..Default::default()
     };
     cfg.recv_capacity = 65000;
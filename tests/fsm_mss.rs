@@ -0,0 +1,156 @@
+use rand::thread_rng;
+use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_helpers::fd_adapter::{AsFdAdapterBase, TCPOverUDPSocketAdapter};
+use rust_sponge::tcp_helpers::lossy_fd_adapter::LossyFdAdapter;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::util::socket::UDPSocket;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+// passive open with the given peer MSS option, then a 5000 byte write sent without nagle
+fn segment_sizes(cfg: TCPConfig, isn: WrappingInt32, peer_mss: Option<u16>) -> Vec<SizeT> {
+    let mut conn = TCPConnection::new(TCPConfig {
+        nodelay: true,
+        ..cfg
+    });
+    let mut header = TCPHeader::new();
+    header.syn = true;
+    header.seqno = isn;
+    header.mss = peer_mss;
+    conn.segment_received(&TCPSegment::new(header, Buffer::new(vec![])));
+    let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+    assert_eq!(syn_ack.header().mss, Some(cfg.segment_size() as u16));
+
+    let mut ack = TCPHeader::new();
+    ack.ack = true;
+    ack.seqno = isn + 1;
+    ack.ackno = syn_ack.header().seqno + 1;
+    ack.win = 60000;
+    conn.segment_received(&TCPSegment::new(ack, Buffer::new(vec![])));
+    conn.write(vec![b'a'; 5000].as_slice());
+
+    conn.segments_out_mut()
+        .drain(..)
+        .map(|seg| seg.payload().size())
+        .collect()
+}
+
+#[test]
+fn t_fsm_mss() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let adapter = TCPOverUDPSocketAdapter::new(UDPSocket::new());
+        assert_eq!(adapter.mss(), 1500 - 20 - 8 - 20);
+        let adapter = LossyFdAdapter::new(adapter);
+        assert_eq!(adapter.mss(), 1452);
+    }
+
+    {
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.mss = Some(1460);
+        header.wscale = Some(7);
+        header.sack_permitted = true;
+        let mut seg = TCPSegment::new(header.clone(), Buffer::new(vec![]));
+        let parsed = TCPSegment::parse_new(Buffer::new(seg.serialize(0)), 0).unwrap();
+        assert!(*parsed.header() == header);
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig::default();
+        assert_eq!(
+            segment_sizes(cfg, isn, Some(500)),
+            vec![500; 10],
+            "the peer's smaller MSS is honored"
+        );
+        assert_eq!(
+            segment_sizes(cfg, isn, None),
+            vec![536, 536, 536, 536, 536, 536, 536, 536, 536, 176],
+            "without the option the peer is assumed to accept 536 bytes"
+        );
+        assert_eq!(segment_sizes(cfg, isn, Some(1460)), vec![1000; 5]);
+
+        let mut cfg = TCPConfig::default();
+        cfg.mss = Some(1452);
+        assert_eq!(
+            segment_sizes(cfg, isn, Some(1460)),
+            vec![1452, 1452, 1452, 644]
+        );
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        cfg.mss = Some(1460);
+        let mut conn = TCPConnection::new(cfg);
+        conn.connect();
+        let syn = conn.segments_out_mut().pop_front().unwrap();
+        assert_eq!(syn.header().mss, Some(1460));
+        assert_eq!(conn.mss(), 1460);
+
+        let mut syn_ack = TCPHeader::new();
+        syn_ack.syn = true;
+        syn_ack.ack = true;
+        syn_ack.ackno = syn.header().seqno + 1;
+        syn_ack.mss = Some(536);
+        syn_ack.win = 10000;
+        conn.segment_received(&TCPSegment::new(syn_ack, Buffer::new(vec![])));
+        assert_eq!(conn.mss(), 536);
+    }
+
+    // options come out of the MSS: the timestamps always, SACK blocks while there are some
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut cfg = TCPConfig::default();
+        cfg.timestamps = true;
+        cfg.sack = true;
+        cfg.nodelay = true;
+        let mut conn = TCPConnection::new(cfg);
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.seqno = isn;
+        header.mss = Some(1000);
+        header.sack_permitted = true;
+        header.timestamps = Some((1, 0));
+        conn.segment_received(&TCPSegment::new(header, Buffer::new(vec![])));
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+
+        let mut ack = TCPHeader::new();
+        ack.ack = true;
+        ack.seqno = isn + 1;
+        ack.ackno = syn_ack.header().seqno + 1;
+        ack.win = 60000;
+        ack.timestamps = Some((2, 0));
+        conn.segment_received(&TCPSegment::new(ack.clone(), Buffer::new(vec![])));
+        conn.write(vec![b'a'; 2000].as_slice());
+        let sizes: Vec<SizeT> = conn
+            .segments_out_mut()
+            .drain(..)
+            .map(|seg| seg.payload().size())
+            .collect();
+        assert_eq!(sizes, vec![988, 988, 24]);
+
+        // a hole in what the peer sent leaves a SACK block to report
+        ack.seqno = isn + 11;
+        ack.ackno = ack.ackno + 2000;
+        ack.timestamps = Some((3, 0));
+        conn.segment_received(&TCPSegment::new(ack, Buffer::new(vec![b'b'; 10])));
+        conn.segments_out_mut().clear();
+        conn.write(vec![b'a'; 2000].as_slice());
+        let sizes: Vec<SizeT> = conn
+            .segments_out_mut()
+            .drain(..)
+            .map(|seg| {
+                assert_eq!(seg.header().sack_blocks.len(), 1);
+                seg.payload().size()
+            })
+            .collect();
+        assert_eq!(sizes, vec![976, 976, 48]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn t_fsm_nagle() {
+    let tx_isn = WrappingInt32::new(1 << 31);
+    // full segments of MAX_PAYLOAD_SIZE, as the peer's SYN announces it
+    let mss = Some(TCPConfig::MAX_PAYLOAD_SIZE as u16);
+    let rx_isn = WrappingInt32::new(5000);
+    let write = |c: char, len: usize| Write::new(c.to_string().repeat(len));
+
+    // nodelay sends every write right away
+    {
+        let cfg = TCPConfig {
+            nodelay: true,
+            ..Default::default()
+        };
+        let mut test_1 = TCPTestHarness::in_established_with_mss(&cfg, tx_isn, rx_isn, mss);
+        test_1.execute(&mut write('a', 1), "".to_string());
+        test_1.execute(&mut write('b', 1), "".to_string());
+        test_1.execute(ExpectSegment::new().with_payload_size(1), "".to_string());
+        test_1.execute(
+            ExpectOneSegment::new().with_payload_size(1),
+            "test 1 failed: second write held back".to_string(),
+        );
+    }
+
+    // by default small writes coalesce while data is unacked
+    {
+        let mut test_2 =
+            TCPTestHarness::in_established_with_mss(&TCPConfig::default(), tx_isn, rx_isn, mss);
+        test_2.send_ack(rx_isn + 1, tx_isn + 1, Some(60000));
+        test_2.execute(&mut write('a', 10), "".to_string());
+        test_2.execute(
+            ExpectOneSegment::new().with_payload_size(10),
+            "".to_string(),
+        );
+        test_2.execute(&mut write('b', 10), "".to_string());
+        test_2.execute(&mut write('c', 10), "".to_string());
+        test_2.execute(
+            &mut ExpectNoSegment {},
+            "test 2 failed: partial segment sent with data unacked".to_string(),
+        );
+        test_2.send_ack(rx_isn + 1, tx_isn + 11, Some(60000));
+        test_2.execute(
+            ExpectOneSegment::new().with_payload_size(20),
+            "test 2 failed: held data not sent on the ack".to_string(),
+        );
+
+        // full segments still go out, only the tail waits
+        test_2.execute(&mut write('d', 2500), "".to_string());
+        test_2.execute(ExpectSegment::new().with_payload_size(1000), "".to_string());
+        test_2.execute(
+            ExpectOneSegment::new().with_payload_size(1000),
+            "".to_string(),
+        );
+        test_2.send_ack(rx_isn + 1, tx_isn + 2031, Some(60000));
+        test_2.execute(
+            ExpectOneSegment::new().with_payload_size(500),
+            "".to_string(),
+        );
+
+        // turning nagle off sends what was held back
+        test_2.execute(&mut write('e', 10), "".to_string());
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_2.execute(&mut SetNodelay::new(true), "".to_string());
+        test_2.execute(
+            ExpectOneSegment::new().with_payload_size(10),
+            "test 2 failed: nodelay didn't flush".to_string(),
+        );
+        test_2.execute(&mut write('f', 10), "".to_string());
+        test_2.execute(
+            ExpectOneSegment::new().with_payload_size(10),
+            "".to_string(),
+        );
+    }
+
+    // closing the stream flushes the tail along with the FIN
+    {
+        let mut test_3 =
+            TCPTestHarness::in_established_with_mss(&TCPConfig::default(), tx_isn, rx_isn, mss);
+        test_3.execute(&mut write('a', 10), "".to_string());
+        test_3.execute(&mut write('b', 10), "".to_string());
+        test_3.execute(
+            ExpectOneSegment::new().with_payload_size(10),
+            "".to_string(),
+        );
+        test_3.execute(&mut Close {}, "".to_string());
+        test_3.execute(
+            ExpectOneSegment::new().with_fin(true).with_payload_size(10),
+            "test 3 failed: tail not sent with the FIN".to_string(),
+        );
+    }
+
+    // the cork holds partial segments even with nothing in flight, until the timeout
+    {
+        let cfg = TCPConfig {
+            cork: true,
+            cork_timeout: 100,
+            ..Default::default()
+        };
+        let mut test_4 = TCPTestHarness::in_established_with_mss(&cfg, tx_isn, rx_isn, mss);
+        test_4.send_ack(rx_isn + 1, tx_isn + 1, Some(60000));
+        test_4.execute(&mut write('a', 10), "".to_string());
+        test_4.execute(&mut Tick::new(50), "".to_string());
+        test_4.execute(&mut write('b', 10), "".to_string());
+        test_4.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_4.execute(&mut Tick::new(49), "".to_string());
+        test_4.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_4.execute(&mut Tick::new(1), "".to_string());
+        test_4.execute(
+            ExpectOneSegment::new().with_payload_size(20),
+            "test 4 failed: corked data not sent on the timeout".to_string(),
+        );
+
+        // the timeout restarts with the next partial segment
+        test_4.execute(&mut write('c', 1200), "".to_string());
+        test_4.execute(
+            ExpectOneSegment::new().with_payload_size(1000),
+            "".to_string(),
+        );
+        test_4.execute(&mut Tick::new(99), "".to_string());
+        test_4.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_4.execute(&mut Tick::new(1), "".to_string());
+        test_4.execute(
+            ExpectOneSegment::new().with_payload_size(200),
+            "".to_string(),
+        );
+    }
+
+    // uncorking sends right away
+    {
+        let cfg = TCPConfig {
+            cork: true,
+            ..Default::default()
+        };
+        let mut test_5 = TCPTestHarness::in_established_with_mss(&cfg, tx_isn, rx_isn, mss);
+        test_5.execute(&mut write('a', 10), "".to_string());
+        test_5.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_5.execute(&mut SetCork::new(false), "".to_string());
+        test_5.execute(
+            ExpectOneSegment::new().with_payload_size(10),
+            "test 5 failed: uncorking didn't flush".to_string(),
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::tcp_fsm_test_harness::*;
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
 use rust_sponge::tcp_helpers::tcp_state::State::{CloseWait, LastAck, CLOSED, ESTABLISHED};
-use rust_sponge::tcp_helpers::tcp_state::{State, TCPState};
 use rust_sponge::wrapping_integers::WrappingInt32;
 use rust_sponge::SizeT;
 
@@ -22,16 +22,10 @@ fn fsm_passive_close() {
             &mut Tick::new((4 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_1.execute(
-            &mut ExpectState::new(TCPState::from(State::LastAck)),
-            "".to_string(),
-        );
+        test_1.execute(&mut ExpectState::new(State::LastAck), "".to_string());
         test_1.send_ack(WrappingInt32::new(2), WrappingInt32::new(2), Option::None);
         test_1.execute(&mut Tick::new(1), "".to_string());
-        test_1.execute(
-            &mut ExpectState::new(TCPState::from(State::CLOSED)),
-            "".to_string(),
-        );
+        test_1.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
     }
 
     // test #2: start in CLOSE_WAIT, close(), throw away first FIN, ack re-tx FIN
@@ -43,17 +37,11 @@ fn fsm_passive_close() {
             &mut Tick::new((4 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(CloseWait)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(CloseWait), "".to_string());
         test_2.execute(&mut Close {}, "".to_string());
         test_2.execute(&mut Tick::new(1), "".to_string());
 
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(LastAck)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(LastAck), "".to_string());
 
         let seg1 = test_2.expect_one_seg(
             ExpectOneSegment::new().with_fin(true),
@@ -84,18 +72,12 @@ fn fsm_passive_close() {
         test_2.send_ack(ack_expect, seg2.header().seqno - 1, Option::None); // wrong ackno!
         test_2.execute(&mut Tick::new(1), "".to_string());
 
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(LastAck)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(LastAck), "".to_string());
 
         test_2.send_ack(ack_expect, seg2.header().seqno + 1, Option::None);
         test_2.execute(&mut Tick::new(1), "".to_string());
 
-        test_2.execute(
-            &mut ExpectState::new(TCPState::from(CLOSED)),
-            "".to_string(),
-        );
+        test_2.execute(&mut ExpectState::new(CLOSED), "".to_string());
     }
 
     // test #3: start in ESTABLSHED, send FIN, recv ACK, check for CLOSE_WAIT
@@ -107,10 +89,7 @@ fn fsm_passive_close() {
             &mut Tick::new((4 * cfg.rt_timeout) as SizeT),
             "".to_string(),
         );
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(ESTABLISHED)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(ESTABLISHED), "".to_string());
 
         let rx_seqno = WrappingInt32::new(1);
         let ack_expect = rx_seqno + 1;
@@ -124,10 +103,7 @@ fn fsm_passive_close() {
             "test 3 failed: bad seg, no ACK, or wrong ackno".to_string(),
         );
 
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(CloseWait)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(CloseWait), "".to_string());
 
         test_3.send_fin(rx_seqno, Option::Some(WrappingInt32::new(0)));
         test_3.execute(&mut Tick::new(1), "".to_string());
@@ -139,19 +115,13 @@ fn fsm_passive_close() {
             "test 3 falied: bad response to 2nd FIN".to_string(),
         );
 
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(CloseWait)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(CloseWait), "".to_string());
 
         test_3.execute(&mut Tick::new(1), "".to_string());
         test_3.execute(&mut Close {}, "".to_string());
         test_3.execute(&mut Tick::new(1), "".to_string());
 
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(LastAck)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(LastAck), "".to_string());
 
         let seg3 = test_3.expect_one_seg(
             ExpectOneSegment::new().with_fin(true),
@@ -161,9 +131,6 @@ fn fsm_passive_close() {
         test_3.send_ack(ack_expect, seg3.header().seqno + 1, Option::None);
         test_3.execute(&mut Tick::new(1), "".to_string());
 
-        test_3.execute(
-            &mut ExpectState::new(TCPState::from(CLOSED)),
-            "".to_string(),
-        );
+        test_3.execute(&mut ExpectState::new(CLOSED), "".to_string());
     }
 }
@@ -0,0 +1,57 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_connection::ResetReason;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn fsm_persist() {
+    let cfg = TCPConfig {
+        persist: true,
+        rto_max: 8000,
+        ..Default::default()
+    };
+    let tx_isn = WrappingInt32::new(1 << 31);
+    let rx_isn = WrappingInt32::new(5000);
+    let probe = || {
+        let mut e = ExpectOneSegment::new();
+        e.with_seqno(tx_isn + 1).with_data("a".to_string());
+        e
+    };
+
+    // a peer that answers the probes keeps the connection, one that stops answering doesn't
+    {
+        let mut test_1 = TCPTestHarness::in_established(&cfg, tx_isn, rx_isn);
+        test_1.execute(&mut Write::new("abc".to_string()), "".to_string());
+        test_1.execute(ExpectOneSegment::new().with_payload_size(3), "".to_string());
+        test_1.send_ack(rx_isn + 1, tx_isn + 1, Some(0));
+
+        for _ in 0..TCPConfig::MAX_RETX_ATTEMPTS - 1 {
+            test_1.execute(&mut Tick::new(cfg.rto_max as SizeT), "".to_string());
+            test_1.execute(&mut probe(), "".to_string());
+        }
+        test_1.send_ack(rx_isn + 1, tx_isn + 1, Some(0));
+
+        for _ in 0..TCPConfig::MAX_RETX_ATTEMPTS {
+            test_1.execute(&mut Tick::new(cfg.rto_max as SizeT), "".to_string());
+            test_1.execute(
+                &mut probe(),
+                "test 1 failed: an answered probe still counts".to_string(),
+            );
+        }
+        test_1.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+        test_1.execute(&mut Tick::new(1), "".to_string());
+        test_1.execute(
+            ExpectOneSegment::new().with_rst(true),
+            "test 1 failed: no RST after the unanswered probes".to_string(),
+        );
+        test_1.execute(&mut ExpectState::new(State::RESET), "".to_string());
+        test_1.execute(
+            &mut ExpectResetReason::new(Some(ResetReason::WindowProbeLimit)),
+            "".to_string(),
+        );
+    }
+}
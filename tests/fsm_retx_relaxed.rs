@@ -1,8 +1,8 @@
 use crate::tcp_fsm_test_harness::*;
 use rand::Rng;
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
 use rust_sponge::tcp_helpers::tcp_state::State::{CloseWait, LastAck, CLOSED, ESTABLISHED, RESET};
-use rust_sponge::tcp_helpers::tcp_state::{State, TCPState};
 use rust_sponge::wrapping_integers::WrappingInt32;
 use rust_sponge::SizeT;
 
@@ -65,16 +65,13 @@ fn fsm_retx_relaxed() {
             check_segment(&mut test_1, &data, false, line!());
         }
 
-        test_1.execute(
-            &mut ExpectState::new(TCPState::from(ESTABLISHED)),
-            "".to_string(),
-        );
+        test_1.execute(&mut ExpectState::new(ESTABLISHED), "".to_string());
 
         test_1.execute(
             &mut Tick::new((1 + (cfg.rt_timeout << TCPConfig::MAX_RETX_ATTEMPTS)) as SizeT),
             "".to_string(),
         );
-        test_1.execute(&mut ExpectState::new(TCPState::from(RESET)), "".to_string());
+        test_1.execute(&mut ExpectState::new(RESET), "".to_string());
         test_1.execute(
             ExpectOneSegment::new().with_rst(true),
             "test 1 failed: RST on re-tx failure was malformed".to_string(),
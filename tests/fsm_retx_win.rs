@@ -70,6 +70,7 @@ fn backoff_test(num_backoffs: u32, cfg: &TCPConfig) {
 #[test]
 fn fsm_retx_win() {
     let mut cfg = TCPConfig {
+        nodelay: true,
         ..Default::default()
     };
     cfg.recv_capacity = 65000;
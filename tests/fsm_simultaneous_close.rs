@@ -0,0 +1,106 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn fsm_simultaneous_close() {
+    let cfg = TCPConfig {
+        ..Default::default()
+    };
+    let tx_isn = WrappingInt32::new(1 << 31);
+    let rx_isn = WrappingInt32::new(12345);
+
+    // test #1: FIN_WAIT_1 -> FIN -> CLOSING -> ACK -> TIME_WAIT -> CLOSED
+    {
+        let mut test_1 = TCPTestHarness::in_fin_wait_1(&cfg, tx_isn, rx_isn);
+
+        // the peer's FIN crossed ours and doesn't ack it
+        test_1.send_fin(rx_isn + 1, Option::Some(tx_isn + 1));
+        test_1.execute(
+            ExpectOneSegment::new()
+                .with_no_flags()
+                .with_ack(true)
+                .with_seqno(tx_isn + 2)
+                .with_ackno(rx_isn + 2),
+            "test 1 failed: bad ACK for the crossing FIN".to_string(),
+        );
+        test_1.execute(&mut ExpectState::new(State::CLOSING), "".to_string());
+
+        test_1.send_ack(rx_isn + 2, tx_isn + 2, Option::None);
+        test_1.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_1.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
+        test_1.execute(
+            &mut Tick::new((10 * cfg.rt_timeout) as SizeT),
+            "".to_string(),
+        );
+        test_1.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
+    }
+
+    // test #2: a FIN that also acks ours goes straight to TIME_WAIT
+    {
+        let mut test_2 = TCPTestHarness::in_fin_wait_1(&cfg, tx_isn, rx_isn);
+
+        test_2.send_fin(rx_isn + 1, Option::Some(tx_isn + 2));
+        test_2.execute(
+            ExpectOneSegment::new()
+                .with_no_flags()
+                .with_ack(true)
+                .with_ackno(rx_isn + 2),
+            "".to_string(),
+        );
+        test_2.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
+    }
+
+    // test #3: a retransmitted FIN in CLOSING is acked again
+    {
+        let mut test_3 = TCPTestHarness::in_closing(&cfg, tx_isn, rx_isn);
+
+        test_3.send_fin(rx_isn + 1, Option::Some(tx_isn + 1));
+        test_3.execute(
+            ExpectOneSegment::new()
+                .with_no_flags()
+                .with_ack(true)
+                .with_ackno(rx_isn + 2),
+            "test 3 failed: duplicate FIN not acked".to_string(),
+        );
+        test_3.execute(&mut ExpectState::new(State::CLOSING), "".to_string());
+
+        // and our FIN is still retransmitted
+        test_3.execute(&mut Tick::new(cfg.rt_timeout as SizeT), "".to_string());
+        test_3.execute(
+            ExpectOneSegment::new()
+                .with_fin(true)
+                .with_seqno(tx_isn + 1),
+            "test 3 failed: FIN not retransmitted in CLOSING".to_string(),
+        );
+    }
+
+    // test #4: a retransmitted FIN in TIME_WAIT is acked and restarts the timer
+    {
+        let mut test_4 = TCPTestHarness::in_time_wait(&cfg, tx_isn, rx_isn);
+
+        test_4.execute(
+            &mut Tick::new((10 * cfg.rt_timeout - 1) as SizeT),
+            "".to_string(),
+        );
+        test_4.send_fin(rx_isn + 1, Option::Some(tx_isn + 2));
+        test_4.execute(
+            ExpectOneSegment::new()
+                .with_no_flags()
+                .with_ack(true)
+                .with_ackno(rx_isn + 2),
+            "test 4 failed: retransmitted FIN not acked".to_string(),
+        );
+        test_4.execute(
+            &mut Tick::new((10 * cfg.rt_timeout - 1) as SizeT),
+            "".to_string(),
+        );
+        test_4.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
+        test_4.execute(&mut Tick::new(1), "".to_string());
+        test_4.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::State;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn fsm_simultaneous_open() {
+    let cfg = TCPConfig {
+        ..Default::default()
+    };
+    let tx_isn = WrappingInt32::new(1 << 31);
+    let rx_isn = WrappingInt32::new(12345);
+
+    // test #1: SYN_SENT -> SYN -> SYN_RCVD -> SYN/ACK -> ESTABLISHED
+    {
+        let mut test_1 = TCPTestHarness::in_syn_sent(&cfg, tx_isn);
+
+        // the peer's SYN crossed ours, which is sent again along with the ACK
+        test_1.send_syn(rx_isn, Option::None);
+        test_1.execute(
+            ExpectOneSegment::new()
+                .with_syn(true)
+                .with_ack(true)
+                .with_seqno(tx_isn)
+                .with_ackno(rx_isn + 1)
+                .with_payload_size(0),
+            "test 1 failed: no SYN/ACK for a SYN in SYN_SENT".to_string(),
+        );
+        test_1.execute(&mut ExpectState::new(State::SynRcvd), "".to_string());
+
+        // the peer answers ours with its own SYN/ACK
+        test_1.send_syn(rx_isn, Option::Some(tx_isn + 1));
+        test_1.execute(
+            ExpectOneSegment::new()
+                .with_no_flags()
+                .with_ack(true)
+                .with_seqno(tx_isn + 1)
+                .with_ackno(rx_isn + 1),
+            "test 1 failed: bad ACK for the SYN/ACK".to_string(),
+        );
+        test_1.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+        test_1.execute(&mut ExpectBytesInFlight::new(0), "".to_string());
+    }
+
+    // test #2: SYN_SENT -> SYN -> SYN_RCVD -> ACK -> ESTABLISHED
+    {
+        let mut test_2 = TCPTestHarness::in_syn_sent(&cfg, tx_isn);
+
+        test_2.send_syn(rx_isn, Option::None);
+        test_2.execute(
+            ExpectOneSegment::new().with_syn(true).with_ack(true),
+            "".to_string(),
+        );
+        test_2.send_ack(rx_isn + 1, tx_isn + 1, Option::None);
+        test_2.execute(
+            &mut ExpectNoSegment {},
+            "test 2 failed: ACK of our SYN was answered".to_string(),
+        );
+        test_2.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+
+        // data flows both ways
+        test_2.send_data(rx_isn + 1, tx_isn + 1, "hi");
+        test_2.execute(
+            ExpectOneSegment::new()
+                .with_ack(true)
+                .with_ackno(rx_isn + 3),
+            "".to_string(),
+        );
+        test_2.execute(
+            ExpectData::new().with_data("hi".to_string()),
+            "".to_string(),
+        );
+    }
+
+    // test #3: the SYN/ACK is retransmitted until acked
+    {
+        let mut test_3 = TCPTestHarness::in_syn_sent(&cfg, tx_isn);
+
+        test_3.send_syn(rx_isn, Option::None);
+        test_3.execute(
+            ExpectOneSegment::new().with_syn(true).with_ack(true),
+            "".to_string(),
+        );
+        test_3.execute(
+            &mut Tick::new((cfg.rt_timeout - 1) as SizeT),
+            "".to_string(),
+        );
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_3.execute(&mut Tick::new(1), "".to_string());
+        test_3.execute(
+            ExpectOneSegment::new()
+                .with_syn(true)
+                .with_ack(true)
+                .with_seqno(tx_isn)
+                .with_ackno(rx_isn + 1),
+            "test 3 failed: SYN/ACK not retransmitted".to_string(),
+        );
+        test_3.execute(&mut ExpectState::new(State::SynRcvd), "".to_string());
+    }
+
+    // test #4: a SYN/ACK that doesn't ack our SYN is ignored in SYN_SENT
+    {
+        let mut test_4 = TCPTestHarness::in_syn_sent(&cfg, tx_isn);
+
+        test_4.send_syn(rx_isn, Option::Some(tx_isn + 2));
+        test_4.execute(
+            &mut ExpectNoSegment {},
+            "test 4 failed: bad SYN/ACK was answered".to_string(),
+        );
+        test_4.execute(&mut ExpectState::new(State::SynSent), "".to_string());
+    }
+}
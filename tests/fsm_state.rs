@@ -0,0 +1,108 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_state::{State, StateError};
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn t_fsm_state() {
+    let cfg = TCPConfig::default();
+    let tx_isn = WrappingInt32::new(1 << 31);
+    let rx_isn = WrappingInt32::new(5000);
+
+    {
+        assert!(State::LISTEN.can_transition_to(State::SynSent));
+        assert!(State::FinWait1.can_transition_to(State::RESET));
+        assert!(!State::ESTABLISHED.can_transition_to(State::TimeWait));
+        assert!(!State::CLOSED.can_transition_to(State::RESET));
+        assert!(!State::RESET.can_transition_to(State::LISTEN));
+    }
+
+    // segments LISTEN has no use for are dropped and reported
+    {
+        let mut test_1 = TCPTestHarness::in_listen(&cfg);
+        test_1.execute(&mut ExpectState::new(State::LISTEN), "".to_string());
+        test_1.execute(&mut ExpectStateError::new(None), "".to_string());
+        test_1.send_ack(rx_isn, WrappingInt32::new(1), None);
+        test_1.execute(&mut ExpectState::new(State::LISTEN), "".to_string());
+        test_1.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_1.execute(
+            &mut ExpectStateError::new(Some(StateError::UnexpectedSegment(State::LISTEN))),
+            "test 1 failed: dropped segment not reported".to_string(),
+        );
+        test_1.execute(&mut ExpectStateError::new(None), "".to_string());
+    }
+
+    // connecting twice is refused, nothing is sent
+    {
+        let mut test_2 = TCPTestHarness::in_syn_sent(&cfg, tx_isn);
+        test_2.execute(&mut Connect {}, "".to_string());
+        test_2.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_2.execute(
+            &mut ExpectStateError::new(Some(StateError::InvalidTransition(
+                State::SynSent,
+                State::SynSent,
+            ))),
+            "test 2 failed: second connect not refused".to_string(),
+        );
+
+        // an ACK of our SYN without the peer's SYN has no place in SYN_SENT
+        test_2.send_ack(rx_isn, tx_isn + 1, None);
+        test_2.execute(&mut ExpectState::new(State::SynSent), "".to_string());
+        test_2.execute(
+            &mut ExpectStateError::new(Some(StateError::UnexpectedSegment(State::SynSent))),
+            "".to_string(),
+        );
+
+        test_2.send_syn(rx_isn, Some(tx_isn + 1));
+        test_2.execute(&mut ExpectState::new(State::ESTABLISHED), "".to_string());
+    }
+
+    // active close: FIN_WAIT_1, FIN_WAIT_2, TIME_WAIT, then CLOSED after the linger time
+    {
+        let mut test_3 = TCPTestHarness::in_fin_wait_1(&cfg, tx_isn, rx_isn);
+        test_3.execute(&mut ExpectState::new(State::FinWait1), "".to_string());
+        test_3.send_ack(rx_isn + 1, tx_isn + 2, None);
+        test_3.execute(&mut ExpectState::new(State::FinWait2), "".to_string());
+        test_3.send_fin(rx_isn + 1, Some(tx_isn + 2));
+        test_3.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
+        test_3.execute(
+            &mut Tick::new(10 * cfg.rt_timeout as SizeT - 1),
+            "".to_string(),
+        );
+        test_3.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
+        test_3.execute(&mut Tick::new(1), "".to_string());
+        test_3.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
+
+        // nothing is taken once closed
+        test_3.execute(ExpectOneSegment::new().with_ack(true), "".to_string());
+        test_3.send_ack(rx_isn + 2, tx_isn + 2, None);
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_3.execute(
+            &mut ExpectStateError::new(Some(StateError::UnexpectedSegment(State::CLOSED))),
+            "test 3 failed: segment taken once closed".to_string(),
+        );
+    }
+
+    // simultaneous close goes through CLOSING
+    {
+        let mut test_4 = TCPTestHarness::in_fin_wait_1(&cfg, tx_isn, rx_isn);
+        test_4.send_fin(rx_isn + 1, Some(tx_isn + 1));
+        test_4.execute(&mut ExpectState::new(State::CLOSING), "".to_string());
+        test_4.send_ack(rx_isn + 2, tx_isn + 2, None);
+        test_4.execute(&mut ExpectState::new(State::TimeWait), "".to_string());
+    }
+
+    // passive close: CLOSE_WAIT, LAST_ACK, then CLOSED without lingering
+    {
+        let mut test_5 = TCPTestHarness::in_close_wait(&cfg, tx_isn, rx_isn);
+        test_5.execute(&mut ExpectState::new(State::CloseWait), "".to_string());
+        test_5.execute(&mut Close {}, "".to_string());
+        test_5.execute(&mut ExpectState::new(State::LastAck), "".to_string());
+        test_5.send_ack(rx_isn + 2, tx_isn + 2, None);
+        test_5.execute(&mut ExpectState::new(State::CLOSED), "".to_string());
+        test_5.execute(&mut ExpectStateError::new(None), "".to_string());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::tcp_fsm_test_harness::*;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod tcp_fsm_test_harness;
+
+#[test]
+fn t_fsm_sws() {
+    let cfg = TCPConfig {
+        recv_capacity: 4000,
+        sws_avoidance: true,
+        ..Default::default()
+    };
+    let tx_isn = WrappingInt32::new(1 << 31);
+    // full segments of MAX_PAYLOAD_SIZE, as the peer's SYN announces it
+    let mss = Some(TCPConfig::MAX_PAYLOAD_SIZE as u16);
+    let rx_isn = WrappingInt32::new(5000);
+    let x = |len: usize| "x".repeat(len);
+    let ack = |ackno: u32, win: u16| {
+        let mut e = ExpectOneSegment::new();
+        e.with_ackno(rx_isn + 1 + ackno).with_win(win);
+        e
+    };
+
+    // by default the window follows every byte read
+    {
+        let cfg = TCPConfig {
+            recv_capacity: 4000,
+            ..Default::default()
+        };
+        let mut test_1 = TCPTestHarness::in_established_with_mss(&cfg, tx_isn, rx_isn, mss);
+        test_1.send_data(rx_isn + 1, tx_isn + 1, &x(4000));
+        test_1.execute(&mut ack(4000, 0), "".to_string());
+        test_1.execute(&mut Read::new(1), "".to_string());
+        test_1.execute(&mut Tick::new(10), "".to_string());
+        test_1.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_1.send_ack(rx_isn + 4001, tx_isn + 1, None);
+        test_1.send_data(rx_isn + 4000, tx_isn + 1, &x(1));
+        test_1.execute(
+            &mut ack(4000, 1),
+            "test 1 failed: one byte read doesn't open the window by one".to_string(),
+        );
+    }
+
+    // the right edge only moves by a full segment at a time
+    {
+        let mut test_2 = TCPTestHarness::in_established_with_mss(&cfg, tx_isn, rx_isn, mss);
+        test_2.send_data(rx_isn + 1, tx_isn + 1, &x(1000));
+        test_2.execute(&mut ack(1000, 3000), "".to_string());
+        test_2.execute(&mut Read::new(200), "".to_string());
+        test_2.send_data(rx_isn + 1001, tx_isn + 1, &x(1000));
+        test_2.execute(&mut ack(2000, 2000), "".to_string());
+        test_2.execute(&mut Read::new(799), "".to_string());
+        test_2.send_data(rx_isn + 2001, tx_isn + 1, &x(500));
+        test_2.execute(&mut ack(2500, 1500), "".to_string());
+        test_2.execute(&mut Read::new(1), "".to_string());
+        test_2.send_data(rx_isn + 2501, tx_isn + 1, &x(500));
+        test_2.execute(
+            &mut ack(3000, 2000),
+            "test 2 failed: a full segment read doesn't move the right edge".to_string(),
+        );
+    }
+
+    // a closed window is reopened by a window update once it is worth it
+    {
+        let mut test_3 = TCPTestHarness::in_established_with_mss(&cfg, tx_isn, rx_isn, mss);
+        for i in 0..4 {
+            test_3.send_data(rx_isn + 1 + i * 1000, tx_isn + 1, &x(1000));
+            test_3.execute(
+                ExpectSegment::new().with_ackno(rx_isn + 1 + (i + 1) * 1000),
+                "".to_string(),
+            );
+        }
+        test_3.execute(&mut Tick::new(10), "".to_string());
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_3.execute(&mut Read::new(500), "".to_string());
+        test_3.execute(&mut Tick::new(10), "".to_string());
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_3.execute(&mut Read::new(499), "".to_string());
+        test_3.execute(&mut Tick::new(10), "".to_string());
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+        test_3.execute(&mut Read::new(1), "".to_string());
+        test_3.execute(&mut Tick::new(10), "".to_string());
+        test_3.execute(
+            &mut ack(4000, 1000),
+            "test 3 failed: no window update".to_string(),
+        );
+        test_3.execute(&mut Tick::new(10), "".to_string());
+        test_3.execute(&mut ExpectNoSegment {}, "".to_string());
+    }
+}
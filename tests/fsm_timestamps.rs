@@ -0,0 +1,133 @@
+use rand::thread_rng;
+use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::tcp_receiver::TCPReceiver;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+fn segment(seqno: WrappingInt32, data: &str, tsval: u32) -> TCPSegment {
+    let mut header = TCPHeader::new();
+    header.seqno = seqno;
+    header.timestamps = Some((tsval, 0));
+    TCPSegment::new(header, Buffer::new(data.as_bytes().to_vec()))
+}
+
+#[test]
+fn t_fsm_timestamps() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let mut header = TCPHeader::new();
+        header.ack = true;
+        header.timestamps = Some((u32::MAX, 12345));
+        header.sack_blocks = (0..4)
+            .map(|i| (WrappingInt32::new(i * 10), WrappingInt32::new(i * 10 + 5)))
+            .collect();
+        let mut seg = TCPSegment::new(header.clone(), Buffer::new(vec![]));
+        let parsed = TCPSegment::parse_new(Buffer::new(seg.serialize(0)), 0).unwrap();
+        assert_eq!(parsed.header().header_length(), TCPHeader::MAX_LENGTH);
+        assert_eq!(parsed.header().timestamps, Some((u32::MAX, 12345)));
+        assert!(parsed.header().sack_blocks[..] == header.sack_blocks[..3]);
+    }
+
+    // PAWS drops segments older than the last one that advanced the window
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut receiver = TCPReceiver::new(4000);
+        receiver.set_paws(true);
+        let mut syn = segment(isn, "", 100);
+        syn.header_mut().syn = true;
+        receiver.segment_received(&syn);
+        assert_eq!(receiver.ts_recent(), Some(100));
+
+        receiver.segment_received(&segment(isn + 1, "abcd", 200));
+        assert!(!receiver.paws_rejected());
+        assert_eq!(receiver.ts_recent(), Some(200));
+
+        receiver.segment_received(&segment(isn + 5, "efgh", 150));
+        assert!(receiver.paws_rejected());
+        assert_eq!(receiver.stream_out().bytes_written(), 4);
+        assert_eq!(receiver.ts_recent(), Some(200));
+
+        // out of order data doesn't update ts_recent
+        receiver.segment_received(&segment(isn + 9, "ijkl", 300));
+        assert_eq!(receiver.ts_recent(), Some(200));
+        receiver.segment_received(&segment(isn + 5, "efgh", 250));
+        assert!(!receiver.paws_rejected());
+        assert_eq!(receiver.stream_out().bytes_written(), 12);
+        assert_eq!(receiver.ts_recent(), Some(250));
+
+        // timestamps wrap around too
+        receiver.segment_received(&segment(isn + 13, "mn", 0x7000_0000));
+        receiver.segment_received(&segment(isn + 15, "op", 0xe000_0000));
+        receiver.segment_received(&segment(isn + 17, "qr", 10));
+        assert!(!receiver.paws_rejected());
+        assert_eq!(receiver.stream_out().bytes_written(), 18);
+    }
+
+    // off without negotiation
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut receiver = TCPReceiver::new(4000);
+        let mut syn = segment(isn, "", 100);
+        syn.header_mut().syn = true;
+        receiver.segment_received(&syn);
+        receiver.segment_received(&segment(isn + 1, "abcd", 50));
+        assert!(!receiver.paws_rejected());
+        assert_eq!(receiver.stream_out().bytes_written(), 4);
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut cfg = TCPConfig::default();
+        cfg.timestamps = true;
+
+        let mut conn = TCPConnection::new(cfg);
+        let mut syn = segment(isn, "", 0);
+        syn.header_mut().syn = true;
+        syn.header_mut().timestamps = None;
+        conn.segment_received(&syn);
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+        assert!(syn_ack.header().timestamps.is_none());
+
+        let mut conn = TCPConnection::new(cfg);
+        let mut syn = segment(isn, "", 7000);
+        syn.header_mut().syn = true;
+        conn.segment_received(&syn);
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+        let (tsval, tsecr) = syn_ack.header().timestamps.unwrap();
+        assert_eq!(tsecr, 7000);
+
+        // the clock runs in ms
+        conn.tick(30);
+        let mut ack = segment(isn + 1, "abc", 7030);
+        ack.header_mut().ack = true;
+        ack.header_mut().ackno = syn_ack.header().seqno + 1;
+        ack.header_mut().win = 1000;
+        ack.header_mut().timestamps = Some((7030, tsval));
+        conn.segment_received(&ack);
+        let seg = conn.segments_out_mut().pop_front().unwrap();
+        assert_eq!(seg.header().ackno, isn + 4);
+        assert_eq!(
+            seg.header().timestamps,
+            Some((tsval.wrapping_add(30), 7030))
+        );
+
+        // an old duplicate is acked but not delivered, and doesn't show the peer is alive
+        conn.tick(50);
+        let mut old = segment(isn + 4, "def", 6000);
+        old.header_mut().ack = true;
+        old.header_mut().ackno = syn_ack.header().seqno + 1;
+        old.header_mut().win = 1000;
+        conn.segment_received(&old);
+        let seg = conn.segments_out_mut().pop_front().unwrap();
+        assert_eq!(seg.header().ackno, isn + 4);
+        assert!(conn.segments_out_mut().is_empty());
+        assert_eq!(conn.inbound_stream().bytes_written(), 3);
+        assert_eq!(conn.time_since_last_segment_received(), 50);
+    }
+}
@@ -0,0 +1,104 @@
+use rand::thread_rng;
+use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+fn exchange(a: &mut TCPConnection, b: &mut TCPConnection) -> SizeT {
+    let mut n = 0;
+    while let Some(seg) = a.segments_out_mut().pop_front() {
+        let seg = TCPSegment::parse_new(Buffer::new(seg.clone().serialize(0)), 0).unwrap();
+        b.segment_received(&seg);
+        n += 1;
+    }
+    n
+}
+
+#[test]
+fn t_fsm_window_scale() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let mut cfg = TCPConfig::default();
+        assert_eq!(cfg.wscale(), 0);
+        cfg.recv_capacity = 1000000;
+        assert_eq!(cfg.wscale(), 4);
+        cfg.recv_capacity = 1 << 31;
+        assert_eq!(cfg.wscale(), TCPConfig::MAX_WSCALE);
+    }
+
+    // the option is only used when both SYNs carry it, and SYN windows are never scaled
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut cfg = TCPConfig::default();
+        cfg.recv_capacity = 1000000;
+        cfg.window_scale = true;
+
+        let mut conn = TCPConnection::new(cfg);
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.seqno = isn;
+        header.win = 10000;
+        conn.segment_received(&TCPSegment::new(header.clone(), Buffer::new(vec![])));
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+        assert!(syn_ack.header().wscale.is_none());
+        assert_eq!(syn_ack.header().win, u16::MAX);
+
+        let mut conn = TCPConnection::new(cfg);
+        header.wscale = Some(2);
+        conn.segment_received(&TCPSegment::new(header, Buffer::new(vec![])));
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+        assert_eq!(syn_ack.header().wscale, Some(4));
+        assert_eq!(syn_ack.header().win, u16::MAX);
+
+        // the peer's windows are shifted by 2
+        let mut ack = TCPHeader::new();
+        ack.ack = true;
+        ack.seqno = isn + 1;
+        ack.ackno = syn_ack.header().seqno + 1;
+        ack.win = 5000;
+        conn.segment_received(&TCPSegment::new(ack, Buffer::new(vec![])));
+        conn.write(vec![b'a'; 30000].as_slice());
+        assert_eq!(conn.bytes_in_flight(), 20000);
+
+        // ours by 4
+        let seg = conn.segments_out_mut().pop_front().unwrap();
+        assert_eq!(seg.header().win, (1000000 >> 4) as u16);
+    }
+
+    // a transfer larger than 64k in flight
+    {
+        let mut cfg = TCPConfig::default();
+        cfg.recv_capacity = 1000000;
+        cfg.send_capacity = 1000000;
+        cfg.window_scale = true;
+
+        let mut client = TCPConnection::new(cfg);
+        let mut server = TCPConnection::new(cfg);
+        client.connect();
+        exchange(&mut client, &mut server);
+        exchange(&mut server, &mut client);
+        exchange(&mut client, &mut server);
+
+        // the SYN/ACK window is unscaled, the first data ack opens the full window
+        let data: Vec<u8> = (0..500000).map(|_| rd.gen::<u8>()).collect();
+        assert_eq!(client.write(&data[..1000]), 1000);
+        exchange(&mut client, &mut server);
+        exchange(&mut server, &mut client);
+        assert_eq!(client.bytes_in_flight(), 0);
+
+        assert_eq!(client.write(&data[1000..]), data.len() - 1000);
+        assert_eq!(client.bytes_in_flight(), data.len() - 1000);
+        exchange(&mut client, &mut server);
+        exchange(&mut server, &mut client);
+        assert_eq!(client.bytes_in_flight(), 0);
+
+        let received = server.inbound_stream_mut().read(data.len());
+        assert!(received == data);
+    }
+}
@@ -2,7 +2,6 @@ use crate::tcp_fsm_test_harness::*;
 use rand::{thread_rng, Rng};
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
 use rust_sponge::tcp_helpers::tcp_state::State::{ESTABLISHED, LISTEN};
-use rust_sponge::tcp_helpers::tcp_state::TCPState;
 use rust_sponge::wrapping_integers::WrappingInt32;
 use rust_sponge::SizeT;
 
@@ -17,6 +16,7 @@ fn fsm_winsize() {
     const MAX_SWIN_MUL: u32 = 6;
 
     let mut cfg = TCPConfig {
+        nodelay: true,
         ..Default::default()
     };
     cfg.send_capacity = (MAX_SWIN * MAX_SWIN_MUL) as SizeT;
@@ -53,10 +53,7 @@ fn fsm_winsize() {
             &mut ExpectNoSegment {},
             "test 1 failed: ACK after acceptable ACK".to_string(),
         );
-        test_1.execute(
-            &mut ExpectState::new(TCPState::from(ESTABLISHED)),
-            "".to_string(),
-        );
+        test_1.execute(&mut ExpectState::new(ESTABLISHED), "".to_string());
 
         // write swin_mul * swin, make sure swin gets sent
         let swin_mul: u32 =
@@ -0,0 +1,44 @@
+use rust_sponge::tcp_helpers::fd_adapter::TCPOverUDPSocketAdapter;
+use rust_sponge::tcp_helpers::syn_cookie::SynCookieMode;
+use rust_sponge::tcp_helpers::tcp_config::{FdAdapterConfig, TCPConfig};
+use rust_sponge::tcp_helpers::tcp_listener::TCPListener;
+use rust_sponge::util::socket::{AsSocket, UDPSocket};
+use std::net::SocketAddrV4;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub fn adapter_config(source: SocketAddrV4, destination: SocketAddrV4) -> FdAdapterConfig {
+    FdAdapterConfig {
+        source,
+        destination,
+        loss_rate_dn: 0,
+        loss_rate_up: 0,
+    }
+}
+
+// a listener on an ephemeral loopback port
+pub fn listener(
+    cfg: &TCPConfig,
+    backlog: usize,
+    mode: SynCookieMode,
+) -> (TCPListener<TCPOverUDPSocketAdapter>, SocketAddrV4) {
+    let sock = UDPSocket::new();
+    sock.bind("127.0.0.1", 0);
+    let addr = SocketAddrV4::from(sock.as_socket().local_address());
+    let mut l = TCPListener::new(TCPOverUDPSocketAdapter::new(sock));
+    l.set_syn_cookies(mode);
+    l.listen(cfg, adapter_config(addr, addr), backlog);
+    (l, addr)
+}
+
+pub fn wait_for(what: &str, cond: impl Fn() -> bool) {
+    let start = Instant::now();
+    while !cond() {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "timed out waiting for {}",
+            what
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
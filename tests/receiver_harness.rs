@@ -143,6 +143,45 @@ impl ExpectUnassembledBytes {
     }
 }
 
+pub struct ExpectSackBlocks {
+    blocks: Vec<(WrappingInt32, WrappingInt32)>,
+}
+impl ReceiverTestStep for ExpectSackBlocks {
+    fn execute(&self, receiver: &mut TCPReceiver) {
+        println!("  step: {}", ReceiverExpectation::to_string(self));
+
+        let b = receiver.sack_blocks() == self.blocks;
+        assert!(
+            b,
+            "The TCPReceiver reported SACK blocks `{}`, but `{}` was expected",
+            ExpectSackBlocks::format_blocks(&receiver.sack_blocks()),
+            ExpectSackBlocks::format_blocks(&self.blocks)
+        );
+    }
+}
+impl ReceiverExpectation for ExpectSackBlocks {
+    fn description(&self) -> String {
+        format!(
+            "SACK blocks {}",
+            ExpectSackBlocks::format_blocks(&self.blocks)
+        )
+    }
+}
+impl ExpectSackBlocks {
+    #[allow(dead_code)]
+    pub fn new(blocks: Vec<(WrappingInt32, WrappingInt32)>) -> ExpectSackBlocks {
+        ExpectSackBlocks { blocks }
+    }
+
+    fn format_blocks(blocks: &[(WrappingInt32, WrappingInt32)]) -> String {
+        blocks
+            .iter()
+            .map(|(l, r)| format!("[{}, {})", l.raw_value(), r.raw_value()))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
 pub struct ExpectTotalAssembledBytes {
     n_bytes: SizeT,
 }
@@ -0,0 +1,155 @@
+use rust_sponge::byte_stream::ByteStream;
+use rust_sponge::stream_reassembler::StreamReassembler;
+use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+const PEER_ISN: u32 = 5000;
+
+// a peer that sends a full window, or `per_round` bytes, every `rtt` ms, all of which the
+// app reads right away. returns the receive capacity after each round
+fn bulk_transfer(
+    cfg: TCPConfig,
+    rounds: usize,
+    rtt: SizeT,
+    per_round: Option<SizeT>,
+) -> Vec<SizeT> {
+    let mut conn = TCPConnection::new(cfg);
+    let mut header = TCPHeader::new();
+    header.syn = true;
+    header.seqno = WrappingInt32::new(PEER_ISN);
+    if cfg.timestamps {
+        header.timestamps = Some((1, 0));
+    }
+    conn.segment_received(&TCPSegment::new(header, Buffer::new(vec![])));
+    let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+    let ackno = syn_ack.header().seqno + 1;
+    let mut window = syn_ack.header().win as SizeT;
+    // our TSval the peer echoes, as of one rtt ago
+    let mut tsecr = syn_ack.header().timestamps.map_or(0, |(tsval, _)| tsval);
+
+    let mut offset: SizeT = 0;
+    let mut capacities = vec![];
+    for _ in 0..rounds {
+        let mut budget = per_round.map_or(window, |n| n.min(window));
+        while budget > 0 {
+            let len = budget.min(1000);
+            let mut header = TCPHeader::new();
+            header.ack = true;
+            header.seqno = WrappingInt32::new(PEER_ISN + 1 + offset as u32);
+            header.ackno = ackno;
+            header.win = 60000;
+            if cfg.timestamps {
+                header.timestamps = Some((1, tsecr));
+            }
+            conn.segment_received(&TCPSegment::new(header, Buffer::new(vec![b'x'; len])));
+            offset += len;
+            budget -= len;
+            let size = conn.inbound_stream().buffer_size();
+            conn.inbound_stream_mut().read(size);
+        }
+        for seg in conn.segments_out_mut().drain(..) {
+            window = seg.header().win as SizeT;
+            tsecr = seg.header().timestamps.map_or(0, |(tsval, _)| tsval);
+        }
+        conn.tick(rtt);
+        capacities.push(conn.recv_capacity());
+    }
+    assert_eq!(conn.inbound_stream().bytes_written(), offset);
+    capacities
+}
+
+#[test]
+fn t_recv_autotune() {
+    {
+        // growing keeps what is buffered, across the wrap of the ring buffer
+        let mut bs = ByteStream::new(8);
+        bs.write(b"abcdef");
+        assert_eq!(bs.read(4), b"abcd".to_vec());
+        bs.write(b"ghijk");
+        bs.grow(16);
+        assert_eq!(bs.capacity(), 16);
+        assert_eq!(bs.remaining_capacity(), 9);
+        bs.write(b"lmnopqrst");
+        assert_eq!(bs.remaining_capacity(), 0);
+        assert_eq!(bs.read(16), b"efghijklmnopqrst".to_vec());
+        bs.grow(4);
+        assert_eq!(bs.capacity(), 16);
+    }
+
+    {
+        // as are the unassembled bytes
+        let mut sr = StreamReassembler::new(8);
+        sr.push_substring(b"abcdef", 0, false);
+        assert_eq!(sr.stream_out_mut().read(6), b"abcdef".to_vec());
+        sr.push_substring(b"jkl", 9, false);
+        assert_eq!(sr.unassembled_bytes(), 3);
+        sr.grow(32);
+        assert_eq!(sr.unassembled_bytes(), 3);
+        sr.push_substring(b"ghi", 6, false);
+        sr.push_substring(b"mnopqrstuvwxyz", 12, true);
+        assert_eq!(sr.unassembled_bytes(), 0);
+        assert_eq!(
+            sr.stream_out_mut().read(32),
+            b"ghijklmnopqrstuvwxyz".to_vec()
+        );
+        assert!(sr.stream_out().eof());
+    }
+
+    let cfg = TCPConfig {
+        recv_capacity: 4000,
+        recv_capacity_max: Some(64000),
+        ..Default::default()
+    };
+
+    {
+        // off by default
+        let capacities = bulk_transfer(
+            TCPConfig {
+                recv_capacity: 4000,
+                ..Default::default()
+            },
+            5,
+            100,
+            None,
+        );
+        assert!(capacities.iter().all(|c| *c == 4000));
+    }
+
+    {
+        // a window-limited transfer doubles its window each rtt up to the maximum
+        let capacities = bulk_transfer(cfg, 10, 100, None);
+        assert!(capacities.windows(2).all(|w| w[0] <= w[1]));
+        assert!(capacities[1] > 4000);
+        assert_eq!(*capacities.last().unwrap(), 64000);
+    }
+
+    {
+        // with timestamps the rtt is measured directly
+        let cfg = TCPConfig {
+            timestamps: true,
+            ..cfg
+        };
+        let capacities = bulk_transfer(cfg, 10, 100, None);
+        assert!(capacities[1] > 4000);
+        assert_eq!(*capacities.last().unwrap(), 64000);
+
+        // so a peer sending less than a window per rtt doesn't get a bigger buffer
+        let capacities = bulk_transfer(cfg, 10, 100, Some(1000));
+        assert!(capacities.iter().all(|c| *c == 4000));
+    }
+
+    {
+        // the window scale leaves room for the largest buffer
+        assert_eq!(cfg.wscale(), 0);
+        let cfg = TCPConfig {
+            recv_capacity_max: Some(1 << 20),
+            ..cfg
+        };
+        assert_eq!(cfg.wscale(), 5);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::receiver_harness::{
+    ExpectAckno, ExpectSackBlocks, ExpectUnassembledBytes, SegmentArrives, TCPReceiverTestHarness,
+};
+use rand::thread_rng;
+use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod receiver_harness;
+
+#[test]
+fn t_recv_sack() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    // blocks for out-of-order data, most recently received first
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut test = TCPReceiverTestHarness::new(4000);
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_syn()
+                .with_seqno(isn),
+        );
+        test.execute(&ExpectSackBlocks::new(vec![]));
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 11)
+                .with_data("abcd".to_string()),
+        );
+        test.execute(&ExpectSackBlocks::new(vec![(isn + 11, isn + 15)]));
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 31)
+                .with_data("efgh".to_string()),
+        );
+        test.execute(&ExpectSackBlocks::new(vec![
+            (isn + 31, isn + 35),
+            (isn + 11, isn + 15),
+        ]));
+        // extends the first block, which is reported first again
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 15)
+                .with_data("ij".to_string()),
+        );
+        test.execute(&ExpectSackBlocks::new(vec![
+            (isn + 11, isn + 17),
+            (isn + 31, isn + 35),
+        ]));
+        test.execute(&ExpectUnassembledBytes::new(10));
+
+        // the hole is filled: only the block beyond the new ackno is left
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 1)
+                .with_data("0123456789".to_string()),
+        );
+        test.execute(&ExpectAckno::new(Some(isn + 17)));
+        test.execute(&ExpectSackBlocks::new(vec![(isn + 31, isn + 35)]));
+    }
+
+    // no more than four blocks
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut test = TCPReceiverTestHarness::new(4000);
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_syn()
+                .with_seqno(isn),
+        );
+        for i in 0..6 {
+            test.execute(
+                SegmentArrives::new("".to_string())
+                    .with_seqno(isn + 11 + i * 10)
+                    .with_data("ab".to_string()),
+            );
+        }
+        test.execute(&ExpectSackBlocks::new(vec![
+            (isn + 61, isn + 63),
+            (isn + 51, isn + 53),
+            (isn + 41, isn + 43),
+            (isn + 31, isn + 33),
+        ]));
+    }
+
+    // D-SACK: duplicates are reported in the first block
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut test = TCPReceiverTestHarness::new(4000);
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_syn()
+                .with_seqno(isn),
+        );
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 1)
+                .with_data("abcd".to_string()),
+        );
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 1)
+                .with_data("abcd".to_string()),
+        );
+        test.execute(&ExpectSackBlocks::new(vec![(isn + 1, isn + 5)]));
+
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 21)
+                .with_data("efgh".to_string()),
+        );
+        test.execute(&ExpectSackBlocks::new(vec![(isn + 21, isn + 25)]));
+        test.execute(
+            SegmentArrives::new("".to_string())
+                .with_seqno(isn + 21)
+                .with_data("efgh".to_string()),
+        );
+        test.execute(&ExpectSackBlocks::new(vec![
+            (isn + 21, isn + 25),
+            (isn + 21, isn + 25),
+        ]));
+    }
+
+    // the connection puts the blocks on its acks once SACK is negotiated
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut cfg = TCPConfig::default();
+        cfg.sack = true;
+        let mut conn = TCPConnection::new(cfg);
+
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.sack_permitted = true;
+        header.seqno = isn;
+        conn.segment_received(&TCPSegment::new(header, Buffer::new(vec![])));
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+        assert!(syn_ack.header().sack_blocks.is_empty());
+
+        let mut header = TCPHeader::new();
+        header.ack = true;
+        header.ackno = syn_ack.header().seqno + 1;
+        header.win = 1000;
+        header.seqno = isn + 11;
+        conn.segment_received(&TCPSegment::new(header, Buffer::new(b"abcd".to_vec())));
+        let ack = conn.segments_out_mut().pop_front().unwrap();
+        assert!(ack.header().ackno == isn + 1);
+        assert!(ack.header().sack_blocks == vec![(isn + 11, isn + 15)]);
+    }
+}
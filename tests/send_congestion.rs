@@ -0,0 +1,133 @@
+use crate::sender_harness::{
+    AckReceived, ExpectBytesInFlight, ExpectCongestionWindow, ExpectNoSegment, ExpectSegment,
+    TCPSenderTestHarness, Tick, WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::congestion_control::controller::CongestionControlAlgorithm;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+mod sender_harness;
+
+#[test]
+fn t_send_congestion() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.congestion_control = CongestionControlAlgorithm::Reno;
+
+        let mut test =
+            TCPSenderTestHarness::new("Reno slow start limits the initial burst".to_string(), &cfg);
+        test.execute(
+            ExpectSegment::new()
+                .with_no_flags()
+                .with_syn(true)
+                .with_payload_size(0)
+                .with_seqno(isn),
+        );
+        test.execute(AckReceived::new(isn + 1).with_win(60000));
+        test.execute(&ExpectCongestionWindow::new(4000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 10000]).unwrap(),
+        ));
+        for i in 0..4 {
+            test.execute(
+                ExpectSegment::new()
+                    .with_no_flags()
+                    .with_payload_size(1000)
+                    .with_seqno(isn + 1 + i * 1000),
+            );
+        }
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectBytesInFlight::new(4000));
+
+        // each ack grows the window by at most one segment
+        test.execute(AckReceived::new(isn + 1 + 1000).with_win(60000));
+        test.execute(&ExpectCongestionWindow::new(5000));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 4001),
+        );
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 5001),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectBytesInFlight::new(5000));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let retx_timeout: u16 = rd.gen_range(10..10000);
+        cfg.fixed_isn = Option::from(isn);
+        cfg.rt_timeout = retx_timeout;
+        cfg.congestion_control = CongestionControlAlgorithm::Reno;
+
+        let mut test =
+            TCPSenderTestHarness::new("Reno collapses to one segment on timeout".to_string(), &cfg);
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(60000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 10000]).unwrap(),
+        ));
+        for i in 0..4 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        test.execute(&ExpectNoSegment {});
+
+        test.execute(&Tick::new(retx_timeout as SizeT));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectCongestionWindow::new(1000));
+        test.execute(&ExpectNoSegment {});
+
+        // everything acked: slow start again from one segment
+        test.execute(AckReceived::new(isn + 4001).with_win(60000));
+        test.execute(&ExpectCongestionWindow::new(2000));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 4001),
+        );
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 5001),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectBytesInFlight::new(2000));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+
+        let mut test = TCPSenderTestHarness::new(
+            "Without congestion control the whole window is used".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 10000]).unwrap(),
+        ));
+        for i in 0..10 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectBytesInFlight::new(10000));
+    }
+}
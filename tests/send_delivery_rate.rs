@@ -0,0 +1,101 @@
+use crate::sender_harness::{
+    AckReceived, ExpectNoSegment, ExpectRateSample, ExpectSegment, TCPSenderTestHarness, Tick,
+    WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod sender_harness;
+
+#[test]
+fn t_send_delivery_rate() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+
+        let mut test = TCPSenderTestHarness::new("Rate sample per ack".to_string(), &cfg);
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(10));
+        test.execute(AckReceived::new(isn + 1).with_win(3000));
+        test.execute(&ExpectRateSample::new(1, 10, Some(10)));
+
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 3000]).unwrap(),
+        ));
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        test.execute(ExpectSegment::new().with_seqno(isn + 1001));
+        test.execute(ExpectSegment::new().with_seqno(isn + 2001));
+        test.execute(&ExpectNoSegment {});
+
+        test.execute(&Tick::new(20));
+        test.execute(AckReceived::new(isn + 1001).with_win(3000));
+        test.execute(&ExpectRateSample::new(1000, 20, Some(20)));
+
+        // the remaining two segments acked together, 5ms later
+        test.execute(&Tick::new(5));
+        test.execute(AckReceived::new(isn + 3001).with_win(3000));
+        test.execute(&ExpectRateSample::new(3000, 25, Some(25)));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.rt_timeout = 100;
+
+        let mut test = TCPSenderTestHarness::new(
+            "Retransmitted segments give no rtt sample".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(3000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 1000]).unwrap(),
+        ));
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        test.execute(&Tick::new(100));
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        test.execute(&Tick::new(30));
+        test.execute(AckReceived::new(isn + 1001).with_win(3000));
+        // the sample still spans the whole time the data was outstanding
+        test.execute(&ExpectRateSample::new(1000, 130, None));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.fast_retransmit = true;
+        cfg.nodelay = true;
+
+        let mut test = TCPSenderTestHarness::new(
+            "A retransmitted hole is the most recently sent segment of the ack".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(10));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 4000]).unwrap(),
+        ));
+        for i in 0..4 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        test.execute(&Tick::new(10));
+        test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        for _ in 0..3 {
+            test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        }
+        test.execute(ExpectSegment::new().with_seqno(isn + 1001));
+        test.execute(&Tick::new(5));
+        test.execute(AckReceived::new(isn + 4001).with_win(10000));
+        // delivered since the retransmission, not since the originals were sent
+        test.execute(&ExpectRateSample::new(3000, 10, None));
+    }
+}
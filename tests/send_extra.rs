@@ -19,6 +19,7 @@ fn t_send_extra() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
         let rto: SizeT = rd.gen_range(30..10000);
@@ -66,6 +67,7 @@ fn t_send_extra() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
         let rto: SizeT = rd.gen_range(30..10000);
@@ -113,6 +115,7 @@ fn t_send_extra() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
         let rto: SizeT = rd.gen_range(30..10000);
@@ -160,6 +163,7 @@ fn t_send_extra() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
         let rto: SizeT = rd.gen_range(30..10000);
@@ -218,6 +222,7 @@ fn t_send_extra() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
         let rto: SizeT = rd.gen_range(30..10000);
@@ -0,0 +1,162 @@
+use crate::sender_harness::{
+    AckReceived, ExpectCongestionWindow, ExpectFastRecovery, ExpectNoSegment, ExpectSegment,
+    TCPSenderTestHarness, Tick, WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::congestion_control::controller::CongestionControlAlgorithm;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+mod sender_harness;
+
+#[test]
+fn t_send_fast_retx() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.fast_retransmit = true;
+
+        let mut test = TCPSenderTestHarness::new(
+            "Third duplicate ack triggers retransmission, partial ack another".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 5000]).unwrap(),
+        ));
+        for i in 0..5 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        test.execute(&ExpectFastRecovery::new(0, 0, false));
+
+        test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(2, 0, false));
+        test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1001),
+        );
+        test.execute(&ExpectFastRecovery::new(3, 1, true));
+        test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        test.execute(&ExpectNoSegment {});
+
+        test.execute(AckReceived::new(isn + 2001).with_win(10000));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 2001),
+        );
+        test.execute(&ExpectFastRecovery::new(0, 1, true));
+
+        test.execute(AckReceived::new(isn + 5001).with_win(10000));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(0, 1, false));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.fast_retransmit = true;
+        cfg.congestion_control = CongestionControlAlgorithm::Reno;
+
+        let mut test = TCPSenderTestHarness::new(
+            "Fast retransmit halves the congestion window".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 4000]).unwrap(),
+        ));
+        for i in 0..4 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        for _ in 0..3 {
+            test.execute(AckReceived::new(isn + 1).with_win(10000));
+        }
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        test.execute(&ExpectCongestionWindow::new(2000));
+        test.execute(&ExpectNoSegment {});
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let retx_timeout: u16 = rd.gen_range(10..10000);
+        cfg.fixed_isn = Option::from(isn);
+        cfg.rt_timeout = retx_timeout;
+        cfg.fast_retransmit = true;
+
+        let mut test = TCPSenderTestHarness::new(
+            "No fast retransmit for duplicates of data already timed out".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 3000]).unwrap(),
+        ));
+        for i in 0..3 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        test.execute(&Tick::new(retx_timeout as SizeT));
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        for _ in 0..3 {
+            test.execute(AckReceived::new(isn + 1).with_win(10000));
+        }
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(3, 0, false));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.fast_retransmit = true;
+        cfg.congestion_control = CongestionControlAlgorithm::Reno;
+
+        let mut test = TCPSenderTestHarness::new(
+            "New data goes out during recovery as dup acks come in".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(60000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 10000]).unwrap(),
+        ));
+        for i in 0..4 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        test.execute(&ExpectNoSegment {});
+        for _ in 0..3 {
+            test.execute(AckReceived::new(isn + 1).with_win(60000));
+        }
+        // cwnd is down to 2000, with three of the four segments gone from the network
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        test.execute(&ExpectCongestionWindow::new(2000));
+        test.execute(ExpectSegment::new().with_seqno(isn + 4001));
+        test.execute(&ExpectNoSegment {});
+        test.execute(AckReceived::new(isn + 1).with_win(60000));
+        test.execute(ExpectSegment::new().with_seqno(isn + 5001));
+        test.execute(&ExpectNoSegment {});
+
+        // the full ack ends recovery, and cwnd is back to measuring what is in flight
+        test.execute(AckReceived::new(isn + 6001).with_win(60000));
+        test.execute(&ExpectFastRecovery::new(0, 1, false));
+        test.execute(ExpectSegment::new().with_seqno(isn + 6001));
+        test.execute(ExpectSegment::new().with_seqno(isn + 7001));
+        test.execute(&ExpectNoSegment {});
+    }
+}
@@ -0,0 +1,183 @@
+use crate::sender_harness::{
+    AckReceived, ExpectNoSegment, ExpectSegment, TCPSenderTestHarness, Tick, WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::congestion_control::controller::CongestionControlAlgorithm;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_sender::TCPSender;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod sender_harness;
+
+#[test]
+fn t_send_pacing() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            ..Default::default()
+        };
+        let mut test =
+            TCPSenderTestHarness::new("Unpaced segments go out at once".to_string(), &cfg);
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(60000));
+        test.execute(&WriteBytes::new("a".repeat(3000)));
+        for i in 0..3 {
+            test.execute(
+                ExpectSegment::new()
+                    .with_payload_size(1000)
+                    .with_seqno(isn + 1 + i * 1000),
+            );
+        }
+        test.execute(&ExpectNoSegment {});
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            pacing: true,
+            pacing_rate_max: Some(10.0),
+            ..Default::default()
+        };
+        let mut test = TCPSenderTestHarness::new(
+            "Segments are spread out at the configured rate".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(60000));
+        test.execute(&WriteBytes::new("a".repeat(3000)));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(99));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1001),
+        );
+        test.execute(&Tick::new(99));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 2001),
+        );
+        test.execute(&ExpectNoSegment {});
+
+        // an idle sender doesn't save up for a burst
+        test.execute(AckReceived::new(isn + 3001).with_win(60000));
+        test.execute(&Tick::new(500));
+        test.execute(&WriteBytes::new("b".repeat(2000)));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 3001),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(100));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 4001),
+        );
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            pacing: true,
+            pacing_rate_max: Some(2500.0),
+            ..Default::default()
+        };
+        let mut test = TCPSenderTestHarness::new(
+            "A tick coarser than the pacing interval releases several segments".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(60000));
+        test.execute(&WriteBytes::new("a".repeat(10000)));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(2));
+        for i in 1..6 {
+            test.execute(
+                ExpectSegment::new()
+                    .with_payload_size(1000)
+                    .with_seqno(isn + 1 + i * 1000),
+            );
+        }
+        test.execute(&ExpectNoSegment {});
+    }
+
+    {
+        // without a rate from the controller it follows cwnd / srtt
+        let isn = WrappingInt32::new(0);
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            congestion_control: CongestionControlAlgorithm::Reno,
+            pacing: true,
+            ..Default::default()
+        };
+        let mut sender = TCPSender::from_config(&cfg);
+        sender.fill_window();
+        assert_eq!(sender.pacing_rate(), None);
+        sender.tick(100);
+        sender.ack_received(isn + 1, 60000);
+        let cwnd = sender.congestion_control().unwrap().cwnd() as f64;
+        assert_eq!(
+            sender.pacing_rate(),
+            Some(TCPSender::PACING_GAIN * cwnd / 100.0)
+        );
+
+        sender.set_pacing(false);
+        assert_eq!(sender.pacing_rate(), None);
+
+        let mut sender = TCPSender::from_config(&TCPConfig {
+            pacing_rate_max: Some(1.0),
+            ..cfg
+        });
+        sender.fill_window();
+        sender.tick(100);
+        sender.ack_received(isn + 1, 60000);
+        assert_eq!(sender.pacing_rate(), Some(1.0));
+    }
+
+    {
+        // a controller with its own rate paces without the pacing option
+        let isn = WrappingInt32::new(0);
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            congestion_control: CongestionControlAlgorithm::Bbr,
+            ..Default::default()
+        };
+        let mut sender = TCPSender::from_config(&cfg);
+        sender.fill_window();
+        assert_eq!(sender.pacing_rate(), None);
+        sender.tick(100);
+        sender.ack_received(isn + 1, 60000);
+        let rate = sender.congestion_control().unwrap().pacing_rate();
+        assert!(rate.is_some());
+        assert_eq!(sender.pacing_rate(), rate);
+
+        sender.segments_out_mut().clear();
+        sender.stream_in_mut().write("a".repeat(3000).as_bytes());
+        sender.fill_window();
+        assert_eq!(sender.segments_out().len(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::sender_harness::{
+    AckReceived, Close, ExpectBytesInFlight, ExpectNoSegment, ExpectSegment, ExpectWindowProbes,
+    TCPSenderTestHarness, Tick, WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::SizeT;
+
+mod sender_harness;
+
+#[test]
+fn t_send_persist() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    let rto: SizeT = 1000;
+    let cfg_for = |isn: WrappingInt32| TCPConfig {
+        fixed_isn: Some(isn),
+        rt_timeout: rto as u16,
+        rto_max: 8000,
+        persist: true,
+        ..Default::default()
+    };
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut test = TCPSenderTestHarness::new(
+            "A zero window is probed one byte at a time with backoff, not as retransmissions"
+                .to_string(),
+            &cfg_for(isn),
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&WriteBytes::new("abc".to_string()));
+        test.execute(AckReceived::new(isn + 1).with_win(0));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectWindowProbes::new(0, Some(rto as u32)));
+
+        test.execute(&Tick::new(rto - 1));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_no_flags()
+                .with_data("a".to_string())
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectNoSegment {});
+
+        let mut timeout = 2 * rto;
+        for probes in 2..12 {
+            test.execute(&Tick::new(timeout - 1));
+            test.execute(&ExpectNoSegment {});
+            test.execute(Tick::new(1).with_max_retx_exceeded(false));
+            test.execute(
+                ExpectSegment::new()
+                    .with_data("a".to_string())
+                    .with_seqno(isn + 1),
+            );
+            timeout = (timeout * 2).min(8000);
+            test.execute(&ExpectWindowProbes::new(probes, Some(timeout as u32)));
+        }
+
+        // the window reopens: the rest goes out and the retransmission timer is back
+        test.execute(AckReceived::new(isn + 2).with_win(1000));
+        test.execute(&ExpectWindowProbes::new(11, None));
+        test.execute(
+            ExpectSegment::new()
+                .with_data("bc".to_string())
+                .with_seqno(isn + 2),
+        );
+        test.execute(&Tick::new(rto - 1));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_data("bc".to_string())
+                .with_seqno(isn + 2),
+        );
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut test = TCPSenderTestHarness::new(
+            "The first byte outstanding when the window closes is what probes it".to_string(),
+            &cfg_for(isn),
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(1000));
+        test.execute(&WriteBytes::new("abc".to_string()));
+        test.execute(
+            ExpectSegment::new()
+                .with_data("abc".to_string())
+                .with_seqno(isn + 1),
+        );
+        test.execute(AckReceived::new(isn + 1).with_win(0));
+        test.execute(&WriteBytes::new("def".to_string()));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(rto));
+        test.execute(
+            ExpectSegment::new()
+                .with_data("a".to_string())
+                .with_seqno(isn + 1),
+        );
+        test.execute(&Tick::new(2 * rto));
+        test.execute(
+            ExpectSegment::new()
+                .with_data("a".to_string())
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectWindowProbes::new(2, Some(4 * rto as u32)));
+
+        // acked but still closed: new data goes out as a probe
+        test.execute(AckReceived::new(isn + 4).with_win(0));
+        test.execute(&ExpectBytesInFlight::new(0));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(4 * rto));
+        test.execute(
+            ExpectSegment::new()
+                .with_data("d".to_string())
+                .with_seqno(isn + 4),
+        );
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let mut test = TCPSenderTestHarness::new(
+            "A pending FIN probes the zero window, nothing to send means no probe".to_string(),
+            &cfg_for(isn),
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(0));
+        test.execute(&Tick::new(10 * rto));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectWindowProbes::new(0, Some(rto as u32)));
+
+        test.execute(&Close {});
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(rto));
+        test.execute(
+            ExpectSegment::new()
+                .with_fin(true)
+                .with_payload_size(0)
+                .with_seqno(isn + 1),
+        );
+        test.execute(AckReceived::new(isn + 2).with_win(0));
+        test.execute(&Tick::new(10 * rto));
+        test.execute(&ExpectNoSegment {});
+    }
+}
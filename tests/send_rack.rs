@@ -0,0 +1,240 @@
+use crate::sender_harness::{
+    AckReceived, ExpectFastRecovery, ExpectNoSegment, ExpectSegment, ExpectTailLossProbes,
+    TCPSenderTestHarness, Tick, WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_sender::TCPSender;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod sender_harness;
+
+#[test]
+fn t_send_rack() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            rack: true,
+            ..Default::default()
+        };
+        let mut test = TCPSenderTestHarness::new(
+            "A lost tail is probed after 2*srtt plus the delayed ack".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(100));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new("abc".to_string()));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(3)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&Tick::new(399));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(3)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectTailLossProbes::new(1));
+        // one probe per episode, then it's up to the RTO
+        test.execute(&Tick::new(900));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(100));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(3)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectTailLossProbes::new(1));
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            ..Default::default()
+        };
+        let mut test =
+            TCPSenderTestHarness::new("Without RACK the tail waits for the RTO".to_string(), &cfg);
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(100));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new("abc".to_string()));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(3)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&Tick::new(999));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(3)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectTailLossProbes::new(0));
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            rack: true,
+            nodelay: false,
+            ..Default::default()
+        };
+        let mut test = TCPSenderTestHarness::new(
+            "The probe carries new data when there is some".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(100));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new("a".repeat(1010)));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(400));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(10)
+                .with_seqno(isn + 1001),
+        );
+        test.execute(&ExpectTailLossProbes::new(1));
+        test.execute(AckReceived::new(isn + 1011).with_win(10000));
+        test.execute(&ExpectNoSegment {});
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            rack: true,
+            sack: true,
+            ..Default::default()
+        };
+        let mut test = TCPSenderTestHarness::new(
+            "A hole is lost once a later segment was delivered a reordering window ago".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(100));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new("a".repeat(4000)));
+        for i in 0..4 {
+            test.execute(
+                ExpectSegment::new()
+                    .with_payload_size(1000)
+                    .with_seqno(isn + 1 + i * 1000),
+            );
+        }
+        test.execute(&Tick::new(100));
+        test.execute(
+            AckReceived::new(isn + 1)
+                .with_win(10000)
+                .with_sack(isn + 1001, isn + 2001),
+        );
+        // no dup ack threshold, but min_rtt / 4 to allow for reordering
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(0, 0, false));
+        test.execute(&Tick::new(24));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectFastRecovery::new(0, 1, true));
+        test.execute(&ExpectNoSegment {});
+        test.execute(AckReceived::new(isn + 4001).with_win(10000));
+        test.execute(&ExpectFastRecovery::new(0, 1, false));
+        test.execute(&Tick::new(1000));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectTailLossProbes::new(0));
+    }
+
+    {
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            rack: true,
+            fast_retransmit: true,
+            ..Default::default()
+        };
+        let mut test = TCPSenderTestHarness::new(
+            "Without SACK dup acks and partial acks still drive the recovery".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(100));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new("a".repeat(4000)));
+        for i in 0..4 {
+            test.execute(
+                ExpectSegment::new()
+                    .with_payload_size(1000)
+                    .with_seqno(isn + 1 + i * 1000),
+            );
+        }
+        for _ in 0..3 {
+            test.execute(AckReceived::new(isn + 1).with_win(10000));
+        }
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectFastRecovery::new(3, 1, true));
+        test.execute(AckReceived::new(isn + 1001).with_win(10000));
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1001),
+        );
+        test.execute(&ExpectFastRecovery::new(0, 1, true));
+        test.execute(AckReceived::new(isn + 4001).with_win(10000));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(0, 1, false));
+    }
+
+    {
+        // a segment delivered after a later one is reordering, not loss
+        let isn = WrappingInt32::new(0);
+        let cfg = TCPConfig {
+            fixed_isn: Some(isn),
+            rack: true,
+            sack: true,
+            ..Default::default()
+        };
+        let mut sender = TCPSender::from_config(&cfg);
+        sender.fill_window();
+        sender.tick(100);
+        sender.ack_received(isn + 1, 10000);
+        sender.stream_in_mut().write("a".repeat(2000).as_bytes());
+        sender.fill_window();
+        sender.segments_out_mut().clear();
+        sender.tick(10);
+        sender.sack_received(&[(isn + 1001, isn + 2001)]);
+        sender.ack_received(isn + 1, 10000);
+        assert!(!sender.reordering_seen());
+        sender.tick(1);
+        sender.ack_received(isn + 2001, 10000);
+        assert!(sender.reordering_seen());
+        sender.tick(10);
+        assert!(sender.segments_out().is_empty());
+        assert_eq!(sender.fast_retransmits(), 0);
+    }
+}
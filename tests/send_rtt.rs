@@ -0,0 +1,132 @@
+use crate::sender_harness::{
+    AckReceived, ExpectNoSegment, ExpectRtt, ExpectSegment, TCPSenderTestHarness, Tick, WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod sender_harness;
+
+#[test]
+fn t_send_rtt() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.adaptive_rto = true;
+        cfg.rto_min = 1;
+
+        let mut test = TCPSenderTestHarness::new(
+            "RTO follows the measured rtt, Karn's rule on retransmission".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&ExpectRtt::new(None, None, 1000));
+        test.execute(&Tick::new(80));
+        test.execute(AckReceived::new(isn + 1).with_win(1000));
+        test.execute(&ExpectRtt::new(Some(80), Some(40), 240));
+
+        test.execute(&WriteBytes::new("abc".to_string()));
+        test.execute(
+            ExpectSegment::new()
+                .with_seqno(isn + 1)
+                .with_data("abc".to_string()),
+        );
+        test.execute(&Tick::new(120));
+        test.execute(AckReceived::new(isn + 4).with_win(1000));
+        test.execute(&ExpectRtt::new(Some(85), Some(40), 245));
+
+        test.execute(&WriteBytes::new("def".to_string()));
+        test.execute(
+            ExpectSegment::new()
+                .with_seqno(isn + 4)
+                .with_data("def".to_string()),
+        );
+        test.execute(&Tick::new(244));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&Tick::new(1));
+        test.execute(
+            ExpectSegment::new()
+                .with_seqno(isn + 4)
+                .with_data("def".to_string()),
+        );
+        test.execute(&ExpectRtt::new(Some(85), Some(40), 490));
+
+        // ack for a retransmitted segment: no sample, back to the un-backed-off rto
+        test.execute(&Tick::new(30));
+        test.execute(AckReceived::new(isn + 7).with_win(1000));
+        test.execute(&ExpectRtt::new(Some(85), Some(40), 245));
+        test.execute(&ExpectNoSegment {});
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.adaptive_rto = true;
+
+        let mut test = TCPSenderTestHarness::new("RTO is clamped to rto_min".to_string(), &cfg);
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(4));
+        test.execute(AckReceived::new(isn + 1).with_win(1000));
+        test.execute(&ExpectRtt::new(
+            Some(4),
+            Some(2),
+            TCPConfig::RTO_MIN_DFLT as u32,
+        ));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+
+        let mut test = TCPSenderTestHarness::new(
+            "Echoed timestamps give a sample for retransmissions too".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(80));
+        test.execute(AckReceived::new(isn + 1).with_win(1000));
+        test.execute(&ExpectRtt::new(
+            Some(80),
+            Some(40),
+            TCPConfig::TIMEOUT_DFLT as u32,
+        ));
+
+        test.execute(&WriteBytes::new("abc".to_string()));
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        test.execute(&Tick::new(1000));
+        test.execute(ExpectSegment::new().with_seqno(isn + 1));
+        test.execute(&Tick::new(80));
+        test.execute(AckReceived::new(isn + 4).with_win(1000).with_ts_echo(1080));
+        test.execute(&ExpectRtt::new(
+            Some(80),
+            Some(30),
+            TCPConfig::TIMEOUT_DFLT as u32,
+        ));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+
+        let mut test = TCPSenderTestHarness::new(
+            "RTT is measured but RTO stays fixed unless adaptive".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(&Tick::new(80));
+        test.execute(AckReceived::new(isn + 1).with_win(1000));
+        test.execute(&ExpectRtt::new(
+            Some(80),
+            Some(40),
+            TCPConfig::TIMEOUT_DFLT as u32,
+        ));
+    }
+}
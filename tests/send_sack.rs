@@ -0,0 +1,225 @@
+use crate::sender_harness::{
+    AckReceived, ExpectFastRecovery, ExpectNoSegment, ExpectSackedBytes, ExpectSegment,
+    TCPSenderTestHarness, WriteBytes,
+};
+use rand::thread_rng;
+use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::tcp_sender::TCPSender;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+mod sender_harness;
+
+#[test]
+fn t_send_sack() {
+    use rand::Rng;
+
+    let mut rd = thread_rng();
+
+    {
+        let mut header = TCPHeader::new();
+        header.seqno = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        header.ack = true;
+        header.sack_permitted = true;
+        header.sack_blocks = vec![
+            (WrappingInt32::new(1000), WrappingInt32::new(2000)),
+            (WrappingInt32::new(u32::MAX - 10), WrappingInt32::new(5)),
+        ];
+        let mut seg = TCPSegment::new(header.clone(), Buffer::new(b"hello".to_vec()));
+        let bytes = seg.serialize(0);
+        assert_eq!(bytes.len(), 20 + 4 + 20 + 5);
+
+        let parsed = TCPSegment::parse_new(Buffer::new(bytes), 0).unwrap();
+        assert!(parsed.header().sack_permitted);
+        assert!(parsed.header().sack_blocks == header.sack_blocks);
+        assert_eq!(parsed.header().header_length(), 44);
+        assert_eq!(parsed.payload().str(), b"hello");
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        cfg.sack = true;
+        let mut sender = TCPSender::from_config(&cfg);
+        sender.fill_window();
+        assert!(sender.segments_out().front().unwrap().header().syn);
+        assert!(
+            sender
+                .segments_out()
+                .front()
+                .unwrap()
+                .header()
+                .sack_permitted
+        );
+
+        cfg.sack = false;
+        let mut sender = TCPSender::from_config(&cfg);
+        sender.fill_window();
+        assert!(
+            !sender
+                .segments_out()
+                .front()
+                .unwrap()
+                .header()
+                .sack_permitted
+        );
+    }
+
+    {
+        // the peer's SYN doesn't offer SACK: the SYN/ACK doesn't either
+        let mut cfg = TCPConfig::default();
+        cfg.sack = true;
+        let mut conn = TCPConnection::new(cfg);
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.seqno = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        conn.segment_received(&TCPSegment::new(header.clone(), Buffer::new(vec![])));
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+        assert!(syn_ack.header().syn && syn_ack.header().ack);
+        assert!(!syn_ack.header().sack_permitted);
+
+        let mut conn = TCPConnection::new(cfg);
+        header.sack_permitted = true;
+        conn.segment_received(&TCPSegment::new(header, Buffer::new(vec![])));
+        let syn_ack = conn.segments_out_mut().pop_front().unwrap();
+        assert!(syn_ack.header().sack_permitted);
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.sack = true;
+
+        let mut test = TCPSenderTestHarness::new(
+            "SACK scoreboard retransmits only the holes".to_string(),
+            &cfg,
+        );
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 6000]).unwrap(),
+        ));
+        for i in 0..6 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+
+        // the first and the third segment are lost
+        test.execute(
+            AckReceived::new(isn + 1)
+                .with_win(10000)
+                .with_sack(isn + 1001, isn + 2001),
+        );
+        test.execute(
+            AckReceived::new(isn + 1)
+                .with_win(10000)
+                .with_sack(isn + 3001, isn + 4001)
+                .with_sack(isn + 1001, isn + 2001),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectSackedBytes::new(2000));
+        test.execute(
+            AckReceived::new(isn + 1)
+                .with_win(10000)
+                .with_sack(isn + 3001, isn + 5001)
+                .with_sack(isn + 1001, isn + 2001),
+        );
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 1),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(3, 1, true));
+
+        // three segments sacked above the second hole now
+        test.execute(
+            AckReceived::new(isn + 1)
+                .with_win(10000)
+                .with_sack(isn + 3001, isn + 6001)
+                .with_sack(isn + 1001, isn + 2001),
+        );
+        test.execute(
+            ExpectSegment::new()
+                .with_payload_size(1000)
+                .with_seqno(isn + 2001),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectSackedBytes::new(4000));
+
+        // partial ack covering the first retransmission, the second one is already on its way
+        test.execute(
+            AckReceived::new(isn + 2001)
+                .with_win(10000)
+                .with_sack(isn + 3001, isn + 6001),
+        );
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(0, 1, true));
+
+        test.execute(AckReceived::new(isn + 6001).with_win(10000));
+        test.execute(&ExpectNoSegment {});
+        test.execute(&ExpectFastRecovery::new(0, 1, false));
+        test.execute(&ExpectSackedBytes::new(0));
+    }
+
+    {
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+
+        let mut test =
+            TCPSenderTestHarness::new("SACK blocks are ignored when SACK is off".to_string(), &cfg);
+        test.execute(ExpectSegment::new().with_syn(true).with_seqno(isn));
+        test.execute(AckReceived::new(isn + 1).with_win(10000));
+        test.execute(&WriteBytes::new(
+            String::from_utf8(vec![b'a'; 3000]).unwrap(),
+        ));
+        for i in 0..3 {
+            test.execute(ExpectSegment::new().with_seqno(isn + 1 + i * 1000));
+        }
+        test.execute(
+            AckReceived::new(isn + 1)
+                .with_win(10000)
+                .with_sack(isn + 1001, isn + 3001),
+        );
+        test.execute(&ExpectSackedBytes::new(0));
+        test.execute(&ExpectNoSegment {});
+    }
+
+    {
+        // a window of a thousand segments with every other one lost: the scoreboard keeps
+        // up without rescanning, and pipe counts each retransmitted hole once
+        let mut cfg = TCPConfig::default();
+        let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
+        cfg.fixed_isn = Option::from(isn);
+        cfg.sack = true;
+        cfg.send_capacity = 1_000_000;
+        let mut sender = TCPSender::from_config(&cfg);
+        sender.fill_window();
+        sender.ack_received(isn + 1, 1_000_000);
+        sender.segments_out_mut().clear();
+        sender.stream_in_mut().write(&vec![b'a'; 1_000_000]);
+        sender.fill_window();
+        assert_eq!(sender.segments_out().len(), 1000);
+        sender.segments_out_mut().clear();
+
+        for i in (1..1000).step_by(2) {
+            let left = isn + 1 + i * 1000;
+            sender.sack_received(&[(left, left + 1000)]);
+            sender.ack_received(isn + 1, 1_000_000);
+        }
+        assert!(sender.in_fast_recovery());
+        assert_eq!(sender.sacked_bytes(), 500_000);
+        // all but the last two even segments are lost, and all of those were resent
+        assert_eq!(sender.sack_retransmits(), 497);
+        assert_eq!(sender.segments_out().len(), 498);
+        assert_eq!(sender.pipe(), 500_000);
+
+        sender.ack_received(isn + 1_000_001, 1_000_000);
+        assert_eq!(sender.bytes_in_flight(), 0);
+        assert_eq!(sender.sacked_bytes(), 0);
+        assert_eq!(sender.pipe(), 0);
+    }
+}
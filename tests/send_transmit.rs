@@ -18,6 +18,7 @@ fn t_send_transmit() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
 
@@ -103,6 +104,7 @@ fn t_send_transmit() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
 
@@ -184,6 +186,7 @@ fn t_send_transmit() {
 
     {
         let mut cfg = TCPConfig::default();
+        cfg.nodelay = true;
         let isn = WrappingInt32::new(rd.gen_range(0..u32::MAX));
         cfg.fixed_isn = Option::from(isn);
 
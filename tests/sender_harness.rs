@@ -105,6 +105,254 @@ impl ExpectBytesInFlight {
     }
 }
 
+pub struct ExpectCongestionWindow {
+    cwnd: SizeT,
+}
+impl SenderTestStep for ExpectCongestionWindow {
+    fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
+        println!("  step: {}", SenderExpectation::to_string(self));
+
+        assert!(
+            sender.congestion_control().is_some(),
+            "The TCPSender has no congestion control configured"
+        );
+        let cc = sender.congestion_control().unwrap();
+        assert_eq!(
+            cc.cwnd(),
+            self.cwnd,
+            "The TCPSender reported congestion state `{}`, but cwnd was expected to be `{}`",
+            cc.summary(),
+            self.cwnd
+        );
+    }
+}
+impl SenderExpectation for ExpectCongestionWindow {
+    fn description(&self) -> String {
+        format!("congestion window of {} bytes", self.cwnd)
+    }
+}
+impl ExpectCongestionWindow {
+    #[allow(dead_code)]
+    pub fn new(w: SizeT) -> ExpectCongestionWindow {
+        ExpectCongestionWindow { cwnd: w }
+    }
+}
+
+pub struct ExpectRateSample {
+    delivered: SizeT,
+    interval: SizeT,
+    rtt: Option<SizeT>,
+}
+impl SenderTestStep for ExpectRateSample {
+    fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
+        println!("  step: {}", SenderExpectation::to_string(self));
+
+        assert!(
+            sender.last_rate_sample().is_some(),
+            "The TCPSender has taken no delivery rate sample"
+        );
+        let rs = sender.last_rate_sample().unwrap();
+        assert!(
+            rs.delivered == self.delivered && rs.interval() == self.interval && rs.rtt == self.rtt,
+            "The TCPSender took rate sample {:?}, but expected {}",
+            rs,
+            self.description()
+        );
+    }
+}
+impl SenderExpectation for ExpectRateSample {
+    fn description(&self) -> String {
+        format!(
+            "rate sample of {} bytes over {} ms with rtt {:?}",
+            self.delivered, self.interval, self.rtt
+        )
+    }
+}
+impl ExpectRateSample {
+    #[allow(dead_code)]
+    pub fn new(delivered: SizeT, interval: SizeT, rtt: Option<SizeT>) -> ExpectRateSample {
+        ExpectRateSample {
+            delivered,
+            interval,
+            rtt,
+        }
+    }
+}
+
+pub struct ExpectRtt {
+    srtt: Option<SizeT>,
+    rttvar: Option<SizeT>,
+    rto: u32,
+}
+impl SenderTestStep for ExpectRtt {
+    fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
+        println!("  step: {}", SenderExpectation::to_string(self));
+
+        assert!(
+            sender.srtt() == self.srtt
+                && sender.rttvar() == self.rttvar
+                && sender.rto() == self.rto,
+            "The TCPSender reported srtt={:?} rttvar={:?} rto={}, but expected {}",
+            sender.srtt(),
+            sender.rttvar(),
+            sender.rto(),
+            self.description()
+        );
+    }
+}
+impl SenderExpectation for ExpectRtt {
+    fn description(&self) -> String {
+        format!(
+            "srtt={:?} rttvar={:?} rto={}",
+            self.srtt, self.rttvar, self.rto
+        )
+    }
+}
+impl ExpectRtt {
+    #[allow(dead_code)]
+    pub fn new(srtt: Option<SizeT>, rttvar: Option<SizeT>, rto: u32) -> ExpectRtt {
+        ExpectRtt { srtt, rttvar, rto }
+    }
+}
+
+pub struct ExpectFastRecovery {
+    dup_acks: SizeT,
+    fast_retransmits: SizeT,
+    in_recovery: bool,
+}
+impl SenderTestStep for ExpectFastRecovery {
+    fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
+        println!("  step: {}", SenderExpectation::to_string(self));
+
+        assert!(
+            sender.dup_acks() == self.dup_acks
+                && sender.fast_retransmits() == self.fast_retransmits
+                && sender.in_fast_recovery() == self.in_recovery,
+            "The TCPSender reported dup_acks={} fast_retransmits={} in_recovery={}, but expected {}",
+            sender.dup_acks(),
+            sender.fast_retransmits(),
+            sender.in_fast_recovery(),
+            self.description()
+        );
+    }
+}
+impl SenderExpectation for ExpectFastRecovery {
+    fn description(&self) -> String {
+        format!(
+            "dup_acks={} fast_retransmits={} in_recovery={}",
+            self.dup_acks, self.fast_retransmits, self.in_recovery
+        )
+    }
+}
+impl ExpectFastRecovery {
+    #[allow(dead_code)]
+    pub fn new(dup_acks: SizeT, fast_retransmits: SizeT, in_recovery: bool) -> ExpectFastRecovery {
+        ExpectFastRecovery {
+            dup_acks,
+            fast_retransmits,
+            in_recovery,
+        }
+    }
+}
+
+pub struct ExpectSackedBytes {
+    bytes: SizeT,
+}
+impl SenderTestStep for ExpectSackedBytes {
+    fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
+        println!("  step: {}", SenderExpectation::to_string(self));
+
+        assert_eq!(
+            sender.sacked_bytes(),
+            self.bytes,
+            "The TCPSender reported {} sacked bytes, but there was expected to be {} sacked bytes",
+            sender.sacked_bytes(),
+            self.bytes
+        );
+    }
+}
+impl SenderExpectation for ExpectSackedBytes {
+    fn description(&self) -> String {
+        format!("{} bytes sacked", self.bytes)
+    }
+}
+impl ExpectSackedBytes {
+    #[allow(dead_code)]
+    pub fn new(bytes: SizeT) -> ExpectSackedBytes {
+        ExpectSackedBytes { bytes }
+    }
+}
+
+pub struct ExpectWindowProbes {
+    probes: SizeT,
+    persist_timeout: Option<u32>,
+}
+impl SenderTestStep for ExpectWindowProbes {
+    fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
+        println!("  step: {}", SenderExpectation::to_string(self));
+
+        assert_eq!(
+            sender.window_probes(),
+            self.probes,
+            "The TCPSender reported {} window probes, but there was expected to be {}",
+            sender.window_probes(),
+            self.probes
+        );
+        assert_eq!(
+            sender.persist_timeout(),
+            self.persist_timeout,
+            "The TCPSender reported a persist timeout of {:?}, but it was expected to be {:?}",
+            sender.persist_timeout(),
+            self.persist_timeout
+        );
+    }
+}
+impl SenderExpectation for ExpectWindowProbes {
+    fn description(&self) -> String {
+        format!(
+            "{} window probes sent, persist timeout {:?}",
+            self.probes, self.persist_timeout
+        )
+    }
+}
+impl ExpectWindowProbes {
+    #[allow(dead_code)]
+    pub fn new(probes: SizeT, persist_timeout: Option<u32>) -> ExpectWindowProbes {
+        ExpectWindowProbes {
+            probes,
+            persist_timeout,
+        }
+    }
+}
+
+pub struct ExpectTailLossProbes {
+    probes: SizeT,
+}
+impl SenderTestStep for ExpectTailLossProbes {
+    fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
+        println!("  step: {}", SenderExpectation::to_string(self));
+
+        assert_eq!(
+            sender.tail_loss_probes(),
+            self.probes,
+            "The TCPSender reported {} tail loss probes, but there was expected to be {}",
+            sender.tail_loss_probes(),
+            self.probes
+        );
+    }
+}
+impl SenderExpectation for ExpectTailLossProbes {
+    fn description(&self) -> String {
+        format!("{} tail loss probes sent", self.probes)
+    }
+}
+impl ExpectTailLossProbes {
+    #[allow(dead_code)]
+    pub fn new(probes: SizeT) -> ExpectTailLossProbes {
+        ExpectTailLossProbes { probes }
+    }
+}
+
 pub struct ExpectNoSegment {}
 impl SenderTestStep for ExpectNoSegment {
     fn execute(&self, _sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
@@ -237,15 +485,21 @@ impl Tick {
 pub struct AckReceived {
     ackno: WrappingInt32,
     window_advertisement: Option<u16>,
+    sack_blocks: Vec<(WrappingInt32, WrappingInt32)>,
+    ts_echo: Option<SizeT>,
 }
 impl SenderTestStep for AckReceived {
     fn execute(&self, sender: &mut TCPSender, _segments: &mut VecDeque<TCPSegment>) {
         println!("  step: {}", SenderAction::to_string(self));
 
+        if let Some(t) = self.ts_echo {
+            sender.timestamp_echo_received(t);
+        }
+        sender.sack_received(&self.sack_blocks);
         sender.ack_received(
             self.ackno,
             self.window_advertisement
-                .unwrap_or(DEFAULT_TEST_WINDOW as u16),
+                .unwrap_or(DEFAULT_TEST_WINDOW as u16) as u32,
         );
         sender.fill_window();
     }
@@ -253,10 +507,14 @@ impl SenderTestStep for AckReceived {
 impl SenderAction for AckReceived {
     fn description(&self) -> String {
         format!(
-            "ack {} winsize {}",
+            "ack {} winsize {}{}",
             self.ackno.raw_value(),
             self.window_advertisement
-                .unwrap_or(DEFAULT_TEST_WINDOW as u16)
+                .unwrap_or(DEFAULT_TEST_WINDOW as u16),
+            self.sack_blocks
+                .iter()
+                .map(|(l, r)| format!(" sack {}-{}", l.raw_value(), r.raw_value()))
+                .collect::<String>()
         )
     }
 }
@@ -266,14 +524,28 @@ impl AckReceived {
         AckReceived {
             ackno: _ackno,
             window_advertisement: None,
+            sack_blocks: vec![],
+            ts_echo: None,
         }
     }
 
     #[allow(dead_code)]
-    pub fn with_win(&mut self, win: u16) -> &AckReceived {
+    pub fn with_win(&mut self, win: u16) -> &mut AckReceived {
         let _ = self.window_advertisement.insert(win);
         self
     }
+
+    #[allow(dead_code)]
+    pub fn with_ts_echo(&mut self, echoed: SizeT) -> &mut AckReceived {
+        self.ts_echo = Some(echoed);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_sack(&mut self, left: WrappingInt32, right: WrappingInt32) -> &mut AckReceived {
+        self.sack_blocks.push((left, right));
+        self
+    }
 }
 
 pub struct Close {}
@@ -556,7 +828,7 @@ impl TCPSenderTestHarness {
     #[allow(dead_code)]
     pub fn new(name_: String, config: &TCPConfig) -> TCPSenderTestHarness {
         let mut harness = TCPSenderTestHarness {
-            sender: TCPSender::new(config.send_capacity, config.rt_timeout, config.fixed_isn),
+            sender: TCPSender::from_config(config),
             outbound_segments: Default::default(),
             name: name_,
         };
@@ -0,0 +1,61 @@
+use rust_sponge::tcp_helpers::syn_cookie::SynCookies;
+use rust_sponge::wrapping_integers::WrappingInt32;
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+#[test]
+fn t_syn_cookie() {
+    let cookies = SynCookies::new();
+    let tuple = (
+        SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 1440),
+        SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 2), 9801),
+    );
+    let other = (tuple.0, SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 2), 9802));
+    let isn = WrappingInt32::new(123456);
+    let now = 1000 * SynCookies::PERIOD_MS + 17;
+
+    {
+        // the mss comes back rounded down to the table
+        for (mss, expected) in [
+            (1460, 1460),
+            (1452, 1440),
+            (1000, 1000),
+            (999, 536),
+            (10, 64),
+        ] {
+            let c = cookies.make(&tuple, isn, mss, now);
+            assert_eq!(cookies.check(&tuple, isn, c, now), Some(expected));
+        }
+    }
+
+    {
+        // only the connection and peer ISN it was made for
+        let c = cookies.make(&tuple, isn, 1460, now);
+        assert_eq!(cookies.check(&other, isn, c, now), None);
+        assert_eq!(cookies.check(&tuple, isn + 1, c, now), None);
+        assert_eq!(cookies.check(&tuple, isn, c + 1, now), None);
+        // a different mss index is caught by the hash
+        let forged = WrappingInt32::new(c.raw_value() ^ (1 << 24));
+        assert_eq!(cookies.check(&tuple, isn, forged, now), None);
+        // nor does another listener's secret work
+        assert_eq!(SynCookies::new().check(&tuple, isn, c, now), None);
+    }
+
+    {
+        // good for MAX_AGE more periods, not in the past
+        let c = cookies.make(&tuple, isn, 1460, now);
+        let last = now + SynCookies::MAX_AGE * SynCookies::PERIOD_MS;
+        let last = last - last % SynCookies::PERIOD_MS + SynCookies::PERIOD_MS - 1;
+        assert_eq!(cookies.check(&tuple, isn, c, last), Some(1460));
+        assert_eq!(cookies.check(&tuple, isn, c, last + 1), None);
+        assert_eq!(
+            cookies.check(&tuple, isn, c, now - SynCookies::PERIOD_MS),
+            None
+        );
+    }
+
+    {
+        // early on there are fewer periods to look back on
+        let c = cookies.make(&tuple, isn, 536, 5);
+        assert_eq!(cookies.check(&tuple, isn, c, 10), Some(536));
+    }
+}
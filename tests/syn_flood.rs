@@ -0,0 +1,207 @@
+use crate::listener_harness::{adapter_config, listener, wait_for};
+use rust_sponge::tcp_helpers::fd_adapter::TCPOverUDPSocketAdapter;
+use rust_sponge::tcp_helpers::syn_cookie::SynCookieMode;
+use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::tcp_helpers::tcp_sponge_socket::{AsLocalStreamSocketMut, TCPSpongeSocket};
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::util::file_descriptor::{AsFileDescriptor, AsFileDescriptorMut};
+use rust_sponge::util::socket::{AsSocket, UDPSocket};
+use rust_sponge::wrapping_integers::WrappingInt32;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::thread;
+use std::time::Duration;
+
+mod listener_harness;
+
+fn segment(seqno: WrappingInt32, ackno: Option<WrappingInt32>, syn: bool) -> Vec<u8> {
+    let mut header = TCPHeader::new();
+    header.syn = syn;
+    header.seqno = seqno;
+    if let Some(ackno) = ackno {
+        header.ack = true;
+        header.ackno = ackno;
+    }
+    header.win = 1000;
+    TCPSegment::new(header, Buffer::new(vec![])).serialize(0)
+}
+
+// peers that each send a SYN and never finish the handshake, from their own port so the
+// listener sees as many connections
+struct SynFlood {
+    peers: Vec<UDPSocket>,
+}
+impl SynFlood {
+    fn new(n: usize) -> SynFlood {
+        SynFlood {
+            peers: (0..n).map(|_| UDPSocket::new()).collect(),
+        }
+    }
+
+    fn isn(i: usize) -> WrappingInt32 {
+        WrappingInt32::new(1000 * i as u32)
+    }
+
+    fn send(&mut self, addr: &SocketAddrV4) {
+        for (i, p) in self.peers.iter_mut().enumerate() {
+            p.sendto(addr, &mut segment(SynFlood::isn(i), None, true));
+        }
+    }
+
+    fn recv(&mut self, i: usize) -> TCPSegment {
+        let (_, payload) = self.peers[i].recv(65536);
+        TCPSegment::parse_new(Buffer::new(payload), 0).unwrap()
+    }
+
+    fn port(&self, i: usize) -> u16 {
+        SocketAddrV4::from(self.peers[i].as_socket().local_address()).port()
+    }
+}
+
+#[test]
+fn t_syn_flood() {
+    let cfg = TCPConfig {
+        rt_timeout: 50,
+        ..Default::default()
+    };
+
+    {
+        // past a full SYN queue every SYN is still answered, without the listener keeping
+        // anything for it
+        let (mut l, addr) = listener(&cfg, 4, SynCookieMode::Auto);
+        let mut flood = SynFlood::new(64);
+        flood.send(&addr);
+        let mut cookies = vec![];
+        for i in 0..64 {
+            let syn_ack = flood.recv(i);
+            assert!(syn_ack.header().syn && syn_ack.header().ack);
+            assert_eq!(syn_ack.header().ackno, SynFlood::isn(i) + 1);
+            cookies.push(syn_ack.header().seqno);
+        }
+        assert_eq!(l.syn_queue_len(), 4);
+        assert_eq!(l.connection_count(), 4);
+        assert_eq!(l.syn_cookies_sent(), 60);
+
+        // an ACK of a cookie that was never sent opens nothing
+        let mut forger = UDPSocket::new();
+        forger.sendto(
+            &addr,
+            &mut segment(WrappingInt32::new(1), Some(cookies[63] + 1), false),
+        );
+        let (_, payload) = forger.recv(65536);
+        let rst = TCPSegment::parse_new(Buffer::new(payload), 0).unwrap();
+        assert!(rst.header().rst);
+        // numbers at the edge of the sequence space wrap
+        forger.sendto(
+            &addr,
+            &mut segment(WrappingInt32::new(0), Some(WrappingInt32::new(0)), false),
+        );
+        let (_, payload) = forger.recv(65536);
+        let rst = TCPSegment::parse_new(Buffer::new(payload), 0).unwrap();
+        assert!(rst.header().rst);
+        assert_eq!(l.connection_count(), 4);
+
+        // the ACK of a cookie rebuilds the connection
+        flood.peers[63].sendto(
+            &addr,
+            &mut segment(SynFlood::isn(63) + 1, Some(cookies[63] + 1), false),
+        );
+        wait_for("the cookie connection", || l.accept_queue_len() == 1);
+        let stream = l.accept().unwrap();
+        assert_eq!(stream.peer_address().port(), flood.port(63));
+        assert_eq!(l.syn_cookies_accepted(), 1);
+        assert_eq!(l.syn_queue_len(), 4);
+
+        // a real client gets through the flood and its data both ways
+        let client = thread::spawn(move || {
+            let mut client = TCPSpongeSocket::new(TCPOverUDPSocketAdapter::new(UDPSocket::new()));
+            let source = SocketAddrV4::new(Ipv4Addr::LOCALHOST, 9801);
+            client.connect(&cfg, adapter_config(source, addr));
+            let s = client.as_socket_mut();
+            s.lock().unwrap().write(b"hello", true);
+            let mut reply = vec![];
+            while !s.lock().unwrap().eof() {
+                reply.extend(s.lock().unwrap().read(65536));
+            }
+            client.wait_until_closed();
+            reply
+        });
+        let mut stream = l.accept().unwrap();
+        assert_eq!(l.syn_cookies_accepted(), 2);
+        let s = stream.as_socket_mut();
+        let mut data = vec![];
+        while data.len() < 5 {
+            data.extend(s.lock().unwrap().read(65536));
+        }
+        assert_eq!(data, b"hello");
+        s.lock().unwrap().write(b"echo: hello", true);
+        stream.shutdown();
+        assert_eq!(client.join().unwrap(), b"echo: hello");
+    }
+
+    {
+        // always on, not even the first SYN takes a slot
+        let (mut l, addr) = listener(&cfg, 4, SynCookieMode::Always);
+        let mut flood = SynFlood::new(1);
+        flood.send(&addr);
+        let cookie = flood.recv(0).header().seqno;
+        assert_eq!(l.connection_count(), 0);
+
+        // the cookie is tied to the peer's ISN
+        flood.peers[0].sendto(
+            &addr,
+            &mut segment(SynFlood::isn(0) + 2, Some(cookie + 1), false),
+        );
+        assert!(flood.recv(0).header().rst);
+        assert_eq!(l.connection_count(), 0);
+
+        flood.peers[0].sendto(
+            &addr,
+            &mut segment(SynFlood::isn(0) + 1, Some(cookie + 1), false),
+        );
+        assert!(l.accept().is_some());
+        assert_eq!(l.syn_cookies_accepted(), 1);
+    }
+
+    {
+        // a good cookie past a full accept queue is dropped, not reset, and the peer's
+        // retransmission gets in once accept() made room
+        let (mut l, addr) = listener(&cfg, 1, SynCookieMode::Always);
+        let mut flood = SynFlood::new(2);
+        flood.send(&addr);
+        let cookies: Vec<_> = (0..2).map(|i| flood.recv(i).header().seqno).collect();
+        flood.peers[0].sendto(
+            &addr,
+            &mut segment(SynFlood::isn(0) + 1, Some(cookies[0] + 1), false),
+        );
+        wait_for("the first cookie connection", || l.accept_queue_len() == 1);
+
+        let mut ack = segment(SynFlood::isn(1) + 1, Some(cookies[1] + 1), false);
+        flood.peers[1].sendto(&addr, &mut ack.clone());
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(l.connection_count(), 1);
+        assert_eq!(l.syn_cookies_accepted(), 1);
+
+        assert!(l.accept().is_some());
+        flood.peers[1].sendto(&addr, &mut ack);
+        let mut stream = l.accept().unwrap();
+        assert_eq!(stream.peer_address().port(), flood.port(1));
+        stream.as_socket_mut().lock().unwrap().write(b"hello", true);
+        let seg = flood.recv(1);
+        assert!(!seg.header().rst);
+        assert_eq!(seg.payload().size(), 5);
+    }
+
+    {
+        // off, the SYNs past the backlog are dropped
+        let (l, addr) = listener(&cfg, 2, SynCookieMode::Off);
+        let mut flood = SynFlood::new(8);
+        flood.send(&addr);
+        flood.recv(0);
+        flood.recv(1);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(l.syn_queue_len(), 2);
+        assert_eq!(l.syn_cookies_sent(), 0);
+    }
+}
@@ -1,9 +1,9 @@
-use rust_sponge::tcp_connection::TCPConnection;
+use rust_sponge::tcp_connection::{ResetReason, TCPConnection};
 use rust_sponge::tcp_helpers::fd_adapter::FdAdapterBase;
 use rust_sponge::tcp_helpers::tcp_config::TCPConfig;
 use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
 use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
-use rust_sponge::tcp_helpers::tcp_state::TCPState;
+use rust_sponge::tcp_helpers::tcp_state::{State, StateError};
 use rust_sponge::util::buffer::Buffer;
 use rust_sponge::util::file_descriptor::{AsFileDescriptor, AsFileDescriptorMut, FileDescriptor};
 use rust_sponge::util::parser::ParseResult;
@@ -798,7 +798,7 @@ impl ExpectOneSegment {
 }
 
 pub struct ExpectState {
-    state: TCPState,
+    state: State,
 }
 impl TCPTestStep for ExpectState {
     fn execute(&mut self, h: &mut TCPTestHarness) {
@@ -810,27 +810,26 @@ impl TCPTestStep for ExpectState {
             self.state,
             "{}",
             format!(
-                "The TCP was in state `{}`, but it was expected to be in state `{}`",
-                actual_state.name(),
-                self.state.name()
+                "The TCP was in state `{:?}`, but it was expected to be in state `{:?}`",
+                actual_state, self.state
             )
         );
     }
 }
 impl TCPExpectation for ExpectState {
     fn description(&self) -> String {
-        format!("TCP in state {}", self.state.name())
+        format!("TCP in state {:?}", self.state)
     }
 }
 impl ExpectState {
     #[allow(dead_code)]
-    pub fn new(state_: TCPState) -> ExpectState {
+    pub fn new(state_: State) -> ExpectState {
         ExpectState { state: state_ }
     }
 }
 
 pub struct ExpectNotInState {
-    state: TCPState,
+    state: State,
 }
 impl TCPTestStep for ExpectNotInState {
     fn execute(&mut self, h: &mut TCPTestHarness) {
@@ -841,18 +840,18 @@ impl TCPTestStep for ExpectNotInState {
             actual_state,
             self.state,
             "{}",
-            make_not("state", self.state.name())
+            make_not("state", format!("{:?}", self.state))
         );
     }
 }
 impl TCPExpectation for ExpectNotInState {
     fn description(&self) -> String {
-        format!("TCP **not** in state {}", self.state.name())
+        format!("TCP **not** in state {:?}", self.state)
     }
 }
 impl ExpectNotInState {
     #[allow(dead_code)]
-    pub fn new(state_: TCPState) -> ExpectNotInState {
+    pub fn new(state_: State) -> ExpectNotInState {
         ExpectNotInState { state: state_ }
     }
 }
@@ -970,6 +969,7 @@ pub struct SendSegment {
     seqno: WrappingInt32,
     ackno: WrappingInt32,
     win: u16,
+    mss: Option<u16>,
     payload_size: SizeT,
     data: Buffer,
 }
@@ -1008,6 +1008,7 @@ impl SendSegment {
             seqno: seg.header().seqno.clone(),
             ackno: seg.header().ackno.clone(),
             win: seg.header().win,
+            mss: seg.header().mss,
             payload_size: 0,
             data: seg.payload().clone(),
         }
@@ -1055,6 +1056,12 @@ impl SendSegment {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn with_mss(&mut self, mss_: Option<u16>) -> &mut SendSegment {
+        self.mss = mss_;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn with_payload_size(&mut self, payload_size_: SizeT) -> &mut SendSegment {
         self.payload_size = payload_size_;
@@ -1077,6 +1084,7 @@ impl SendSegment {
         data_hdr.ackno = self.ackno.clone();
         data_hdr.seqno = self.seqno.clone();
         data_hdr.win = self.win;
+        data_hdr.mss = self.mss;
 
         TCPSegment::new(data_hdr, self.data.clone())
     }
@@ -1091,6 +1099,7 @@ impl Default for SendSegment {
             seqno: WrappingInt32::new(0),
             ackno: WrappingInt32::new(0),
             win: 0,
+            mss: None,
             payload_size: 0,
             data: Buffer::new(vec![]),
         }
@@ -1209,6 +1218,135 @@ impl TCPAction for Close {
     }
 }
 
+pub struct Read {
+    len: SizeT,
+}
+impl TCPTestStep for Read {
+    fn execute(&mut self, h: &mut TCPTestHarness) {
+        println!("  step: {}", TCPAction::to_string(self));
+
+        h.fsm.inbound_stream_mut().read(self.len);
+    }
+}
+impl TCPAction for Read {
+    fn description(&self) -> String {
+        format!("read {} bytes", self.len)
+    }
+}
+impl Read {
+    #[allow(dead_code)]
+    pub fn new(len_: SizeT) -> Read {
+        Read { len: len_ }
+    }
+}
+
+pub struct SetNodelay {
+    nodelay: bool,
+}
+impl TCPTestStep for SetNodelay {
+    fn execute(&mut self, h: &mut TCPTestHarness) {
+        println!("  step: {}", TCPAction::to_string(self));
+
+        h.fsm.set_nodelay(self.nodelay);
+    }
+}
+impl TCPAction for SetNodelay {
+    fn description(&self) -> String {
+        format!("set nodelay to {}", self.nodelay)
+    }
+}
+impl SetNodelay {
+    #[allow(dead_code)]
+    pub fn new(nodelay_: bool) -> SetNodelay {
+        SetNodelay { nodelay: nodelay_ }
+    }
+}
+
+pub struct SetCork {
+    cork: bool,
+}
+impl TCPTestStep for SetCork {
+    fn execute(&mut self, h: &mut TCPTestHarness) {
+        println!("  step: {}", TCPAction::to_string(self));
+
+        h.fsm.set_cork(self.cork);
+    }
+}
+impl TCPAction for SetCork {
+    fn description(&self) -> String {
+        format!("set cork to {}", self.cork)
+    }
+}
+impl SetCork {
+    #[allow(dead_code)]
+    pub fn new(cork_: bool) -> SetCork {
+        SetCork { cork: cork_ }
+    }
+}
+
+pub struct ExpectResetReason {
+    reason: Option<ResetReason>,
+}
+impl TCPTestStep for ExpectResetReason {
+    fn execute(&mut self, h: &mut TCPTestHarness) {
+        println!("  step: {}", TCPExpectation::to_string(self));
+
+        let actual_reason = h.fsm.reset_reason();
+        assert_eq!(
+            actual_reason,
+            self.reason,
+            "{}",
+            format!(
+                "The TCP was reset for `{:?}`, but it was expected to be for `{:?}`",
+                actual_reason, self.reason
+            )
+        );
+    }
+}
+impl TCPExpectation for ExpectResetReason {
+    fn description(&self) -> String {
+        format!("TCP reset for {:?}", self.reason)
+    }
+}
+impl ExpectResetReason {
+    #[allow(dead_code)]
+    pub fn new(reason_: Option<ResetReason>) -> ExpectResetReason {
+        ExpectResetReason { reason: reason_ }
+    }
+}
+
+// takes the error, so a second one expects None
+pub struct ExpectStateError {
+    error: Option<StateError>,
+}
+impl TCPTestStep for ExpectStateError {
+    fn execute(&mut self, h: &mut TCPTestHarness) {
+        println!("  step: {}", TCPExpectation::to_string(self));
+
+        let actual_error = h.fsm.take_state_error();
+        assert_eq!(
+            actual_error,
+            self.error,
+            "{}",
+            format!(
+                "The TCP reported `{:?}`, but it was expected to report `{:?}`",
+                actual_error, self.error
+            )
+        );
+    }
+}
+impl TCPExpectation for ExpectStateError {
+    fn description(&self) -> String {
+        format!("TCP reported {:?}", self.error)
+    }
+}
+impl ExpectStateError {
+    #[allow(dead_code)]
+    pub fn new(error_: Option<StateError>) -> ExpectStateError {
+        ExpectStateError { error: error_ }
+    }
+}
+
 pub struct TCPTestHarness {
     fsm: TCPConnection,
     flt: TestFdAdapter,
@@ -1277,6 +1415,16 @@ impl TCPTestHarness {
 
     #[allow(dead_code)]
     pub fn send_syn(&mut self, seqno: WrappingInt32, ackno: Option<WrappingInt32>) {
+        self.send_syn_with_mss(seqno, ackno, None);
+    }
+
+    #[allow(dead_code)]
+    pub fn send_syn_with_mss(
+        &mut self,
+        seqno: WrappingInt32,
+        ackno: Option<WrappingInt32>,
+        mss: Option<u16>,
+    ) {
         let mut step = SendSegment {
             ..Default::default()
         };
@@ -1285,7 +1433,8 @@ impl TCPTestHarness {
         }
         step.with_syn(true)
             .with_seqno(seqno)
-            .with_win(TCPTestHarness::DEFAULT_TEST_WINDOW as u16);
+            .with_win(TCPTestHarness::DEFAULT_TEST_WINDOW as u16)
+            .with_mss(mss);
         self.execute(&mut step, "".to_string());
     }
 
@@ -1376,9 +1525,20 @@ impl TCPTestHarness {
         cfg: &TCPConfig,
         tx_isn: WrappingInt32,
         rx_isn: WrappingInt32,
+    ) -> TCPTestHarness {
+        TCPTestHarness::in_established_with_mss(cfg, tx_isn, rx_isn, None)
+    }
+
+    // the peer's SYN announces `mss`, without one the connection assumes 536 bytes
+    #[allow(dead_code)]
+    pub fn in_established_with_mss(
+        cfg: &TCPConfig,
+        tx_isn: WrappingInt32,
+        rx_isn: WrappingInt32,
+        mss: Option<u16>,
     ) -> TCPTestHarness {
         let mut h = TCPTestHarness::in_syn_sent(cfg, tx_isn);
-        h.send_syn(rx_isn, Option::Some(tx_isn + 1));
+        h.send_syn_with_mss(rx_isn, Option::Some(tx_isn + 1), mss);
         h.execute(
             ExpectOneSegment::new()
                 .with_no_flags()
@@ -0,0 +1,151 @@
+use crate::listener_harness::{adapter_config, listener, wait_for};
+use rust_sponge::tcp_helpers::fd_adapter::TCPOverUDPSocketAdapter;
+use rust_sponge::tcp_helpers::syn_cookie::SynCookieMode;
+use rust_sponge::tcp_helpers::tcp_config::{FdAdapterConfig, TCPConfig};
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_listener::TCPListener;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::tcp_helpers::tcp_sponge_socket::{AsLocalStreamSocketMut, TCPSpongeSocket};
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::util::file_descriptor::{AsFileDescriptor, AsFileDescriptorMut};
+use rust_sponge::util::socket::{AsSocket, UDPSocket};
+use rust_sponge::wrapping_integers::WrappingInt32;
+use rust_sponge::LossyTCPOverUDPSocketAdapter;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::thread;
+use std::time::Duration;
+
+mod listener_harness;
+
+// reads until the peer shuts down its side
+fn read_all(sock: &mut dyn AsLocalStreamSocketMut) -> Vec<u8> {
+    let s = sock.as_socket_mut();
+    let mut s = s.lock().unwrap();
+    let mut ret = vec![];
+    while !s.eof() {
+        ret.extend(s.read(65536));
+    }
+    ret
+}
+
+fn raw_segment(syn: bool, ack: bool) -> Vec<u8> {
+    let mut header = TCPHeader::new();
+    header.syn = syn;
+    header.ack = ack;
+    header.seqno = WrappingInt32::new(1000);
+    header.ackno = WrappingInt32::new(77);
+    header.win = 1000;
+    TCPSegment::new(header, Buffer::new(vec![])).serialize(0)
+}
+
+#[test]
+fn t_tcp_listener() {
+    let cfg = TCPConfig {
+        rt_timeout: 50,
+        ..Default::default()
+    };
+
+    {
+        // two clients at once, each gets its own stream and its own reply
+        let (mut l, addr) = listener(&cfg, 4, SynCookieMode::Auto);
+        let clients: Vec<_> = (0..2)
+            .map(|i| {
+                let cfg = cfg;
+                thread::spawn(move || {
+                    let mut client =
+                        TCPSpongeSocket::new(TCPOverUDPSocketAdapter::new(UDPSocket::new()));
+                    let source = SocketAddrV4::new(Ipv4Addr::LOCALHOST, 9801);
+                    client.connect(&cfg, adapter_config(source, addr));
+                    let msg = format!("hello from {}", i);
+                    client
+                        .as_socket_mut()
+                        .lock()
+                        .unwrap()
+                        .write(msg.as_bytes(), true);
+                    let reply = read_all(&mut client);
+                    client.wait_until_closed();
+                    (msg, reply)
+                })
+            })
+            .collect();
+
+        let mut servers = vec![];
+        for _ in 0..2 {
+            let mut stream = l.accept().unwrap();
+            assert_eq!(stream.local_address(), addr);
+            let server = thread::spawn(move || {
+                let mut data = vec![];
+                let s = stream.as_socket_mut();
+                while !data.starts_with(b"hello from ") || data.len() < 12 {
+                    data.extend(s.lock().unwrap().read(65536));
+                }
+                data.splice(0..0, b"echo: ".iter().cloned());
+                s.lock().unwrap().write(&data, true);
+                stream.shutdown();
+                stream.peer_address()
+            });
+            servers.push(server);
+        }
+        let peers: Vec<_> = servers.into_iter().map(|s| s.join().unwrap()).collect();
+        assert_ne!(peers[0], peers[1]);
+
+        for c in clients {
+            let (msg, reply) = c.join().unwrap();
+            assert_eq!(reply, format!("echo: {}", msg).into_bytes());
+        }
+        // the server side closed first and lingers in TIME_WAIT before it goes
+        wait_for("connections to close", || l.connection_count() == 0);
+        l.close();
+        assert!(l.accept().is_none());
+    }
+
+    {
+        // a segment for no connection is answered with a RST
+        let (_l, addr) = listener(&cfg, 4, SynCookieMode::Auto);
+        let mut peer = UDPSocket::new();
+        peer.sendto(&addr, &mut raw_segment(false, true));
+        let (_, payload) = peer.recv(65536);
+        let rst = TCPSegment::parse_new(Buffer::new(payload), 0).unwrap();
+        assert!(rst.header().rst);
+        assert_eq!(rst.header().seqno, WrappingInt32::new(77));
+    }
+
+    {
+        // with SYN cookies off, SYNs past the backlog are dropped
+        let (l, addr) = listener(&cfg, 1, SynCookieMode::Off);
+        let mut first = UDPSocket::new();
+        first.sendto(&addr, &mut raw_segment(true, false));
+        let (_, payload) = first.recv(65536);
+        let syn_ack = TCPSegment::parse_new(Buffer::new(payload), 0).unwrap();
+        assert!(syn_ack.header().syn && syn_ack.header().ack);
+        assert_eq!(syn_ack.header().ackno, WrappingInt32::new(1001));
+
+        let mut second = UDPSocket::new();
+        second.sendto(&addr, &mut raw_segment(true, false));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(l.syn_queue_len(), 1);
+        assert_eq!(l.connection_count(), 1);
+        assert_eq!(l.accept_queue_len(), 0);
+        assert_eq!(l.syn_cookies_sent(), 0);
+    }
+
+    {
+        // a lossy adapter drops what the listener reads as well
+        let sock = UDPSocket::new();
+        sock.bind("127.0.0.1", 0);
+        let addr = SocketAddrV4::from(sock.as_socket().local_address());
+        let mut l = TCPListener::new(LossyTCPOverUDPSocketAdapter::new(
+            TCPOverUDPSocketAdapter::new(sock),
+        ));
+        let c_ad = FdAdapterConfig {
+            loss_rate_dn: u16::MAX,
+            ..adapter_config(addr, addr)
+        };
+        l.listen(&cfg, c_ad, 4);
+        let mut peer = UDPSocket::new();
+        peer.sendto(&addr, &mut raw_segment(true, false));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(l.connection_count(), 0);
+        assert_eq!(l.syn_queue_len(), 0);
+    }
+}
@@ -0,0 +1,228 @@
+use rust_sponge::tcp_helpers::tcp_header::TCPHeader;
+use rust_sponge::tcp_helpers::tcp_option::TCPOption;
+use rust_sponge::tcp_helpers::tcp_segment::TCPSegment;
+use rust_sponge::util::buffer::Buffer;
+use rust_sponge::util::parser::{NetParser, ParseResult};
+use rust_sponge::wrapping_integers::WrappingInt32;
+
+// the parsed segment and the doff that went on the wire
+fn round_trip(header: &TCPHeader) -> (TCPSegment, u8) {
+    let mut seg = TCPSegment::new(header.clone(), Buffer::new(vec![]));
+    let bytes = seg.serialize(0);
+    let doff = bytes[12] >> 4;
+    (TCPSegment::parse_new(Buffer::new(bytes), 0).unwrap(), doff)
+}
+
+// serializes `header`, whose only option must be Unknown(30, [0, 0]), with the option bytes
+// swapped for `opt`. `opt` must keep the 16-bit sum of the original so the checksum holds
+fn parse_patched(header: &TCPHeader, opt: [u8; 4]) -> Result<TCPSegment, ParseResult> {
+    let mut seg = TCPSegment::new(header.clone(), Buffer::new(vec![]));
+    let mut bytes = seg.serialize(0);
+    assert_eq!(
+        &bytes[TCPHeader::LENGTH..TCPHeader::LENGTH + 4],
+        &[30, 4, 0, 0]
+    );
+    bytes[TCPHeader::LENGTH..TCPHeader::LENGTH + 4].copy_from_slice(&opt);
+    TCPSegment::parse_new(Buffer::new(bytes), 0)
+}
+
+#[test]
+fn t_tcp_options() {
+    {
+        let opts = vec![
+            TCPOption::Mss(1460),
+            TCPOption::WindowScale(7),
+            TCPOption::SackPermitted,
+            TCPOption::Sack(vec![
+                (WrappingInt32::new(100), WrappingInt32::new(200)),
+                (WrappingInt32::new(u32::MAX - 5), WrappingInt32::new(10)),
+            ]),
+            TCPOption::Timestamps(0xdead_beef, 42),
+            TCPOption::Unknown(30, vec![1, 2, 3]),
+            TCPOption::Unknown(31, vec![]),
+        ];
+        for opt in opts {
+            let mut bytes: Vec<u8> = vec![];
+            opt.serialize(&mut bytes);
+            assert_eq!(bytes.len(), opt.length());
+            assert_eq!(bytes[0], opt.kind());
+
+            let mut buf = Buffer::new(bytes.clone());
+            let mut p = NetParser::new(&mut buf);
+            let parsed = TCPOption::parse_all(&mut p, bytes.len()).unwrap();
+            assert_eq!(parsed, vec![opt]);
+        }
+    }
+
+    {
+        // layout bytes are consumed but not returned, everything after EOL is padding
+        let bytes = vec![1, 1, 2, 4, 0x05, 0xb4, 1, 0, 2, 4];
+        let len = bytes.len();
+        let mut buf = Buffer::new(bytes);
+        let mut p = NetParser::new(&mut buf);
+        let parsed = TCPOption::parse_all(&mut p, len).unwrap();
+        assert_eq!(parsed, vec![TCPOption::Mss(1460)]);
+    }
+
+    {
+        let bad = vec![
+            // length runs past the options area
+            vec![2, 8, 0, 0],
+            // length below 2
+            vec![30, 1, 0, 0],
+            // kind without a length byte
+            vec![1, 1, 1, 30],
+            // known kind with the wrong length
+            vec![3, 4, 0, 0],
+            // SACK with a partial block
+            vec![5, 6, 0, 0, 0, 0, 0, 0],
+            // SACK without blocks
+            vec![5, 2, 0, 0],
+        ];
+        for bytes in bad {
+            let len = bytes.len();
+            let mut buf = Buffer::new(bytes);
+            let mut p = NetParser::new(&mut buf);
+            assert_eq!(
+                TCPOption::parse_all(&mut p, len),
+                Err(ParseResult::BadOption)
+            );
+        }
+    }
+
+    {
+        // no options, no padding
+        let header = TCPHeader::new();
+        assert_eq!(header.header_length(), TCPHeader::LENGTH);
+        assert!(header.options().is_empty());
+        assert_eq!(round_trip(&header).1, 5);
+    }
+
+    {
+        // MSS alone is already aligned, window scale gets a NOP in front
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.mss = Some(536);
+        assert_eq!(header.header_length(), 24);
+        header.wscale = Some(14);
+        assert_eq!(
+            header.options(),
+            vec![
+                TCPOption::Mss(536),
+                TCPOption::Nop,
+                TCPOption::WindowScale(14)
+            ]
+        );
+        assert_eq!(header.header_length(), 28);
+        let (parsed, doff) = round_trip(&header);
+        assert_eq!(doff, 7);
+        assert!(*parsed.header() == header);
+    }
+
+    {
+        // unknown options are passed through, and padded with EOL to a 4 byte boundary
+        let mut header = TCPHeader::new();
+        header.ack = true;
+        header.timestamps = Some((7, 9));
+        header.unknown_options = vec![TCPOption::Unknown(30, vec![0xab])];
+        assert_eq!(header.header_length(), TCPHeader::LENGTH + 12 + 4);
+
+        let mut seg = TCPSegment::new(header.clone(), Buffer::new(vec![]));
+        let bytes = seg.serialize(0);
+        assert_eq!(bytes.len(), 36);
+        assert_eq!(&bytes[32..36], &[30, 3, 0xab, 0]);
+
+        let (parsed, doff) = round_trip(&header);
+        assert_eq!(doff, 9);
+        assert_eq!(parsed.header().unknown_options, header.unknown_options);
+        assert!(*parsed.header() == header);
+    }
+
+    {
+        // SACK blocks are capped to what fits next to the timestamps
+        let mut header = TCPHeader::new();
+        header.ack = true;
+        header.timestamps = Some((1, 2));
+        header.sack_blocks = (0..4)
+            .map(|i| {
+                (
+                    WrappingInt32::new(i * 100),
+                    WrappingInt32::new(i * 100 + 50),
+                )
+            })
+            .collect();
+        let (parsed, _) = round_trip(&header);
+        assert_eq!(parsed.header().header_length(), TCPHeader::MAX_LENGTH);
+        assert_eq!(
+            parsed.header().sack_blocks,
+            header.sack_blocks[..3].to_vec()
+        );
+    }
+
+    {
+        // what doesn't fit in the option space is dropped instead of overflowing it
+        let mut header = TCPHeader::new();
+        header.syn = true;
+        header.mss = Some(1460);
+        header.wscale = Some(7);
+        header.sack_permitted = true;
+        header.timestamps = Some((1, 2));
+        header.sack_blocks = vec![(WrappingInt32::new(100), WrappingInt32::new(200)); 2];
+        header.unknown_options = vec![
+            TCPOption::Unknown(30, vec![0; 8]),
+            TCPOption::Unknown(31, vec![0; 2]),
+        ];
+        assert_eq!(header.header_length(), TCPHeader::MAX_LENGTH);
+        let (parsed, doff) = round_trip(&header);
+        assert_eq!(doff, 15);
+        assert_eq!(
+            parsed.header().sack_blocks,
+            header.sack_blocks[..1].to_vec()
+        );
+        assert_eq!(
+            parsed.header().unknown_options,
+            vec![TCPOption::Unknown(31, vec![0; 2])]
+        );
+
+        header.unknown_options = vec![TCPOption::Unknown(30, vec![0; 60])];
+        let (parsed, _) = round_trip(&header);
+        assert!(parsed.header().unknown_options.is_empty());
+    }
+
+    {
+        let mut header = TCPHeader::new();
+        header.ack = true;
+        header.unknown_options = vec![TCPOption::Unknown(30, vec![0, 0])];
+        assert!(parse_patched(&header, [30, 4, 0, 0]).is_ok());
+        // MSS with a length of 3
+        assert_eq!(
+            parse_patched(&header, [2, 3, 0x1c, 0x01]).err(),
+            Some(ParseResult::BadOption)
+        );
+        // length past the end of the header
+        assert_eq!(
+            parse_patched(&header, [2, 40, 0x1b, 0xdc]).err(),
+            Some(ParseResult::BadOption)
+        );
+    }
+
+    {
+        // the header length is worked out without serializing, it has to match what is sent
+        for mask in 0..32 {
+            for blocks in 0..5 {
+                let mut header = TCPHeader::new();
+                header.mss = (mask & 1 != 0).then_some(1460);
+                header.wscale = (mask & 2 != 0).then_some(7);
+                header.sack_permitted = mask & 4 != 0;
+                header.timestamps = (mask & 8 != 0).then_some((1, 2));
+                if mask & 16 != 0 {
+                    header.unknown_options = vec![TCPOption::Unknown(30, vec![0; 6])];
+                }
+                header.sack_blocks =
+                    vec![(WrappingInt32::new(100), WrappingInt32::new(200)); blocks];
+                let mut seg = TCPSegment::new(header.clone(), Buffer::new(vec![]));
+                assert_eq!(header.header_length(), seg.serialize(0).len());
+            }
+        }
+    }
+}